@@ -0,0 +1,110 @@
+//! In-memory image codecs that do not depend on the standard library.
+//!
+//! Each submodule decodes from and encodes to byte slices, so the formats can be used
+//! on embedded targets where no file system is available. The `io` module provides
+//! file-based wrappers around these codecs when the `std` feature is enabled.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use core::fmt;
+
+pub mod bmp;
+pub mod exif;
+pub mod netpbm;
+pub mod qoi;
+pub mod tga;
+
+/// Errors that can occur while decoding or encoding with the in-memory codecs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// The data ended before the image was complete
+    UnexpectedEof,
+    /// The data is malformed
+    InvalidData(String),
+    /// The data uses a feature that is not supported
+    Unsupported(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnexpectedEof => write!(f, "Unexpected end of data"),
+            CodecError::InvalidData(msg) => write!(f, "Invalid data: {}", msg),
+            CodecError::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodecError {}
+
+/// Reads a little-endian `u16` at `offset`.
+fn read_u16_le(bytes: &[u8], offset: usize) -> Result<u16, CodecError> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(CodecError::UnexpectedEof)
+}
+
+/// Reads a little-endian `u32` at `offset`.
+fn read_u32_le(bytes: &[u8], offset: usize) -> Result<u32, CodecError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(CodecError::UnexpectedEof)
+}
+
+/// Appends the decimal representation of `value` to `out`.
+fn push_decimal(out: &mut Vec<u8>, value: u32) {
+    let mut digits = [0u8; 10];
+    let mut len = 0;
+    let mut value = value;
+    loop {
+        digits[len] = b'0' + (value % 10) as u8;
+        len += 1;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    out.extend(digits[..len].iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_error_display() {
+        assert_eq!(
+            format!("{}", CodecError::UnexpectedEof),
+            "Unexpected end of data"
+        );
+        assert_eq!(
+            format!("{}", CodecError::InvalidData("bad header".to_string())),
+            "Invalid data: bad header"
+        );
+    }
+
+    #[test]
+    fn test_push_decimal() {
+        let mut out = Vec::new();
+        push_decimal(&mut out, 0);
+        out.push(b' ');
+        push_decimal(&mut out, 65535);
+        assert_eq!(out, b"0 65535");
+    }
+
+    #[test]
+    fn test_read_le() {
+        let bytes = [0x34, 0x12, 0x78, 0x56];
+        assert_eq!(read_u16_le(&bytes, 0), Ok(0x1234));
+        assert_eq!(read_u32_le(&bytes, 0), Ok(0x5678_1234));
+        assert_eq!(read_u32_le(&bytes, 1), Err(CodecError::UnexpectedEof));
+    }
+}
@@ -0,0 +1,579 @@
+//! Windows bitmap (BMP) codec.
+//!
+//! Decodes 1, 4 and 8-bit paletted images (uncompressed or RLE8), 16-bit and 32-bit
+//! bitfield images, and 24-bit and 32-bit true-color images, stored either bottom-up
+//! or top-down. Paletted images whose palette only contains gray levels decode to a
+//! grayscale matrix; all other images decode to RGB. Alpha channels are dropped.
+//!
+//! Encoding writes grayscale images as 8-bit paletted bitmaps and RGB images as
+//! 24-bit bitmaps.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::codec::bmp;
+//! use cv_rusty::{DynamicImage, Matrix3};
+//!
+//! let image = Matrix3::new(2, 1, vec![255, 0, 0, 0, 0, 255]);
+//! let bytes = bmp::encode(&image).unwrap();
+//! assert!(bytes.starts_with(b"BM"));
+//!
+//! match bmp::decode(&bytes).unwrap() {
+//!     DynamicImage::Rgb8(decoded) => assert_eq!(decoded.data(), image.data()),
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::ToString;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::{read_u16_le, read_u32_le, CodecError};
+use crate::matrix::{DynamicImage, Matrix1, Matrix3, WritableImage};
+
+/// Size of the `BITMAPFILEHEADER`.
+const FILE_HEADER_SIZE: usize = 14;
+/// Size of the `BITMAPINFOHEADER`.
+const INFO_HEADER_SIZE: usize = 40;
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Longest run of pixels a single RLE8 packet can produce.
+const MAX_RLE_RUN: usize = 255;
+
+/// Decodes a BMP image.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded image data
+///
+/// # Returns
+///
+/// A `DynamicImage::Gray8` for paletted images with a gray palette, otherwise a
+/// `DynamicImage::Rgb8`.
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage, CodecError> {
+    if !bytes.starts_with(b"BM") {
+        return Err(CodecError::InvalidData("Missing BMP signature".to_string()));
+    }
+
+    let data_offset = read_u32_le(bytes, 10)? as usize;
+    let header_size = read_u32_le(bytes, FILE_HEADER_SIZE)? as usize;
+
+    // BITMAPCOREHEADER (OS/2) uses 16-bit dimensions and 3-byte palette entries
+    let (width, height, bpp, compression, colors_used, entry_size) = if header_size == 12 {
+        (
+            read_u16_le(bytes, 18)? as i32,
+            read_u16_le(bytes, 20)? as i16 as i32,
+            read_u16_le(bytes, 24)?,
+            BI_RGB,
+            0,
+            3,
+        )
+    } else if header_size >= INFO_HEADER_SIZE {
+        (
+            read_u32_le(bytes, 18)? as i32,
+            read_u32_le(bytes, 22)? as i32,
+            read_u16_le(bytes, 28)?,
+            read_u32_le(bytes, 30)?,
+            read_u32_le(bytes, 46)? as usize,
+            4,
+        )
+    } else {
+        return Err(CodecError::InvalidData(format!(
+            "Invalid BMP header size {}",
+            header_size
+        )));
+    };
+
+    if width <= 0 || height == 0 {
+        return Err(CodecError::InvalidData(
+            "Invalid BMP dimensions".to_string(),
+        ));
+    }
+    let top_down = height < 0;
+    let width = width as usize;
+    let height = height.unsigned_abs() as usize;
+    width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(3))
+        .ok_or_else(|| CodecError::InvalidData("BMP dimensions too large".to_string()))?;
+
+    // Bitfield masks directly follow the 40-byte header, whether or not they are
+    // part of a larger header version
+    let mut palette_offset = FILE_HEADER_SIZE + header_size;
+    if header_size == INFO_HEADER_SIZE {
+        match compression {
+            BI_BITFIELDS => palette_offset += 12,
+            BI_ALPHABITFIELDS => palette_offset += 16,
+            _ => {}
+        }
+    }
+
+    let target = |file_row: usize| {
+        if top_down {
+            file_row
+        } else {
+            height - 1 - file_row
+        }
+    };
+
+    match (bpp, compression) {
+        (1 | 4 | 8, BI_RGB) | (8, BI_RLE8) => {
+            // The palette can never extend into the pixel data, whatever the header says
+            let available = data_offset.saturating_sub(palette_offset) / entry_size;
+            let count = match colors_used {
+                0 => 1 << bpp,
+                n => n.min(256),
+            }
+            .min(available);
+            let palette: Vec<(u8, u8, u8)> = (0..count)
+                .map(|i| {
+                    let offset = palette_offset + i * entry_size;
+                    bytes
+                        .get(offset..offset + 3)
+                        .map(|bgr| (bgr[2], bgr[1], bgr[0]))
+                        .ok_or(CodecError::UnexpectedEof)
+                })
+                .collect::<Result<_, _>>()?;
+
+            if compression == BI_RLE8 {
+                // Each input byte can expand to at most one run, which bounds the
+                // allocation for truncated or forged headers
+                let max_pixels = bytes
+                    .len()
+                    .saturating_sub(data_offset)
+                    .saturating_mul(MAX_RLE_RUN);
+                if width * height > max_pixels {
+                    return Err(CodecError::UnexpectedEof);
+                }
+            }
+            let stride = match compression {
+                BI_RLE8 => 0,
+                _ => raster_stride(bytes, data_offset, width, height, bpp)?,
+            };
+
+            let mut indices = vec![0u8; width * height];
+            if compression == BI_RLE8 {
+                decode_rle8(bytes, data_offset, width, height, &target, &mut indices)?;
+            } else {
+                let mask = ((1u16 << bpp) - 1) as u8;
+                for file_row in 0..height {
+                    let row = row_bytes(bytes, data_offset, stride, file_row)?;
+                    let out = &mut indices[target(file_row) * width..][..width];
+                    for (x, index) in out.iter_mut().enumerate() {
+                        let bit = x * bpp as usize;
+                        let shift = 8 - bpp as usize - bit % 8;
+                        *index = (row[bit / 8] >> shift) & mask;
+                    }
+                }
+            }
+
+            // Out-of-range indices are treated as black, like most viewers do
+            let color = |index: u8| palette.get(index as usize).copied().unwrap_or((0, 0, 0));
+            if palette.iter().all(|&(r, g, b)| r == g && g == b) {
+                let data = indices.iter().map(|&i| color(i).0).collect();
+                Ok(DynamicImage::Gray8(Matrix1::new(width, height, data)))
+            } else {
+                let data = indices
+                    .iter()
+                    .flat_map(|&i| {
+                        let (r, g, b) = color(i);
+                        [r, g, b]
+                    })
+                    .collect();
+                Ok(DynamicImage::Rgb8(Matrix3::new(width, height, data)))
+            }
+        }
+        (24, BI_RGB) => {
+            let stride = raster_stride(bytes, data_offset, width, height, 24)?;
+            let mut data = vec![0u8; width * height * 3];
+            for file_row in 0..height {
+                let row = row_bytes(bytes, data_offset, stride, file_row)?;
+                let out = &mut data[target(file_row) * width * 3..][..width * 3];
+                for (rgb, bgr) in out.chunks_exact_mut(3).zip(row.chunks_exact(3)) {
+                    rgb.copy_from_slice(&[bgr[2], bgr[1], bgr[0]]);
+                }
+            }
+            Ok(DynamicImage::Rgb8(Matrix3::new(width, height, data)))
+        }
+        (16 | 32, BI_RGB | BI_BITFIELDS | BI_ALPHABITFIELDS) => {
+            let masks = if compression == BI_RGB {
+                if bpp == 16 {
+                    [0x7C00, 0x03E0, 0x001F]
+                } else {
+                    [0x00FF_0000, 0x0000_FF00, 0x0000_00FF]
+                }
+            } else {
+                [
+                    read_u32_le(bytes, 54)?,
+                    read_u32_le(bytes, 58)?,
+                    read_u32_le(bytes, 62)?,
+                ]
+            };
+
+            let pixel_bytes = bpp as usize / 8;
+            let stride = raster_stride(bytes, data_offset, width, height, bpp)?;
+            let mut data = vec![0u8; width * height * 3];
+            for file_row in 0..height {
+                let row = row_bytes(bytes, data_offset, stride, file_row)?;
+                let out = &mut data[target(file_row) * width * 3..][..width * 3];
+                for (rgb, px) in out.chunks_exact_mut(3).zip(row.chunks_exact(pixel_bytes)) {
+                    let value = if pixel_bytes == 2 {
+                        u16::from_le_bytes([px[0], px[1]]) as u32
+                    } else {
+                        u32::from_le_bytes([px[0], px[1], px[2], px[3]])
+                    };
+                    for (channel, &mask) in rgb.iter_mut().zip(masks.iter()) {
+                        *channel = extract_channel(value, mask);
+                    }
+                }
+            }
+            Ok(DynamicImage::Rgb8(Matrix3::new(width, height, data)))
+        }
+        _ => Err(CodecError::Unsupported(format!(
+            "BMP with {} bits per pixel and compression {}",
+            bpp, compression
+        ))),
+    }
+}
+
+/// Encodes an image as a BMP.
+///
+/// Grayscale images are written as 8-bit paletted bitmaps with a gray palette and
+/// RGB images as 24-bit bitmaps.
+///
+/// # Arguments
+///
+/// * `image` - The image to encode (`Matrix1` or `Matrix3`)
+///
+/// # Returns
+///
+/// The encoded file contents, or `CodecError::Unsupported` for 16-bit images.
+pub fn encode<I: WritableImage>(image: &I) -> Result<Vec<u8>, CodecError> {
+    if image.bit_depth() != 8 {
+        return Err(CodecError::Unsupported(format!(
+            "{}-bit BMP",
+            image.bit_depth()
+        )));
+    }
+
+    let width = image.width();
+    let height = image.height();
+    let channels = image.channels();
+    let bpp = if channels == 1 { 8 } else { 24 };
+    let palette_size = if channels == 1 { 256 * 4 } else { 0 };
+    let stride = row_stride(width, bpp);
+    let data_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE + palette_size;
+    let file_size = data_offset + stride * height;
+
+    let mut out = Vec::with_capacity(file_size);
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&(file_size as u32).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+
+    out.extend_from_slice(&(INFO_HEADER_SIZE as u32).to_le_bytes());
+    out.extend_from_slice(&(width as i32).to_le_bytes());
+    out.extend_from_slice(&(height as i32).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&bpp.to_le_bytes());
+    out.extend_from_slice(&BI_RGB.to_le_bytes());
+    out.extend_from_slice(&((stride * height) as u32).to_le_bytes());
+    // 72 DPI in pixels per metre
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&[0; 8]);
+
+    if channels == 1 {
+        for level in 0..=255u8 {
+            out.extend_from_slice(&[level, level, level, 0]);
+        }
+    }
+
+    // Rows are stored bottom-up, in BGR order and padded to 4 bytes
+    let samples = image.sample_bytes();
+    let row_len = width * channels;
+    let padding = stride - width * bpp as usize / 8;
+    for y in (0..height).rev() {
+        let row = &samples[y * row_len..][..row_len];
+        if channels == 1 {
+            out.extend_from_slice(row);
+        } else {
+            for rgb in row.chunks_exact(3) {
+                out.extend_from_slice(&[rgb[2], rgb[1], rgb[0]]);
+            }
+        }
+        out.extend(core::iter::repeat_n(0, padding));
+    }
+
+    Ok(out)
+}
+
+/// Returns the number of bytes per row, padded to a multiple of 4.
+fn row_stride(width: usize, bpp: u16) -> usize {
+    (width * bpp as usize).div_ceil(32) * 4
+}
+
+/// Returns the row stride of an uncompressed bitmap after checking that all of its
+/// rows are present in `bytes`.
+fn raster_stride(
+    bytes: &[u8],
+    data_offset: usize,
+    width: usize,
+    height: usize,
+    bpp: u16,
+) -> Result<usize, CodecError> {
+    let stride = width
+        .checked_mul(bpp as usize)
+        .map(|bits| bits.div_ceil(32) * 4)
+        .ok_or_else(|| CodecError::InvalidData("BMP dimensions too large".to_string()))?;
+    let end = stride
+        .checked_mul(height)
+        .and_then(|len| len.checked_add(data_offset))
+        .ok_or_else(|| CodecError::InvalidData("BMP dimensions too large".to_string()))?;
+    if end > bytes.len() {
+        return Err(CodecError::UnexpectedEof);
+    }
+    Ok(stride)
+}
+
+/// Returns the bytes of row `file_row` of an uncompressed bitmap.
+fn row_bytes(
+    bytes: &[u8],
+    data_offset: usize,
+    stride: usize,
+    file_row: usize,
+) -> Result<&[u8], CodecError> {
+    file_row
+        .checked_mul(stride)
+        .and_then(|offset| offset.checked_add(data_offset))
+        .and_then(|start| bytes.get(start..start.checked_add(stride)?))
+        .ok_or(CodecError::UnexpectedEof)
+}
+
+/// Extracts the channel selected by `mask` from `value`, scaled to 8 bits.
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let max = (mask >> mask.trailing_zeros()) as u64;
+    let channel = ((value & mask) >> mask.trailing_zeros()) as u64;
+    ((channel * 255 + max / 2) / max) as u8
+}
+
+/// Decodes RLE8-compressed indices into `indices` (stored top-down).
+fn decode_rle8(
+    bytes: &[u8],
+    data_offset: usize,
+    width: usize,
+    height: usize,
+    target: &dyn Fn(usize) -> usize,
+    indices: &mut [u8],
+) -> Result<(), CodecError> {
+    let byte = |pos: usize| bytes.get(pos).copied().ok_or(CodecError::UnexpectedEof);
+    let mut put = |x: usize, row: usize, index: u8| {
+        if x < width && row < height {
+            indices[target(row) * width + x] = index;
+        }
+    };
+
+    let mut pos = data_offset;
+    let (mut x, mut row) = (0, 0);
+    loop {
+        let count = byte(pos)? as usize;
+        let value = byte(pos + 1)?;
+        pos += 2;
+
+        if count > 0 {
+            for _ in 0..count {
+                put(x, row, value);
+                x += 1;
+            }
+            continue;
+        }
+
+        match value {
+            // End of line
+            0 => {
+                x = 0;
+                row += 1;
+            }
+            // End of bitmap
+            1 => return Ok(()),
+            // Delta
+            2 => {
+                x += byte(pos)? as usize;
+                row += byte(pos + 1)? as usize;
+                pos += 2;
+            }
+            // Absolute run, padded to an even number of bytes
+            n => {
+                let n = n as usize;
+                let run = bytes.get(pos..pos + n).ok_or(CodecError::UnexpectedEof)?;
+                for &index in run {
+                    put(x, row, index);
+                    x += 1;
+                }
+                pos += n + (n & 1);
+            }
+        }
+
+        if row >= height {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a BMP with a `BITMAPINFOHEADER`, followed by `extra` (masks and palette).
+    fn build(
+        width: i32,
+        height: i32,
+        bpp: u16,
+        compression: u32,
+        extra: &[u8],
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let data_offset = (FILE_HEADER_SIZE + INFO_HEADER_SIZE + extra.len()) as u32;
+        let mut out = Vec::new();
+        out.extend_from_slice(b"BM");
+        out.extend_from_slice(&(data_offset + pixels.len() as u32).to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&data_offset.to_le_bytes());
+        out.extend_from_slice(&40u32.to_le_bytes());
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&bpp.to_le_bytes());
+        out.extend_from_slice(&compression.to_le_bytes());
+        out.extend_from_slice(&[0; 20]);
+        out.extend_from_slice(extra);
+        out.extend_from_slice(pixels);
+        out
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let gray = Matrix1::new(3, 2, vec![0, 10, 20, 30, 40, 255]);
+        match decode(&encode(&gray).unwrap()).unwrap() {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), gray.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+
+        let rgb = Matrix3::new(3, 2, (0..18).collect());
+        let bytes = encode(&rgb).unwrap();
+        // 3 pixels * 3 bytes padded to 12 bytes per row
+        assert_eq!(bytes.len(), 54 + 24);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), rgb.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+
+        let deep = crate::Matrix1U16::zeros(1, 1);
+        assert!(matches!(encode(&deep), Err(CodecError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_paletted_top_down() {
+        // 1-bit, top-down: black/white palette
+        let palette = [0, 0, 0, 0, 255, 255, 255, 0];
+        let bytes = build(
+            3,
+            -2,
+            1,
+            BI_RGB,
+            &palette,
+            &[0b1010_0000, 0, 0, 0, 0b0110_0000, 0, 0, 0],
+        );
+        match decode(&bytes).unwrap() {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), &[255, 0, 255, 0, 255, 255]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+
+        // 4-bit, bottom-up, color palette
+        let palette = [0, 0, 255, 0, 0, 255, 0, 0];
+        let bytes = build(2, 2, 4, BI_RGB, &palette, &[0x01, 0, 0, 0, 0x10, 0, 0, 0]);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Rgb8(m) => {
+                assert_eq!(m.get_pixel(0, 0), Some((0, 255, 0)));
+                assert_eq!(m.get_pixel(1, 0), Some((255, 0, 0)));
+                assert_eq!(m.get_pixel(0, 1), Some((255, 0, 0)));
+            }
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rle8() {
+        let palette: Vec<u8> = (0..=255u8).flat_map(|v| [v, v, v, 0]).collect();
+        let pixels = [
+            3, 7, 0, 0, // bottom row: 7 7 7, end of line
+            0, 3, 1, 2, 3, 0, // top row: absolute run 1 2 3 (padded)
+            0, 1, // end of bitmap
+        ];
+        let bytes = build(3, 2, 8, BI_RLE8, &palette, &pixels);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), &[1, 2, 3, 7, 7, 7]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bitfields_with_alpha() {
+        // 32-bit RGBA (alpha in the low byte), top-down
+        let masks: Vec<u8> = [0xFF00_0000u32, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF]
+            .iter()
+            .flat_map(|m| m.to_le_bytes())
+            .collect();
+        let pixel = 0x1020_3080u32.to_le_bytes();
+        let bytes = build(1, -1, 32, BI_ALPHABITFIELDS, &masks, &pixel);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), &[0x10, 0x20, 0x30]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+
+        // 16-bit default 5-5-5
+        let bytes = build(1, 1, 16, BI_RGB, &[], &[0x1F, 0x7C, 0, 0]);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), &[255, 0, 255]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        assert!(matches!(decode(b"PNG"), Err(CodecError::InvalidData(_))));
+        let truncated = build(4, 4, 24, BI_RGB, &[], &[0; 10]);
+        assert!(matches!(decode(&truncated), Err(CodecError::UnexpectedEof)));
+        let rle4 = build(4, 4, 4, 2, &[], &[]);
+        assert!(matches!(decode(&rle4), Err(CodecError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_oversized_dimensions() {
+        // Headers claiming huge images fail before the pixel buffer is allocated
+        let palette = [0u8; 8];
+        for (bpp, compression, extra) in [
+            (24, BI_RGB, &[][..]),
+            (32, BI_RGB, &[][..]),
+            (8, BI_RGB, &palette[..]),
+            (8, BI_RLE8, &palette[..]),
+        ] {
+            let bytes = build(0x7fff_ffff, 0x7fff_ffff, bpp, compression, extra, &[0, 1]);
+            assert!(matches!(decode(&bytes), Err(CodecError::UnexpectedEof)));
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! EXIF metadata parsing.
+//!
+//! Extracts the orientation, capture time, camera make and model, and GPS position
+//! from an EXIF block. The block is usually found in the APP1 segment of a JPEG file
+//! (see [`find_in_jpeg`]), but any TIFF-structured EXIF payload can be parsed.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cv_rusty::codec::exif;
+//!
+//! let jpeg = std::fs::read("photo.jpg").unwrap();
+//! if let Some(block) = exif::find_in_jpeg(&jpeg) {
+//!     let metadata = exif::parse(block).unwrap();
+//!     println!("Taken with {:?} at {:?}", metadata.model, metadata.date_time);
+//! }
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+use super::CodecError;
+use crate::transform::Orientation;
+
+const TAG_MAKE: u16 = 0x010F;
+const TAG_MODEL: u16 = 0x0110;
+const TAG_ORIENTATION: u16 = 0x0112;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_GPS_IFD: u16 = 0x8825;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+
+const TAG_GPS_LATITUDE_REF: u16 = 1;
+const TAG_GPS_LATITUDE: u16 = 2;
+const TAG_GPS_LONGITUDE_REF: u16 = 3;
+const TAG_GPS_LONGITUDE: u16 = 4;
+const TAG_GPS_ALTITUDE_REF: u16 = 5;
+const TAG_GPS_ALTITUDE: u16 = 6;
+
+const TYPE_BYTE: u16 = 1;
+const TYPE_ASCII: u16 = 2;
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+
+/// Metadata extracted from an EXIF block.
+///
+/// Fields are `None` when the corresponding tag is absent or malformed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExifMetadata {
+    /// How the stored pixels must be transformed to appear upright
+    pub orientation: Option<Orientation>,
+    /// Capture time as `YYYY:MM:DD HH:MM:SS`, preferring the original capture time
+    /// over the file modification time
+    pub date_time: Option<String>,
+    /// Camera manufacturer
+    pub make: Option<String>,
+    /// Camera model
+    pub model: Option<String>,
+    /// Location where the photo was taken
+    pub gps: Option<GpsPosition>,
+}
+
+/// A GPS position in decimal degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition {
+    /// Latitude in degrees, positive north of the equator
+    pub latitude: f64,
+    /// Longitude in degrees, positive east of Greenwich
+    pub longitude: f64,
+    /// Altitude in metres relative to sea level, if recorded
+    pub altitude: Option<f64>,
+}
+
+/// Locates the EXIF block in a JPEG file.
+///
+/// # Arguments
+///
+/// * `jpeg` - The complete JPEG file contents
+///
+/// # Returns
+///
+/// The EXIF payload of the first APP1 `Exif` segment (without the `Exif\0\0` prefix),
+/// or `None` if the file has no EXIF block.
+pub fn find_in_jpeg(jpeg: &[u8]) -> Option<&[u8]> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= jpeg.len() {
+        if jpeg[pos] != 0xFF {
+            return None;
+        }
+        let marker = jpeg[pos + 1];
+        // Fill bytes may precede a marker
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        // Start of scan: metadata segments always come before the image data
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+
+        let length = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+        let segment = jpeg.get(pos + 4..pos + 2 + length)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return Some(&segment[6..]);
+        }
+        pos += 2 + length;
+    }
+
+    None
+}
+
+/// Parses an EXIF block.
+///
+/// # Arguments
+///
+/// * `block` - The EXIF payload, with or without the leading `Exif\0\0` marker
+///
+/// # Returns
+///
+/// The extracted metadata, or a `CodecError` if the TIFF header is invalid.
+/// Individual malformed tags are skipped.
+pub fn parse(block: &[u8]) -> Result<ExifMetadata, CodecError> {
+    let data = block.strip_prefix(b"Exif\0\0").unwrap_or(block);
+    let reader = match data.get(..4) {
+        Some([b'I', b'I', 42, 0]) => Reader {
+            data,
+            little_endian: true,
+        },
+        Some([b'M', b'M', 0, 42]) => Reader {
+            data,
+            little_endian: false,
+        },
+        Some(_) => return Err(CodecError::InvalidData("Invalid EXIF header".to_string())),
+        None => return Err(CodecError::UnexpectedEof),
+    };
+
+    let mut metadata = ExifMetadata::default();
+    let mut date_time = None;
+    let mut date_time_original = None;
+    let mut exif_ifd = None;
+    let mut gps_ifd = None;
+
+    let ifd0 = reader.u32(4).ok_or(CodecError::UnexpectedEof)? as usize;
+    for entry in reader.entries(ifd0) {
+        match entry.tag {
+            TAG_ORIENTATION => {
+                metadata.orientation = reader.unsigned(&entry).and_then(Orientation::from_exif)
+            }
+            TAG_MAKE => metadata.make = reader.ascii(&entry),
+            TAG_MODEL => metadata.model = reader.ascii(&entry),
+            TAG_DATE_TIME => date_time = reader.ascii(&entry),
+            TAG_EXIF_IFD => exif_ifd = reader.offset(&entry),
+            TAG_GPS_IFD => gps_ifd = reader.offset(&entry),
+            _ => {}
+        }
+    }
+
+    if let Some(offset) = exif_ifd {
+        for entry in reader.entries(offset) {
+            if entry.tag == TAG_DATE_TIME_ORIGINAL {
+                date_time_original = reader.ascii(&entry);
+            }
+        }
+    }
+    metadata.date_time = date_time_original.or(date_time);
+
+    if let Some(offset) = gps_ifd {
+        metadata.gps = reader.gps(offset);
+    }
+
+    Ok(metadata)
+}
+
+/// A directory entry.
+struct Entry {
+    tag: u16,
+    field_type: u16,
+    count: usize,
+    /// Offset of the value, which is stored inline when it fits in four bytes
+    value_offset: usize,
+}
+
+/// Endian-aware reader over a TIFF-structured block.
+struct Reader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl Reader<'_> {
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let b = self.data.get(offset..offset + 2)?;
+        Some(if self.little_endian {
+            u16::from_le_bytes([b[0], b[1]])
+        } else {
+            u16::from_be_bytes([b[0], b[1]])
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let b = self.data.get(offset..offset + 4)?;
+        Some(if self.little_endian {
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        } else {
+            u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+        })
+    }
+
+    /// Returns the entries of the directory at `offset` (empty if out of bounds).
+    fn entries(&self, offset: usize) -> impl Iterator<Item = Entry> + '_ {
+        let count = self.u16(offset).unwrap_or(0) as usize;
+        (0..count).map_while(move |i| {
+            let base = offset + 2 + i * 12;
+            let field_type = self.u16(base + 2)?;
+            let count = self.u32(base + 4)? as usize;
+            let size = match field_type {
+                TYPE_SHORT => 2,
+                TYPE_LONG => 4,
+                TYPE_RATIONAL => 8,
+                _ => 1,
+            };
+            let value_offset = if count.saturating_mul(size) <= 4 {
+                base + 8
+            } else {
+                self.u32(base + 8)? as usize
+            };
+            Some(Entry {
+                tag: self.u16(base)?,
+                field_type,
+                count,
+                value_offset,
+            })
+        })
+    }
+
+    /// Reads the first value of a BYTE, SHORT or LONG entry.
+    fn unsigned(&self, entry: &Entry) -> Option<u16> {
+        match entry.field_type {
+            TYPE_BYTE => self.data.get(entry.value_offset).map(|&v| v as u16),
+            TYPE_SHORT => self.u16(entry.value_offset),
+            TYPE_LONG => self.u32(entry.value_offset).map(|v| v as u16),
+            _ => None,
+        }
+    }
+
+    /// Reads a sub-directory offset.
+    fn offset(&self, entry: &Entry) -> Option<usize> {
+        match entry.field_type {
+            TYPE_LONG => self.u32(entry.value_offset).map(|v| v as usize),
+            _ => None,
+        }
+    }
+
+    /// Reads an ASCII entry, trimming the NUL terminator and padding.
+    fn ascii(&self, entry: &Entry) -> Option<String> {
+        if entry.field_type != TYPE_ASCII {
+            return None;
+        }
+        let bytes = self
+            .data
+            .get(entry.value_offset..entry.value_offset + entry.count)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        let text = core::str::from_utf8(&bytes[..end]).ok()?.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// Reads the `index`-th value of a RATIONAL entry.
+    fn rational(&self, entry: &Entry, index: usize) -> Option<f64> {
+        if entry.field_type != TYPE_RATIONAL || index >= entry.count {
+            return None;
+        }
+        let offset = entry.value_offset + index * 8;
+        let numerator = self.u32(offset)? as f64;
+        let denominator = self.u32(offset + 4)? as f64;
+        (denominator != 0.0).then(|| numerator / denominator)
+    }
+
+    /// Reads the position from the GPS directory at `offset`.
+    fn gps(&self, offset: usize) -> Option<GpsPosition> {
+        let mut latitude = None;
+        let mut longitude = None;
+        let mut altitude = None;
+        let mut south = false;
+        let mut west = false;
+        let mut below_sea_level = false;
+
+        for entry in self.entries(offset) {
+            match entry.tag {
+                TAG_GPS_LATITUDE_REF => south = self.ascii(&entry).as_deref() == Some("S"),
+                TAG_GPS_LONGITUDE_REF => west = self.ascii(&entry).as_deref() == Some("W"),
+                TAG_GPS_LATITUDE => latitude = self.degrees(&entry),
+                TAG_GPS_LONGITUDE => longitude = self.degrees(&entry),
+                TAG_GPS_ALTITUDE_REF => below_sea_level = self.unsigned(&entry) == Some(1),
+                TAG_GPS_ALTITUDE => altitude = self.rational(&entry, 0),
+                _ => {}
+            }
+        }
+
+        Some(GpsPosition {
+            latitude: if south { -latitude? } else { latitude? },
+            longitude: if west { -longitude? } else { longitude? },
+            altitude: altitude.map(|a| if below_sea_level { -a } else { a }),
+        })
+    }
+
+    /// Converts a degrees/minutes/seconds RATIONAL triple to decimal degrees.
+    fn degrees(&self, entry: &Entry) -> Option<f64> {
+        let degrees = self.rational(entry, 0)?;
+        let minutes = self.rational(entry, 1).unwrap_or(0.0);
+        let seconds = self.rational(entry, 2).unwrap_or(0.0);
+        Some(degrees + minutes / 60.0 + seconds / 3600.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a big-endian EXIF block with IFD0, an Exif IFD and a GPS IFD.
+    fn sample_block() -> Vec<u8> {
+        fn entry(out: &mut Vec<u8>, tag: u16, field_type: u16, count: u32, value: u32) {
+            out.extend_from_slice(&tag.to_be_bytes());
+            out.extend_from_slice(&field_type.to_be_bytes());
+            out.extend_from_slice(&count.to_be_bytes());
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+
+        // Layout: header (8), IFD0 at 8 with 5 entries (2 + 60 + 4 = 66) -> 74,
+        // Exif IFD at 74 with 1 entry (18) -> 92, GPS IFD at 92 with 5 entries (66) -> 158,
+        // then data: model (8) at 158, date (20) at 166, lat (24) at 186, lon (24) at 210
+        let mut out = b"MM\0\x2a\0\0\0\x08".to_vec();
+        out.extend_from_slice(&5u16.to_be_bytes());
+        entry(
+            &mut out,
+            TAG_MAKE,
+            TYPE_ASCII,
+            4,
+            u32::from_be_bytes(*b"ACM\0"),
+        );
+        entry(&mut out, TAG_MODEL, TYPE_ASCII, 8, 158);
+        entry(&mut out, TAG_ORIENTATION, TYPE_SHORT, 1, 6 << 16);
+        entry(&mut out, TAG_EXIF_IFD, TYPE_LONG, 1, 74);
+        entry(&mut out, TAG_GPS_IFD, TYPE_LONG, 1, 92);
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        out.extend_from_slice(&1u16.to_be_bytes());
+        entry(&mut out, TAG_DATE_TIME_ORIGINAL, TYPE_ASCII, 20, 166);
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        out.extend_from_slice(&5u16.to_be_bytes());
+        entry(
+            &mut out,
+            TAG_GPS_LATITUDE_REF,
+            TYPE_ASCII,
+            2,
+            u32::from_be_bytes(*b"S\0\0\0"),
+        );
+        entry(&mut out, TAG_GPS_LATITUDE, TYPE_RATIONAL, 3, 186);
+        entry(
+            &mut out,
+            TAG_GPS_LONGITUDE_REF,
+            TYPE_ASCII,
+            2,
+            u32::from_be_bytes(*b"E\0\0\0"),
+        );
+        entry(&mut out, TAG_GPS_LONGITUDE, TYPE_RATIONAL, 3, 210);
+        entry(&mut out, TAG_GPS_ALTITUDE_REF, TYPE_BYTE, 1, 0);
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        assert_eq!(out.len(), 158);
+        out.extend_from_slice(b"Model 7\0");
+        out.extend_from_slice(b"2024:05:06 07:08:09\0");
+        for value in [(33, 1), (52, 1), (3000, 100), (151, 1), (12, 1), (0, 1)] {
+            out.extend_from_slice(&(value.0 as u32).to_be_bytes());
+            out.extend_from_slice(&(value.1 as u32).to_be_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse() {
+        let metadata = parse(&sample_block()).unwrap();
+        assert_eq!(metadata.orientation, Some(Orientation::Rotate90));
+        assert_eq!(metadata.make.as_deref(), Some("ACM"));
+        assert_eq!(metadata.model.as_deref(), Some("Model 7"));
+        assert_eq!(metadata.date_time.as_deref(), Some("2024:05:06 07:08:09"));
+
+        let gps = metadata.gps.unwrap();
+        assert!((gps.latitude - -(33.0 + 52.0 / 60.0 + 30.0 / 3600.0)).abs() < 1e-9);
+        assert!((gps.longitude - (151.0 + 12.0 / 60.0)).abs() < 1e-9);
+        assert_eq!(gps.altitude, None);
+    }
+
+    #[test]
+    fn test_find_in_jpeg() {
+        let block = sample_block();
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 4, 0, 0];
+        jpeg.extend_from_slice(&[0xFF, 0xE1]);
+        jpeg.extend_from_slice(&((block.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&block);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0, 2, 0xFF, 0xD9]);
+
+        assert_eq!(find_in_jpeg(&jpeg), Some(block.as_slice()));
+        assert_eq!(find_in_jpeg(&[0xFF, 0xD8, 0xFF, 0xDA, 0, 2]), None);
+        assert_eq!(find_in_jpeg(b"not a jpeg"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse(b"Exif\0\0"), Err(CodecError::UnexpectedEof));
+        assert!(matches!(
+            parse(b"XX\0\x2a\0\0\0\x08"),
+            Err(CodecError::InvalidData(_))
+        ));
+        // Truncated directories yield empty metadata rather than an error
+        assert_eq!(
+            parse(b"II\x2a\0\x08\0\0\0\x05\0").unwrap(),
+            ExifMetadata::default()
+        );
+    }
+}
@@ -0,0 +1,1032 @@
+//! Netpbm (PBM, PGM, PPM and PAM) codec.
+//!
+//! Supports the ASCII (`P1`-`P3`) and binary (`P4`-`P6`) variants of PBM, PGM and PPM,
+//! as well as PAM (`P7`). Any `maxval` up to 65535 is accepted: images with
+//! `maxval <= 255` decode to 8-bit matrices and larger `maxval` to 16-bit matrices,
+//! with samples rescaled to the full range of the output type.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::codec::netpbm::{self, PnmEncoding};
+//! use cv_rusty::{DynamicImage, Matrix1};
+//!
+//! let image = Matrix1::new(2, 1, vec![0, 255]);
+//! let bytes = netpbm::encode(&image, PnmEncoding::Binary);
+//! assert!(bytes.starts_with(b"P5"));
+//!
+//! match netpbm::decode(&bytes).unwrap() {
+//!     DynamicImage::Gray8(decoded) => assert_eq!(decoded.data(), image.data()),
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::ToString;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::{push_decimal, CodecError};
+use crate::matrix::{DynamicImage, Matrix1, Matrix1U16, Matrix3, Matrix3U16, WritableImage};
+
+/// Sample encoding used when writing PBM, PGM and PPM files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PnmEncoding {
+    /// Plain text samples (`P1`, `P2`, `P3`)
+    Ascii,
+    /// Raw binary samples (`P4`, `P5`, `P6`)
+    Binary,
+}
+
+/// Maximum line length of ASCII rasters, as recommended by the Netpbm specification.
+const MAX_ASCII_LINE: usize = 70;
+
+/// Decodes a Netpbm image of any variant.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded image data
+///
+/// # Returns
+///
+/// A `DynamicImage` holding grayscale data for PBM, PGM and single-channel PAM files
+/// and RGB data for PPM and three-channel PAM files. Alpha channels in PAM files are
+/// dropped. PBM images decode to 0 (black) and 255 (white).
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage, CodecError> {
+    if bytes.len() < 2 || bytes[0] != b'P' {
+        return Err(CodecError::InvalidData(
+            "Missing Netpbm signature".to_string(),
+        ));
+    }
+
+    let mut parser = Parser::new(bytes, 2);
+    match bytes[1] {
+        b'1' | b'4' => {
+            let (width, height, _) = parser.dimensions(true)?;
+            let bits = if bytes[1] == b'1' {
+                parser.ascii_bits(sample_count(&[width, height])?)?
+            } else {
+                parser.skip_single_whitespace()?;
+                parser.packed_bits(width, height)?
+            };
+            // PBM stores 1 for black
+            let data = bits.iter().map(|&b| if b == 1 { 0 } else { 255 }).collect();
+            Ok(DynamicImage::Gray8(Matrix1::new(width, height, data)))
+        }
+        magic @ (b'2' | b'3' | b'5' | b'6') => {
+            let (width, height, maxval) = parser.dimensions(false)?;
+            let channels = if magic == b'2' || magic == b'5' { 1 } else { 3 };
+            let count = sample_count(&[width, height, channels])?;
+            let samples = if magic == b'2' || magic == b'3' {
+                parser.ascii_samples(count)?
+            } else {
+                parser.skip_single_whitespace()?;
+                parser.binary_samples(count, maxval)?
+            };
+            build_image(width, height, channels, 0, maxval, samples)
+        }
+        b'7' => decode_pam(&mut parser),
+        other => Err(CodecError::Unsupported(format!(
+            "Netpbm variant P{}",
+            other as char
+        ))),
+    }
+}
+
+/// Encodes an image as PGM (single-channel) or PPM (RGB).
+///
+/// 8-bit images are written with `maxval` 255 and 16-bit images with `maxval` 65535.
+///
+/// # Arguments
+///
+/// * `image` - The image to encode (`Matrix1`, `Matrix3` or their 16-bit variants)
+/// * `encoding` - ASCII or binary samples
+///
+/// # Returns
+///
+/// The encoded file contents.
+pub fn encode<I: WritableImage>(image: &I, encoding: PnmEncoding) -> Vec<u8> {
+    let magic = match (image.channels(), encoding) {
+        (1, PnmEncoding::Ascii) => b"P2",
+        (1, PnmEncoding::Binary) => b"P5",
+        (_, PnmEncoding::Ascii) => b"P3",
+        (_, PnmEncoding::Binary) => b"P6",
+    };
+    let maxval = if image.bit_depth() == 16 { 65535 } else { 255 };
+
+    let mut out = Vec::new();
+    out.extend_from_slice(magic);
+    out.push(b'\n');
+    push_decimal(&mut out, image.width() as u32);
+    out.push(b' ');
+    push_decimal(&mut out, image.height() as u32);
+    out.push(b'\n');
+    push_decimal(&mut out, maxval);
+    out.push(b'\n');
+
+    let bytes = image.sample_bytes();
+    match encoding {
+        PnmEncoding::Binary => out.extend_from_slice(&bytes),
+        PnmEncoding::Ascii => {
+            let row_len = image.width() * image.channels();
+            let samples: Vec<u32> = if maxval == 65535 {
+                bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as u32)
+                    .collect()
+            } else {
+                bytes.iter().map(|&v| v as u32).collect()
+            };
+            if row_len > 0 {
+                for row in samples.chunks(row_len) {
+                    push_ascii_row(&mut out, row);
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Encodes a grayscale image as a bilevel PBM.
+///
+/// Pixels darker than `threshold` are written as black and all others as white.
+///
+/// # Arguments
+///
+/// * `image` - The grayscale image to encode
+/// * `threshold` - Gray level at or above which a pixel is white
+/// * `encoding` - ASCII (`P1`) or packed binary (`P4`) bits
+///
+/// # Returns
+///
+/// The encoded file contents.
+pub fn encode_pbm(image: &Matrix1, threshold: u8, encoding: PnmEncoding) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(match encoding {
+        PnmEncoding::Ascii => b"P1",
+        PnmEncoding::Binary => b"P4",
+    });
+    out.push(b'\n');
+    push_decimal(&mut out, image.width() as u32);
+    out.push(b' ');
+    push_decimal(&mut out, image.height() as u32);
+    out.push(b'\n');
+
+    if image.width() == 0 {
+        return out;
+    }
+
+    for row in image.data().chunks_exact(image.width()) {
+        let bits = row.iter().map(|&v| (v < threshold) as u8);
+        match encoding {
+            PnmEncoding::Ascii => {
+                let values: Vec<u32> = bits.map(|b| b as u32).collect();
+                push_ascii_row(&mut out, &values);
+            }
+            PnmEncoding::Binary => {
+                let mut packed = vec![0u8; image.width().div_ceil(8)];
+                for (x, bit) in bits.enumerate() {
+                    packed[x / 8] |= bit << (7 - x % 8);
+                }
+                out.extend_from_slice(&packed);
+            }
+        }
+    }
+
+    out
+}
+
+/// Encodes an image as PAM (`P7`) with a `GRAYSCALE` or `RGB` tuple type.
+///
+/// # Arguments
+///
+/// * `image` - The image to encode (`Matrix1`, `Matrix3` or their 16-bit variants)
+///
+/// # Returns
+///
+/// The encoded file contents.
+pub fn encode_pam<I: WritableImage>(image: &I) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"P7\nWIDTH ");
+    push_decimal(&mut out, image.width() as u32);
+    out.extend_from_slice(b"\nHEIGHT ");
+    push_decimal(&mut out, image.height() as u32);
+    out.extend_from_slice(b"\nDEPTH ");
+    push_decimal(&mut out, image.channels() as u32);
+    out.extend_from_slice(b"\nMAXVAL ");
+    push_decimal(&mut out, if image.bit_depth() == 16 { 65535 } else { 255 });
+    out.extend_from_slice(if image.channels() == 1 {
+        b"\nTUPLTYPE GRAYSCALE\nENDHDR\n".as_slice()
+    } else {
+        b"\nTUPLTYPE RGB\nENDHDR\n".as_slice()
+    });
+    out.extend_from_slice(&image.sample_bytes());
+    out
+}
+
+/// Appends one raster row of ASCII samples, wrapping long lines.
+fn push_ascii_row(out: &mut Vec<u8>, samples: &[u32]) {
+    let mut line_start = out.len();
+    for (i, &value) in samples.iter().enumerate() {
+        if i > 0 {
+            if out.len() - line_start + 6 > MAX_ASCII_LINE {
+                out.push(b'\n');
+                line_start = out.len();
+            } else {
+                out.push(b' ');
+            }
+        }
+        push_decimal(out, value);
+    }
+    out.push(b'\n');
+}
+
+/// Decodes the body of a PAM file after the `P7` signature.
+fn decode_pam(parser: &mut Parser) -> Result<DynamicImage, CodecError> {
+    let mut width = None;
+    let mut height = None;
+    let mut depth = None;
+    let mut maxval = None;
+
+    loop {
+        let line = parser.line()?;
+        let mut fields = line
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|f| !f.is_empty());
+        let Some(key) = fields.next() else {
+            continue;
+        };
+        if key.starts_with(b"#") {
+            continue;
+        }
+        match key {
+            b"ENDHDR" => break,
+            b"TUPLTYPE" => {}
+            b"WIDTH" | b"HEIGHT" | b"DEPTH" | b"MAXVAL" => {
+                let value = fields
+                    .next()
+                    .and_then(parse_decimal)
+                    .ok_or_else(|| CodecError::InvalidData("Malformed PAM header".to_string()))?;
+                match key {
+                    b"WIDTH" => width = Some(value),
+                    b"HEIGHT" => height = Some(value),
+                    b"DEPTH" => depth = Some(value),
+                    _ => maxval = Some(value),
+                }
+            }
+            _ => {
+                return Err(CodecError::InvalidData(
+                    "Unknown PAM header field".to_string(),
+                ))
+            }
+        }
+    }
+
+    let missing = || CodecError::InvalidData("Incomplete PAM header".to_string());
+    let width = width.ok_or_else(missing)?;
+    let height = height.ok_or_else(missing)?;
+    let depth = depth.ok_or_else(missing)?;
+    let maxval = maxval.ok_or_else(missing)?;
+
+    let (channels, alpha) = match depth {
+        1 => (1, 0),
+        2 => (1, 1),
+        3 => (3, 0),
+        4 => (3, 1),
+        _ => {
+            return Err(CodecError::Unsupported(format!("PAM depth {}", depth)));
+        }
+    };
+
+    let samples = parser.binary_samples(sample_count(&[width, height, depth])?, maxval)?;
+    build_image(width, height, channels, alpha, maxval, samples)
+}
+
+/// Builds an image from raw samples, dropping alpha and rescaling to 8 or 16 bits.
+fn build_image(
+    width: usize,
+    height: usize,
+    channels: usize,
+    alpha: usize,
+    maxval: usize,
+    samples: Vec<u16>,
+) -> Result<DynamicImage, CodecError> {
+    if maxval == 0 || maxval > 65535 {
+        return Err(CodecError::InvalidData(format!(
+            "Invalid maxval {}",
+            maxval
+        )));
+    }
+
+    if samples.iter().any(|&v| v as usize > maxval) {
+        return Err(CodecError::InvalidData("Sample exceeds maxval".to_string()));
+    }
+
+    let stride = channels + alpha;
+    let colors = samples
+        .chunks_exact(stride)
+        .flat_map(|pixel| pixel[..channels].iter().copied());
+
+    let image = if maxval <= 255 {
+        let data = colors
+            .map(|v| ((v as usize * 255 + maxval / 2) / maxval) as u8)
+            .collect();
+        if channels == 1 {
+            DynamicImage::Gray8(Matrix1::new(width, height, data))
+        } else {
+            DynamicImage::Rgb8(Matrix3::new(width, height, data))
+        }
+    } else {
+        let data = colors
+            .map(|v| ((v as usize * 65535 + maxval / 2) / maxval) as u16)
+            .collect();
+        if channels == 1 {
+            DynamicImage::Gray16(Matrix1U16::new(width, height, data))
+        } else {
+            DynamicImage::Rgb16(Matrix3U16::new(width, height, data))
+        }
+    };
+
+    Ok(image)
+}
+
+/// Parses an unsigned ASCII decimal number.
+fn parse_decimal(digits: &[u8]) -> Option<usize> {
+    if digits.is_empty() || digits.len() > 9 || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(
+        digits
+            .iter()
+            .fold(0, |acc, &d| acc * 10 + (d - b'0') as usize),
+    )
+}
+
+/// Widest row accepted by [`RowDecoder`], which bounds its row buffers for forged or
+/// corrupt headers.
+pub const MAX_ROW_WIDTH: usize = 1 << 20;
+
+/// Decodes a binary Netpbm image (`P4`, `P5` or `P6`) one row at a time.
+///
+/// Only one encoded and one decoded row are held in memory, so images far larger
+/// than the available RAM can be processed. The header is parsed from a byte slice,
+/// and the raster is then pulled row by row through a callback that fills a buffer
+/// from any source, such as flash, an SD card or a network socket.
+///
+/// PBM and PGM files produce single-channel rows and PPM files produce RGB rows.
+/// Samples are always scaled to 8 bits, including in files with a `maxval` above
+/// 255, and PBM pixels decode to 0 (black) and 255 (white). ASCII and PAM files
+/// cannot be decoded row by row; use [`decode`] for those.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::codec::{netpbm::RowDecoder, CodecError};
+///
+/// let bytes = b"P5\n2 2\n255\n\x01\x02\x03\x04";
+/// let (mut decoder, mut pos) = RowDecoder::new(bytes)?;
+/// let mut sums = Vec::new();
+/// while let Some(row) = decoder.next_row(|raw| {
+///     let chunk = bytes.get(pos..pos + raw.len()).ok_or(CodecError::UnexpectedEof)?;
+///     raw.copy_from_slice(chunk);
+///     pos += raw.len();
+///     Ok::<_, CodecError>(())
+/// })? {
+///     sums.push(row.iter().map(|&v| v as u32).sum::<u32>());
+/// }
+/// assert_eq!(sums, vec![3, 7]);
+/// # Ok::<(), CodecError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RowDecoder {
+    magic: u8,
+    width: usize,
+    height: usize,
+    maxval: usize,
+    raw: Vec<u8>,
+    row: Vec<u8>,
+    rows_read: usize,
+}
+
+impl RowDecoder {
+    /// Parses the header at the start of `bytes`.
+    ///
+    /// `bytes` only needs to hold the header, but it must include the single
+    /// whitespace byte that ends it.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The start of the encoded image
+    ///
+    /// # Returns
+    ///
+    /// The decoder together with the length of the header, which is the offset of the
+    /// first row. Fails with `CodecError::UnexpectedEof` if `bytes` ends inside the
+    /// header, `CodecError::Unsupported` for ASCII and PAM files, and
+    /// `CodecError::InvalidData` for malformed headers and rows wider than
+    /// [`MAX_ROW_WIDTH`].
+    pub fn new(bytes: &[u8]) -> Result<(Self, usize), CodecError> {
+        let magic = match bytes {
+            [b'P', magic @ (b'4' | b'5' | b'6'), ..] => *magic,
+            [b'P', magic @ b'1'..=b'7', ..] => {
+                return Err(CodecError::Unsupported(format!(
+                    "Netpbm variant P{} cannot be decoded row by row",
+                    *magic as char
+                )));
+            }
+            [] | [b'P'] => return Err(CodecError::UnexpectedEof),
+            _ => {
+                return Err(CodecError::InvalidData(
+                    "Missing Netpbm signature".to_string(),
+                ))
+            }
+        };
+
+        let mut parser = Parser::new(bytes, 2);
+        let (width, height, maxval) = parser.dimensions(magic == b'4')?;
+        parser.skip_single_whitespace()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(CodecError::InvalidData(format!(
+                "Invalid maxval {}",
+                maxval
+            )));
+        }
+
+        let channels = if magic == b'6' { 3 } else { 1 };
+        let sample_bytes = if maxval > 255 { 2 } else { 1 };
+        if width > MAX_ROW_WIDTH {
+            return Err(CodecError::InvalidData(format!(
+                "Row width {} exceeds the limit of {}",
+                width, MAX_ROW_WIDTH
+            )));
+        }
+        let raw_len = if magic == b'4' {
+            width.div_ceil(8)
+        } else {
+            sample_count(&[width, channels, sample_bytes])?
+        };
+
+        let decoder = Self {
+            magic,
+            width,
+            height,
+            maxval,
+            raw: vec![0; raw_len],
+            row: vec![0; width * channels],
+            rows_read: 0,
+        };
+        Ok((decoder, parser.pos))
+    }
+
+    /// Returns the image width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the image height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of samples per pixel in the rows: 1 for PBM and PGM, 3 for PPM.
+    pub fn channels(&self) -> usize {
+        if self.magic == b'6' {
+            3
+        } else {
+            1
+        }
+    }
+
+    /// Decodes the next row, top to bottom.
+    ///
+    /// # Arguments
+    ///
+    /// * `read` - Callback that fills the given buffer with the next encoded bytes,
+    ///   failing if the source ends early
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(row))` with `width * channels` samples, `Ok(None)` after the
+    /// last row, the error of `read`, or `CodecError::InvalidData` if a sample
+    /// exceeds the `maxval`.
+    pub fn next_row<E, F>(&mut self, mut read: F) -> Result<Option<&[u8]>, E>
+    where
+        E: From<CodecError>,
+        F: FnMut(&mut [u8]) -> Result<(), E>,
+    {
+        if self.rows_read == self.height {
+            return Ok(None);
+        }
+        read(&mut self.raw)?;
+        self.rows_read += 1;
+
+        let maxval = self.maxval;
+        let out_of_range = || CodecError::InvalidData("Sample exceeds maxval".to_string());
+        if self.magic == b'4' {
+            // PBM packs 8 pixels per byte, most significant bit first, with 1 for black
+            for (x, pixel) in self.row.iter_mut().enumerate() {
+                let bit = (self.raw[x / 8] >> (7 - x % 8)) & 1;
+                *pixel = if bit == 1 { 0 } else { 255 };
+            }
+        } else if maxval > 255 {
+            for (pixel, pair) in self.row.iter_mut().zip(self.raw.chunks_exact(2)) {
+                let value = u16::from_be_bytes([pair[0], pair[1]]) as usize;
+                if value > maxval {
+                    return Err(out_of_range().into());
+                }
+                *pixel = ((value * 255 + maxval / 2) / maxval) as u8;
+            }
+        } else if maxval == 255 {
+            self.row.copy_from_slice(&self.raw);
+        } else {
+            for (pixel, &value) in self.row.iter_mut().zip(&self.raw) {
+                if value as usize > maxval {
+                    return Err(out_of_range().into());
+                }
+                *pixel = ((value as usize * 255 + maxval / 2) / maxval) as u8;
+            }
+        }
+        Ok(Some(&self.row))
+    }
+}
+
+/// Encodes a binary PGM (`P5`) or PPM (`P6`) image one row at a time.
+///
+/// The encoder only validates and counts rows; the header and every row are passed to
+/// a callback that writes them to any sink, so no image buffer is needed. Every row
+/// must be written before calling [`RowEncoder::finish`].
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::codec::{netpbm::RowEncoder, CodecError};
+///
+/// let mut out = Vec::new();
+/// let mut encoder = RowEncoder::new(2, 2, 1)?;
+/// let mut sink = |bytes: &[u8]| {
+///     out.extend_from_slice(bytes);
+///     Ok::<_, CodecError>(())
+/// };
+/// encoder.write_header(&mut sink)?;
+/// encoder.write_row(&[1, 2], &mut sink)?;
+/// encoder.write_row(&[3, 4], &mut sink)?;
+/// encoder.finish()?;
+/// assert_eq!(out, b"P5\n2 2\n255\n\x01\x02\x03\x04");
+/// # Ok::<(), CodecError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RowEncoder {
+    width: usize,
+    height: usize,
+    channels: usize,
+    rows_left: usize,
+}
+
+impl RowEncoder {
+    /// Creates a row encoder for an 8-bit image.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Image width in pixels
+    /// * `height` - Image height in pixels
+    /// * `channels` - 1 for a PGM or 3 for a PPM
+    ///
+    /// # Returns
+    ///
+    /// The encoder, or `CodecError::Unsupported` for other channel counts.
+    pub fn new(width: usize, height: usize, channels: usize) -> Result<Self, CodecError> {
+        if channels != 1 && channels != 3 {
+            return Err(CodecError::Unsupported(format!(
+                "Cannot stream {}-channel rows",
+                channels
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            channels,
+            rows_left: height,
+        })
+    }
+
+    /// Passes the file header to `write`.
+    pub fn write_header<E, F>(&self, mut write: F) -> Result<(), E>
+    where
+        F: FnMut(&[u8]) -> Result<(), E>,
+    {
+        let mut header = Vec::new();
+        header.extend_from_slice(if self.channels == 1 { b"P5\n" } else { b"P6\n" });
+        push_decimal(&mut header, self.width as u32);
+        header.push(b' ');
+        push_decimal(&mut header, self.height as u32);
+        header.extend_from_slice(b"\n255\n");
+        write(&header)
+    }
+
+    /// Validates the next row, top to bottom, and passes it to `write`.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - `width * channels` samples
+    /// * `write` - Callback that appends bytes to the output
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, the error of `write`, or
+    /// `CodecError::InvalidData` if the row has the wrong length or all rows have
+    /// already been written.
+    pub fn write_row<E, F>(&mut self, row: &[u8], mut write: F) -> Result<(), E>
+    where
+        E: From<CodecError>,
+        F: FnMut(&[u8]) -> Result<(), E>,
+    {
+        if self.rows_left == 0 {
+            return Err(
+                CodecError::InvalidData("All rows have already been written".to_string()).into(),
+            );
+        }
+        let row_len = self.width * self.channels;
+        if row.len() != row_len {
+            return Err(CodecError::InvalidData(format!(
+                "Row has {} samples but {} were expected",
+                row.len(),
+                row_len
+            ))
+            .into());
+        }
+        write(row)?;
+        self.rows_left -= 1;
+        Ok(())
+    }
+
+    /// Checks that every row has been written.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or `CodecError::InvalidData` if rows are missing.
+    pub fn finish(&self) -> Result<(), CodecError> {
+        if self.rows_left > 0 {
+            return Err(CodecError::InvalidData(format!(
+                "{} rows were never written",
+                self.rows_left
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Multiplies image dimensions into a sample count, rejecting overflow.
+fn sample_count(factors: &[usize]) -> Result<usize, CodecError> {
+    factors
+        .iter()
+        .try_fold(1usize, |count, &factor| count.checked_mul(factor))
+        .ok_or_else(|| CodecError::InvalidData("Netpbm dimensions too large".to_string()))
+}
+
+/// Cursor over the bytes of a Netpbm file.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(bytes: &'a [u8], pos: usize) -> Self {
+        Self { bytes, pos }
+    }
+
+    /// Skips whitespace and `#` comments between header tokens.
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == b'#' {
+                while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Reads a decimal header token.
+    fn header_value(&mut self) -> Result<usize, CodecError> {
+        self.skip_whitespace_and_comments();
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        if start == self.pos && self.pos == self.bytes.len() {
+            return Err(CodecError::UnexpectedEof);
+        }
+        parse_decimal(&self.bytes[start..self.pos])
+            .ok_or_else(|| CodecError::InvalidData("Malformed Netpbm header".to_string()))
+    }
+
+    /// Reads the width and height, followed by the maxval unless the image is bilevel
+    /// (PBM), in which case the maxval is 1.
+    fn dimensions(&mut self, bilevel: bool) -> Result<(usize, usize, usize), CodecError> {
+        let width = self.header_value()?;
+        let height = self.header_value()?;
+        let maxval = if bilevel { 1 } else { self.header_value()? };
+        Ok((width, height, maxval))
+    }
+
+    /// Consumes the single whitespace byte separating the header from binary data.
+    fn skip_single_whitespace(&mut self) -> Result<(), CodecError> {
+        match self.bytes.get(self.pos) {
+            Some(b) if b.is_ascii_whitespace() => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(CodecError::InvalidData(
+                "Missing whitespace after header".to_string(),
+            )),
+            None => Err(CodecError::UnexpectedEof),
+        }
+    }
+
+    /// Reads one header line of a PAM file (without the newline).
+    fn line(&mut self) -> Result<&'a [u8], CodecError> {
+        if self.pos >= self.bytes.len() {
+            return Err(CodecError::UnexpectedEof);
+        }
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+            self.pos += 1;
+        }
+        let line = &self.bytes[start..self.pos];
+        self.pos += 1;
+        Ok(line)
+    }
+
+    /// Fails unless at least `len` bytes are left, so that buffers sized from the
+    /// header are only allocated for input that can actually fill them.
+    fn ensure_remaining(&self, len: usize) -> Result<(), CodecError> {
+        if len > self.bytes.len() - self.pos.min(self.bytes.len()) {
+            return Err(CodecError::UnexpectedEof);
+        }
+        Ok(())
+    }
+
+    /// Reads `count` whitespace-separated ASCII samples.
+    fn ascii_samples(&mut self, count: usize) -> Result<Vec<u16>, CodecError> {
+        // Every sample takes at least one digit
+        self.ensure_remaining(count)?;
+        let mut samples = Vec::with_capacity(count);
+        for _ in 0..count {
+            let value = self.header_value()?;
+            if value > 65535 {
+                return Err(CodecError::InvalidData("Sample out of range".to_string()));
+            }
+            samples.push(value as u16);
+        }
+        Ok(samples)
+    }
+
+    /// Reads `count` ASCII PBM bits, which may or may not be separated by whitespace.
+    fn ascii_bits(&mut self, count: usize) -> Result<Vec<u8>, CodecError> {
+        self.ensure_remaining(count)?;
+        let mut bits = Vec::with_capacity(count);
+        while bits.len() < count {
+            self.skip_whitespace_and_comments();
+            match self.bytes.get(self.pos) {
+                Some(b'0') => bits.push(0),
+                Some(b'1') => bits.push(1),
+                Some(_) => {
+                    return Err(CodecError::InvalidData("Invalid PBM bit".to_string()));
+                }
+                None => return Err(CodecError::UnexpectedEof),
+            }
+            self.pos += 1;
+        }
+        Ok(bits)
+    }
+
+    /// Reads packed binary PBM rows, each padded to a whole byte.
+    fn packed_bits(&mut self, width: usize, height: usize) -> Result<Vec<u8>, CodecError> {
+        let row_bytes = width.div_ceil(8);
+        let raster = self.take(sample_count(&[row_bytes, height])?)?;
+        let mut bits = Vec::with_capacity(width * height);
+        for row in raster.chunks_exact(row_bytes.max(1)).take(height) {
+            for x in 0..width {
+                bits.push((row[x / 8] >> (7 - x % 8)) & 1);
+            }
+        }
+        Ok(bits)
+    }
+
+    /// Reads `count` binary samples of one or two bytes depending on `maxval`.
+    fn binary_samples(&mut self, count: usize, maxval: usize) -> Result<Vec<u16>, CodecError> {
+        if maxval < 256 {
+            Ok(self.take(count)?.iter().map(|&v| v as u16).collect())
+        } else {
+            Ok(self
+                .take(sample_count(&[count, 2])?)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect())
+        }
+    }
+
+    /// Takes the next `len` bytes.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(CodecError::UnexpectedEof)?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pgm_ppm_roundtrip() {
+        let gray = Matrix1::new(3, 2, vec![0, 50, 100, 150, 200, 255]);
+        for encoding in [PnmEncoding::Ascii, PnmEncoding::Binary] {
+            match decode(&encode(&gray, encoding)).unwrap() {
+                DynamicImage::Gray8(m) => assert_eq!(m.data(), gray.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+        }
+
+        let rgb = Matrix3::new(2, 1, vec![1, 2, 3, 250, 251, 252]);
+        match decode(&encode(&rgb, PnmEncoding::Binary)).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), rgb.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_16_bit_maxval() {
+        let depth = Matrix1U16::new(2, 2, vec![0, 1000, 40000, 65535]);
+        for encoding in [PnmEncoding::Ascii, PnmEncoding::Binary] {
+            match decode(&encode(&depth, encoding)).unwrap() {
+                DynamicImage::Gray16(m) => assert_eq!(m.data(), depth.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+        }
+
+        // 12-bit data is rescaled to the full 16-bit range
+        match decode(b"P2 2 1 4095 0 4095").unwrap() {
+            DynamicImage::Gray16(m) => assert_eq!(m.data(), &[0, 65535]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ascii_with_comments_and_small_maxval() {
+        let bytes = b"P3\n# created by hand\n2 1 # size\n15\n15 0 0  0 15 7\n";
+        match decode(bytes).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), &[255, 0, 0, 0, 255, 119]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pbm_roundtrip() {
+        let image = Matrix1::new(
+            10,
+            2,
+            (0..20).map(|i| if i % 3 == 0 { 0 } else { 255 }).collect(),
+        );
+        for encoding in [PnmEncoding::Ascii, PnmEncoding::Binary] {
+            match decode(&encode_pbm(&image, 128, encoding)).unwrap() {
+                DynamicImage::Gray8(m) => assert_eq!(m.data(), image.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+        }
+
+        // ASCII bits may be written without separators
+        match decode(b"P1\n3 1\n101").unwrap() {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), &[0, 255, 0]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pam() {
+        let rgb = Matrix3U16::new(1, 2, vec![1, 2, 3, 4, 5, 6]);
+        match decode(&encode_pam(&rgb)).unwrap() {
+            DynamicImage::Rgb16(m) => assert_eq!(m.data(), rgb.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+
+        // Gray + alpha drops the alpha channel
+        let bytes = b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 2\nMAXVAL 255\nTUPLTYPE GRAYSCALE_ALPHA\nENDHDR\n\x10\xff\x20\x00";
+        match decode(bytes).unwrap() {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), &[0x10, 0x20]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        assert!(matches!(
+            decode(b"P5 4 4 255\n\x00"),
+            Err(CodecError::UnexpectedEof)
+        ));
+        assert!(matches!(decode(b"GIF89a"), Err(CodecError::InvalidData(_))));
+        assert!(matches!(decode(b"P9"), Err(CodecError::Unsupported(_))));
+        assert!(decode(b"P2 1 1 0 0").is_err());
+    }
+
+    #[test]
+    fn test_row_decoder_and_encoder() {
+        let mut out = Vec::new();
+        let mut sink = |bytes: &[u8]| {
+            out.extend_from_slice(bytes);
+            Ok::<_, CodecError>(())
+        };
+        let mut encoder = RowEncoder::new(2, 2, 3).unwrap();
+        encoder.write_header(&mut sink).unwrap();
+        encoder.write_row(&[1, 2, 3, 4, 5, 6], &mut sink).unwrap();
+        assert!(encoder.write_row(&[1, 2, 3], &mut sink).is_err());
+        assert!(encoder.finish().is_err());
+        encoder
+            .write_row(&[7, 8, 9, 10, 11, 12], &mut sink)
+            .unwrap();
+        assert!(encoder.write_row(&[0; 6], &mut sink).is_err());
+        encoder.finish().unwrap();
+        assert!(RowEncoder::new(1, 1, 2).is_err());
+
+        let (mut decoder, mut pos) = RowDecoder::new(&out).unwrap();
+        assert_eq!(
+            (decoder.width(), decoder.height(), decoder.channels()),
+            (2, 2, 3)
+        );
+        let mut rows = Vec::new();
+        let mut read = |raw: &mut [u8]| {
+            let chunk = out
+                .get(pos..pos + raw.len())
+                .ok_or(CodecError::UnexpectedEof)?;
+            raw.copy_from_slice(chunk);
+            pos += raw.len();
+            Ok::<_, CodecError>(())
+        };
+        while let Some(row) = decoder.next_row(&mut read).unwrap() {
+            rows.extend_from_slice(row);
+        }
+        assert_eq!(rows, (1..=12).collect::<Vec<u8>>());
+
+        // Headers must be complete, streamable and of a sane width
+        assert!(matches!(
+            RowDecoder::new(b"P5\n2 2\n255"),
+            Err(CodecError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            RowDecoder::new(b"P2\n1 1\n255\n0\n"),
+            Err(CodecError::Unsupported(_))
+        ));
+        assert!(matches!(
+            RowDecoder::new(b"P5 999999999 1 255\n"),
+            Err(CodecError::InvalidData(_))
+        ));
+
+        // Samples above maxval are rejected instead of wrapping
+        let bytes = b"P5 1 1 15\n\xc8";
+        let (mut decoder, start) = RowDecoder::new(bytes).unwrap();
+        let result = decoder.next_row(|raw: &mut [u8]| {
+            raw.copy_from_slice(&bytes[start..]);
+            Ok::<_, CodecError>(())
+        });
+        assert!(matches!(result, Err(CodecError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_oversized_header_and_samples() {
+        // Dimensions that cannot fit in the input fail before allocating
+        for bytes in [
+            b"P2 999999999 999999999 255 0".as_slice(),
+            b"P1 999999999 999999999 0",
+            b"P5 999999999 999999999 255\n\x00",
+            b"P6 999999999 999999999 65535\n\x00",
+            b"P4 999999999 999999999\n\x00",
+            b"P7\nWIDTH 999999999\nHEIGHT 999999999\nDEPTH 4\nMAXVAL 255\nENDHDR\n",
+        ] {
+            assert!(decode(bytes).is_err());
+        }
+
+        assert!(matches!(
+            decode(b"P2 1 1 15 200"),
+            Err(CodecError::InvalidData(_))
+        ));
+        assert!(matches!(
+            decode(b"P5 1 1 15\n\xc8"),
+            Err(CodecError::InvalidData(_))
+        ));
+    }
+}
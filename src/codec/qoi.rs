@@ -0,0 +1,284 @@
+//! QOI ("Quite OK Image") codec.
+//!
+//! QOI is a simple lossless format that encodes and decodes considerably faster than
+//! PNG at a similar file size, which makes it well suited for dumping frames while
+//! debugging. Images are always stored as RGB or RGBA; alpha is dropped on decode and
+//! grayscale images are expanded to RGB on encode.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::codec::qoi;
+//! use cv_rusty::Matrix3;
+//!
+//! let image = Matrix3::new(2, 1, vec![255, 0, 0, 255, 0, 0]);
+//! let bytes = qoi::encode(&image).unwrap();
+//! assert_eq!(qoi::decode(&bytes).unwrap().data(), image.data());
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::ToString;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::CodecError;
+use crate::matrix::{Matrix3, WritableImage};
+
+const HEADER_SIZE: usize = 14;
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xC0;
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const MASK_2: u8 = 0xC0;
+
+/// Longest run a single `OP_RUN` can encode.
+const MAX_RUN: u8 = 62;
+
+/// An RGBA pixel.
+type Rgba = [u8; 4];
+
+/// Position of a pixel in the running color index.
+fn hash(px: Rgba) -> usize {
+    (px[0] as usize * 3 + px[1] as usize * 5 + px[2] as usize * 7 + px[3] as usize * 11) % 64
+}
+
+/// Decodes a QOI image.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded image data
+///
+/// # Returns
+///
+/// A `Matrix3` with the RGB data of the image. Alpha is dropped.
+pub fn decode(bytes: &[u8]) -> Result<Matrix3, CodecError> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(CodecError::UnexpectedEof);
+    }
+    if !bytes.starts_with(b"qoif") {
+        return Err(CodecError::InvalidData("Missing QOI signature".to_string()));
+    }
+
+    let width = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let height = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+    let channels = bytes[12];
+    if channels != 3 && channels != 4 {
+        return Err(CodecError::InvalidData(format!(
+            "Invalid QOI channel count {}",
+            channels
+        )));
+    }
+
+    // Every pixel needs at least 1/62 of a byte, which bounds the allocation
+    let pixel_count = width
+        .checked_mul(height)
+        .filter(|&n| n / MAX_RUN as usize <= bytes.len())
+        .ok_or_else(|| CodecError::InvalidData("QOI dimensions too large".to_string()))?;
+
+    let mut data = vec![0u8; pixel_count * 3];
+    let mut index = [[0u8; 4]; 64];
+    let mut px: Rgba = [0, 0, 0, 255];
+    let mut pos = HEADER_SIZE;
+    let mut run = 0;
+    let byte = |pos: usize| bytes.get(pos).copied().ok_or(CodecError::UnexpectedEof);
+
+    for out in data.chunks_exact_mut(3) {
+        if run > 0 {
+            run -= 1;
+        } else {
+            let b1 = byte(pos)?;
+            pos += 1;
+            match b1 {
+                OP_RGB => {
+                    px[0] = byte(pos)?;
+                    px[1] = byte(pos + 1)?;
+                    px[2] = byte(pos + 2)?;
+                    pos += 3;
+                }
+                OP_RGBA => {
+                    px[0] = byte(pos)?;
+                    px[1] = byte(pos + 1)?;
+                    px[2] = byte(pos + 2)?;
+                    px[3] = byte(pos + 3)?;
+                    pos += 4;
+                }
+                _ => match b1 & MASK_2 {
+                    OP_INDEX => px = index[b1 as usize],
+                    OP_DIFF => {
+                        px[0] = px[0].wrapping_add(((b1 >> 4) & 0x03).wrapping_sub(2));
+                        px[1] = px[1].wrapping_add(((b1 >> 2) & 0x03).wrapping_sub(2));
+                        px[2] = px[2].wrapping_add((b1 & 0x03).wrapping_sub(2));
+                    }
+                    OP_LUMA => {
+                        let b2 = byte(pos)?;
+                        pos += 1;
+                        let dg = (b1 & 0x3F).wrapping_sub(32);
+                        px[0] = px[0].wrapping_add(dg.wrapping_add((b2 >> 4).wrapping_sub(8)));
+                        px[1] = px[1].wrapping_add(dg);
+                        px[2] = px[2].wrapping_add(dg.wrapping_add((b2 & 0x0F).wrapping_sub(8)));
+                    }
+                    _ => run = b1 & 0x3F,
+                },
+            }
+            index[hash(px)] = px;
+        }
+        out.copy_from_slice(&px[..3]);
+    }
+
+    Ok(Matrix3::new(width, height, data))
+}
+
+/// Encodes an image as QOI.
+///
+/// RGB images are written as-is and grayscale images are expanded to RGB. The
+/// colorspace field is set to sRGB.
+///
+/// # Arguments
+///
+/// * `image` - The image to encode (`Matrix1` or `Matrix3`)
+///
+/// # Returns
+///
+/// The encoded file contents, or `CodecError::Unsupported` for 16-bit images.
+pub fn encode<I: WritableImage>(image: &I) -> Result<Vec<u8>, CodecError> {
+    if image.bit_depth() != 8 {
+        return Err(CodecError::Unsupported(format!(
+            "{}-bit QOI",
+            image.bit_depth()
+        )));
+    }
+
+    let samples = image.sample_bytes();
+    let channels = image.channels();
+
+    let mut out = Vec::with_capacity(HEADER_SIZE + samples.len() / 2 + END_MARKER.len());
+    out.extend_from_slice(b"qoif");
+    out.extend_from_slice(&(image.width() as u32).to_be_bytes());
+    out.extend_from_slice(&(image.height() as u32).to_be_bytes());
+    out.push(3);
+    out.push(0);
+
+    let mut index = [[0u8; 4]; 64];
+    let mut prev: Rgba = [0, 0, 0, 255];
+    let mut run = 0u8;
+
+    for sample in samples.chunks_exact(channels) {
+        let px: Rgba = if channels == 1 {
+            [sample[0], sample[0], sample[0], 255]
+        } else {
+            [sample[0], sample[1], sample[2], 255]
+        };
+
+        if px == prev {
+            run += 1;
+            if run == MAX_RUN {
+                out.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+
+        if run > 0 {
+            out.push(OP_RUN | (run - 1));
+            run = 0;
+        }
+
+        let slot = hash(px);
+        if index[slot] == px {
+            out.push(OP_INDEX | slot as u8);
+        } else {
+            index[slot] = px;
+
+            let vr = px[0].wrapping_sub(prev[0]) as i8;
+            let vg = px[1].wrapping_sub(prev[1]) as i8;
+            let vb = px[2].wrapping_sub(prev[2]) as i8;
+            let vg_r = vr.wrapping_sub(vg);
+            let vg_b = vb.wrapping_sub(vg);
+
+            if (-2..2).contains(&vr) && (-2..2).contains(&vg) && (-2..2).contains(&vb) {
+                out.push(OP_DIFF | ((vr + 2) as u8) << 4 | ((vg + 2) as u8) << 2 | (vb + 2) as u8);
+            } else if (-8..8).contains(&vg_r) && (-32..32).contains(&vg) && (-8..8).contains(&vg_b)
+            {
+                out.push(OP_LUMA | (vg + 32) as u8);
+                out.push(((vg_r + 8) as u8) << 4 | (vg_b + 8) as u8);
+            } else {
+                out.extend_from_slice(&[OP_RGB, px[0], px[1], px[2]]);
+            }
+        }
+
+        prev = px;
+    }
+
+    if run > 0 {
+        out.push(OP_RUN | (run - 1));
+    }
+    out.extend_from_slice(&END_MARKER);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix1;
+
+    #[test]
+    fn test_roundtrip() {
+        // Mix of runs, small diffs, luma diffs, index hits and full colors
+        let mut data = Vec::new();
+        for i in 0..200u32 {
+            let v = match i % 50 {
+                0..=19 => [10, 20, 30],
+                20..=29 => [11, 21, 29],
+                30..=39 => [40, 50, 45],
+                _ => [(i * 37) as u8, (i * 11) as u8, (i * 73) as u8],
+            };
+            data.extend_from_slice(&v);
+        }
+        let image = Matrix3::new(20, 10, data);
+        let bytes = encode(&image).unwrap();
+        assert!(bytes.len() < image.data().len());
+        assert!(bytes.ends_with(&END_MARKER));
+        assert_eq!(decode(&bytes).unwrap().data(), image.data());
+
+        let gray = Matrix1::new(3, 1, vec![0, 128, 255]);
+        let decoded = decode(&encode(&gray).unwrap()).unwrap();
+        assert_eq!(decoded.get_pixel(1, 0), Some((128, 128, 128)));
+    }
+
+    #[test]
+    fn test_decode_rgba_ops() {
+        let mut bytes = b"qoif\0\0\0\x03\0\0\0\x01\x04\0".to_vec();
+        // RGBA, 2-pixel run, end marker
+        bytes.extend_from_slice(&[OP_RGBA, 1, 2, 3, 4, OP_RUN | 1]);
+        bytes.extend_from_slice(&END_MARKER);
+        assert_eq!(decode(&bytes).unwrap().data(), &[1, 2, 3, 1, 2, 3, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        assert!(matches!(decode(b"qoif"), Err(CodecError::UnexpectedEof)));
+        assert!(matches!(
+            decode(b"qoiX\0\0\0\x01\0\0\0\x01\x03\0"),
+            Err(CodecError::InvalidData(_))
+        ));
+        assert!(matches!(
+            decode(b"qoif\0\0\0\x04\0\0\0\x01\x03\0\xFE"),
+            Err(CodecError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            decode(b"qoif\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x03\0"),
+            Err(CodecError::InvalidData(_))
+        ));
+    }
+}
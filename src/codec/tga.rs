@@ -0,0 +1,433 @@
+//! Truevision TGA codec.
+//!
+//! Decodes color-mapped, true-color (15/16/24/32-bit) and grayscale images, both
+//! uncompressed and run-length encoded, in any of the four origin corners. Alpha is
+//! dropped. Encoding writes grayscale images as 8-bit grayscale and RGB images as
+//! 24-bit true-color, optionally run-length encoded.
+//!
+//! TGA files have no signature, so they cannot be detected from their contents.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::codec::tga::{self, TgaCompression};
+//! use cv_rusty::{DynamicImage, Matrix1};
+//!
+//! let image = Matrix1::new(4, 1, vec![7, 7, 7, 200]);
+//! let bytes = tga::encode(&image, TgaCompression::Rle).unwrap();
+//!
+//! match tga::decode(&bytes).unwrap() {
+//!     DynamicImage::Gray8(decoded) => assert_eq!(decoded.data(), image.data()),
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::ToString;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::{read_u16_le, CodecError};
+use crate::matrix::{DynamicImage, Matrix1, Matrix3, WritableImage};
+
+const HEADER_SIZE: usize = 18;
+
+const TYPE_COLOR_MAPPED: u8 = 1;
+const TYPE_TRUE_COLOR: u8 = 2;
+const TYPE_GRAY: u8 = 3;
+const TYPE_RLE_FLAG: u8 = 8;
+
+/// Image descriptor bit set when the first row is the right-most column.
+const RIGHT_TO_LEFT: u8 = 0x10;
+/// Image descriptor bit set when the first row is the top row.
+const TOP_TO_BOTTOM: u8 = 0x20;
+
+/// Longest run or raw packet a single RLE packet header can describe.
+const MAX_PACKET: usize = 128;
+
+/// Compression used when writing TGA files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TgaCompression {
+    /// Uncompressed pixel data
+    None,
+    /// Run-length encoded pixel data
+    Rle,
+}
+
+/// Decodes a TGA image.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded image data
+///
+/// # Returns
+///
+/// A `DynamicImage::Gray8` for grayscale images, otherwise a `DynamicImage::Rgb8`.
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage, CodecError> {
+    let header = bytes.get(..HEADER_SIZE).ok_or(CodecError::UnexpectedEof)?;
+    let id_length = header[0] as usize;
+    let color_map_type = header[1];
+    let image_type = header[2];
+    let map_first = read_u16_le(header, 3)? as usize;
+    let map_length = read_u16_le(header, 5)? as usize;
+    let map_bits = header[7];
+    let width = read_u16_le(header, 12)? as usize;
+    let height = read_u16_le(header, 14)? as usize;
+    let depth = header[16];
+    let descriptor = header[17];
+
+    let rle = image_type & TYPE_RLE_FLAG != 0;
+    let kind = image_type & !TYPE_RLE_FLAG;
+    let supported = match kind {
+        TYPE_COLOR_MAPPED => color_map_type == 1 && (depth == 8 || depth == 16),
+        TYPE_TRUE_COLOR => matches!(depth, 15 | 16 | 24 | 32),
+        TYPE_GRAY => depth == 8 || depth == 16,
+        _ => false,
+    };
+    if !supported {
+        return Err(CodecError::Unsupported(format!(
+            "TGA image type {} with {} bits per pixel",
+            image_type, depth
+        )));
+    }
+    if width == 0 || height == 0 {
+        return Err(CodecError::InvalidData(
+            "Invalid TGA dimensions".to_string(),
+        ));
+    }
+
+    let mut pos = HEADER_SIZE + id_length;
+
+    // The color map is present (and must be skipped) even for non-mapped images
+    let mut palette = Vec::new();
+    if color_map_type == 1 {
+        let entry_bytes = (map_bits as usize).div_ceil(8);
+        if !matches!(map_bits, 15 | 16 | 24 | 32) {
+            return Err(CodecError::Unsupported(format!(
+                "TGA color map with {} bits per entry",
+                map_bits
+            )));
+        }
+        let map = bytes
+            .get(pos..pos + map_length * entry_bytes)
+            .ok_or(CodecError::UnexpectedEof)?;
+        palette = map
+            .chunks_exact(entry_bytes)
+            .map(|entry| color(entry, map_bits))
+            .collect();
+        pos += map.len();
+    }
+
+    let pixel_bytes = (depth as usize).div_ceil(8);
+    let count = width * height;
+    let units = if rle {
+        decode_rle(&bytes[pos.min(bytes.len())..], count, pixel_bytes)?
+    } else {
+        bytes
+            .get(pos..pos + count * pixel_bytes)
+            .ok_or(CodecError::UnexpectedEof)?
+            .to_vec()
+    };
+
+    // Stored row and column for each output pixel, honoring the origin corner
+    let source = |x: usize, y: usize| {
+        let row = if descriptor & TOP_TO_BOTTOM != 0 {
+            y
+        } else {
+            height - 1 - y
+        };
+        let col = if descriptor & RIGHT_TO_LEFT != 0 {
+            width - 1 - x
+        } else {
+            x
+        };
+        &units[(row * width + col) * pixel_bytes..][..pixel_bytes]
+    };
+    let pixels = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+
+    if kind == TYPE_GRAY {
+        // 16-bit grayscale is gray plus an alpha byte
+        let data = pixels.map(|(x, y)| source(x, y)[0]).collect();
+        return Ok(DynamicImage::Gray8(Matrix1::new(width, height, data)));
+    }
+
+    let data = pixels
+        .flat_map(|(x, y)| {
+            let unit = source(x, y);
+            let (r, g, b) = if kind == TYPE_COLOR_MAPPED {
+                let index = if pixel_bytes == 2 {
+                    u16::from_le_bytes([unit[0], unit[1]]) as usize
+                } else {
+                    unit[0] as usize
+                };
+                // Indices outside the map are treated as black
+                index
+                    .checked_sub(map_first)
+                    .and_then(|i| palette.get(i).copied())
+                    .unwrap_or((0, 0, 0))
+            } else {
+                color(unit, depth)
+            };
+            [r, g, b]
+        })
+        .collect();
+    Ok(DynamicImage::Rgb8(Matrix3::new(width, height, data)))
+}
+
+/// Encodes an image as TGA.
+///
+/// Grayscale images are written as 8-bit grayscale (type 3 or 11) and RGB images as
+/// 24-bit true-color (type 2 or 10), with the origin in the top-left corner.
+///
+/// # Arguments
+///
+/// * `image` - The image to encode (`Matrix1` or `Matrix3`)
+/// * `compression` - Whether to run-length encode the pixel data
+///
+/// # Returns
+///
+/// The encoded file contents, or `CodecError::Unsupported` for 16-bit images or images
+/// larger than 65535 pixels in either dimension.
+pub fn encode<I: WritableImage>(
+    image: &I,
+    compression: TgaCompression,
+) -> Result<Vec<u8>, CodecError> {
+    if image.bit_depth() != 8 {
+        return Err(CodecError::Unsupported(format!(
+            "{}-bit TGA",
+            image.bit_depth()
+        )));
+    }
+    let (width, height) = (image.width(), image.height());
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(CodecError::Unsupported(format!(
+            "TGA dimensions {}x{}",
+            width, height
+        )));
+    }
+
+    let gray = image.channels() == 1;
+    let mut image_type = if gray { TYPE_GRAY } else { TYPE_TRUE_COLOR };
+    if compression == TgaCompression::Rle {
+        image_type |= TYPE_RLE_FLAG;
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&[0, 0, image_type, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    out.extend_from_slice(&(width as u16).to_le_bytes());
+    out.extend_from_slice(&(height as u16).to_le_bytes());
+    out.push(if gray { 8 } else { 24 });
+    out.push(TOP_TO_BOTTOM);
+
+    // TGA stores true-color pixels as BGR
+    let samples = image.sample_bytes();
+    let pixels: Vec<&[u8]> = samples.chunks_exact(image.channels()).collect();
+    let push_pixel = |out: &mut Vec<u8>, px: &[u8]| {
+        if gray {
+            out.push(px[0]);
+        } else {
+            out.extend_from_slice(&[px[2], px[1], px[0]]);
+        }
+    };
+
+    match compression {
+        TgaCompression::None => {
+            for px in &pixels {
+                push_pixel(&mut out, px);
+            }
+        }
+        TgaCompression::Rle => {
+            // Packets never cross scanlines, as recommended by the specification
+            for row in pixels.chunks(width.max(1)) {
+                let mut i = 0;
+                while i < row.len() {
+                    let run = row[i..]
+                        .iter()
+                        .take(MAX_PACKET)
+                        .take_while(|&&px| px == row[i])
+                        .count();
+                    if run > 1 {
+                        out.push(0x80 | (run - 1) as u8);
+                        push_pixel(&mut out, row[i]);
+                        i += run;
+                    } else {
+                        // Raw packet up to the next pair of equal pixels
+                        let mut end = i + 1;
+                        while end < row.len() && end - i < MAX_PACKET && row[end] != row[end - 1] {
+                            end += 1;
+                        }
+                        if end < row.len() && row[end] == row[end - 1] && end - 1 > i {
+                            end -= 1;
+                        }
+                        out.push((end - i - 1) as u8);
+                        for px in &row[i..end] {
+                            push_pixel(&mut out, px);
+                        }
+                        i = end;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Converts a little-endian BGR(A) or 15/16-bit ARGB1555 value to RGB.
+fn color(unit: &[u8], bits: u8) -> (u8, u8, u8) {
+    if bits <= 16 {
+        let value = u16::from_le_bytes([unit[0], unit[1]]);
+        let expand = |v: u16| {
+            let v = (v & 0x1F) as u8;
+            (v << 3) | (v >> 2)
+        };
+        (expand(value >> 10), expand(value >> 5), expand(value))
+    } else {
+        (unit[2], unit[1], unit[0])
+    }
+}
+
+/// Expands run-length encoded packets into `count` pixels of `pixel_bytes` each.
+fn decode_rle(bytes: &[u8], count: usize, pixel_bytes: usize) -> Result<Vec<u8>, CodecError> {
+    let len = count * pixel_bytes;
+    // Each input byte yields at most one packet of MAX_PACKET pixels, so a forged
+    // header cannot reserve more than the input can fill
+    let mut out = Vec::with_capacity(len.min(bytes.len().saturating_mul(MAX_PACKET * pixel_bytes)));
+    let mut pos = 0;
+    while out.len() < len {
+        let header = *bytes.get(pos).ok_or(CodecError::UnexpectedEof)?;
+        pos += 1;
+        let n = (header & 0x7F) as usize + 1;
+        if header & 0x80 != 0 {
+            let px = bytes
+                .get(pos..pos + pixel_bytes)
+                .ok_or(CodecError::UnexpectedEof)?;
+            for _ in 0..n {
+                out.extend_from_slice(px);
+            }
+            pos += pixel_bytes;
+        } else {
+            let raw = bytes
+                .get(pos..pos + n * pixel_bytes)
+                .ok_or(CodecError::UnexpectedEof)?;
+            out.extend_from_slice(raw);
+            pos += raw.len();
+        }
+    }
+    // A packet may overshoot the image; the excess is ignored
+    out.truncate(len);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let rgb = Matrix3::new(
+            5,
+            2,
+            vec![
+                1, 2, 3, 1, 2, 3, 1, 2, 3, 9, 9, 9, 8, 8, 8, //
+                7, 7, 7, 6, 6, 6, 5, 5, 5, 5, 5, 5, 4, 4, 4,
+            ],
+        );
+        for compression in [TgaCompression::None, TgaCompression::Rle] {
+            match decode(&encode(&rgb, compression).unwrap()).unwrap() {
+                DynamicImage::Rgb8(m) => assert_eq!(m.data(), rgb.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+        }
+
+        let gray = Matrix1::new(300, 1, (0..300).map(|v| (v / 100) as u8).collect());
+        let bytes = encode(&gray, TgaCompression::Rle).unwrap();
+        // Three runs of 100 pixels
+        assert_eq!(bytes.len(), HEADER_SIZE + 6);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), gray.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bottom_up_16_bit() {
+        // 1x2, bottom-left origin, ARGB1555: bottom pixel red, top pixel blue
+        let mut bytes = vec![
+            0,
+            0,
+            TYPE_TRUE_COLOR,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            1,
+            0,
+            2,
+            0,
+            16,
+            0,
+        ];
+        bytes.extend_from_slice(&0x7C00u16.to_le_bytes());
+        bytes.extend_from_slice(&0x001Fu16.to_le_bytes());
+        match decode(&bytes).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), &[0, 0, 255, 255, 0, 0]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_color_mapped_rle() {
+        // Two-entry 24-bit map starting at index 0, RLE: run of 3 x index 1
+        let mut bytes = vec![
+            0,
+            1,
+            TYPE_COLOR_MAPPED | TYPE_RLE_FLAG,
+            0,
+            0,
+            2,
+            0,
+            24,
+            0,
+            0,
+            0,
+            0,
+            3,
+            0,
+            1,
+            0,
+            8,
+            TOP_TO_BOTTOM,
+        ];
+        bytes.extend_from_slice(&[0, 0, 0, 30, 20, 10]);
+        bytes.extend_from_slice(&[0x82, 1]);
+        match decode(&bytes).unwrap() {
+            DynamicImage::Rgb8(m) => assert_eq!(m.data(), &[10, 20, 30, 10, 20, 30, 10, 20, 30]),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        assert!(matches!(decode(&[0; 4]), Err(CodecError::UnexpectedEof)));
+        let header = [0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 24, 0];
+        assert!(matches!(decode(&header), Err(CodecError::UnexpectedEof)));
+        let no_image = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 24, 0];
+        assert!(matches!(decode(&no_image), Err(CodecError::Unsupported(_))));
+
+        // A 65535x65535 RLE header with a single packet runs out of input
+        let huge = [
+            0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 32, 0, 0x81, 1, 2, 3, 4,
+        ];
+        assert!(matches!(decode(&huge), Err(CodecError::UnexpectedEof)));
+    }
+}
@@ -1,14 +1,21 @@
 //! Color space conversion module.
 //!
-//! This module provides functions for converting between different color spaces
-//! and converting multi-channel images to single-channel grayscale images.
+//! This module provides functions for converting between different color spaces,
+//! converting between sRGB and linear light, converting whole images to perceptual
+//! color spaces such as HSV and L\*a\*b\*, converting multi-channel images to
+//! single-channel grayscale images, and importing and exporting raw YUV camera frames.
 //!
 //! This module is `no_std` compatible and only requires the `alloc` crate.
 
 #[cfg(not(feature = "std"))]
 use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-use crate::matrix::{Matrix1, Matrix3};
+use crate::matrix::{Matrix1, Matrix1U16, Matrix3, Matrix3U16};
 
 /// Methods for converting RGB images to grayscale.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -86,40 +93,97 @@ impl Matrix3 {
     /// ```
     pub fn to_grayscale_with_method(&self, method: GrayscaleMethod) -> Matrix1 {
         let mut gray_data = vec![0u8; self.width() * self.height()];
+        method.convert_row(self.data(), &mut gray_data);
+        Matrix1::new(self.width(), self.height(), gray_data)
+    }
+}
+
+impl GrayscaleMethod {
+    /// Converts a row of interleaved RGB pixels to grayscale.
+    ///
+    /// This is the per-pixel kernel behind [`Matrix3::to_grayscale_with_method`], for
+    /// converting images row by row while they are streamed (see `io::PngRowReader`).
+    ///
+    /// # Arguments
+    ///
+    /// * `rgb` - Interleaved RGB samples
+    /// * `gray` - Output samples, one per RGB pixel
+    ///
+    /// # Panics
+    ///
+    /// Panics if `gray` is shorter than `rgb.len() / 3`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::GrayscaleMethod;
+    ///
+    /// let mut gray = [0u8; 2];
+    /// GrayscaleMethod::Average.convert_row(&[30, 60, 90, 255, 255, 255], &mut gray);
+    /// assert_eq!(gray, [60, 255]);
+    /// ```
+    pub fn convert_row(self, rgb: &[u8], gray: &mut [u8]) {
+        let pixels = rgb.chunks_exact(3);
+        assert!(gray.len() >= pixels.len(), "Output row is too short");
 
-        match method {
+        match self {
             GrayscaleMethod::Luminosity => {
-                for (i, pixel) in gray_data.iter_mut().enumerate() {
-                    let rgb_idx = i * 3;
-                    let r = self.data()[rgb_idx] as f32;
-                    let g = self.data()[rgb_idx + 1] as f32;
-                    let b = self.data()[rgb_idx + 2] as f32;
+                for (pixel, rgb) in gray.iter_mut().zip(pixels) {
+                    let r = rgb[0] as f32;
+                    let g = rgb[1] as f32;
+                    let b = rgb[2] as f32;
                     *pixel = (0.299 * r + 0.587 * g + 0.114 * b) as u8;
                 }
             }
             GrayscaleMethod::Average => {
-                for (i, pixel) in gray_data.iter_mut().enumerate() {
-                    let rgb_idx = i * 3;
-                    let r = self.data()[rgb_idx] as u16;
-                    let g = self.data()[rgb_idx + 1] as u16;
-                    let b = self.data()[rgb_idx + 2] as u16;
+                for (pixel, rgb) in gray.iter_mut().zip(pixels) {
+                    let r = rgb[0] as u16;
+                    let g = rgb[1] as u16;
+                    let b = rgb[2] as u16;
                     *pixel = ((r + g + b) / 3) as u8;
                 }
             }
             GrayscaleMethod::Lightness => {
-                for (i, pixel) in gray_data.iter_mut().enumerate() {
-                    let rgb_idx = i * 3;
-                    let r = self.data()[rgb_idx];
-                    let g = self.data()[rgb_idx + 1];
-                    let b = self.data()[rgb_idx + 2];
-                    let max = r.max(g).max(b);
-                    let min = r.min(g).min(b);
+                for (pixel, rgb) in gray.iter_mut().zip(pixels) {
+                    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+                    let min = rgb[0].min(rgb[1]).min(rgb[2]);
                     *pixel = ((max as u16 + min as u16) / 2) as u8;
                 }
             }
         }
+    }
+}
 
-        Matrix1::new(self.width(), self.height(), gray_data)
+impl Matrix3U16 {
+    /// Converts a 16-bit RGB image to 16-bit grayscale using the specified method.
+    ///
+    /// Uses the same formulas as [`Matrix3::to_grayscale_with_method`] without
+    /// reducing the sample precision.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The grayscale conversion method to use
+    ///
+    /// # Returns
+    ///
+    /// A single-channel Matrix1U16 containing the grayscale image.
+    pub fn to_grayscale_with_method(&self, method: GrayscaleMethod) -> Matrix1U16 {
+        let gray_data = self
+            .data()
+            .chunks_exact(3)
+            .map(|rgb| {
+                let (r, g, b) = (rgb[0] as u32, rgb[1] as u32, rgb[2] as u32);
+                match method {
+                    GrayscaleMethod::Luminosity => {
+                        (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) as u16
+                    }
+                    GrayscaleMethod::Average => ((r + g + b) / 3) as u16,
+                    GrayscaleMethod::Lightness => ((r.max(g).max(b) + r.min(g).min(b)) / 2) as u16,
+                }
+            })
+            .collect();
+
+        Matrix1U16::new(self.width(), self.height(), gray_data)
     }
 }
 
@@ -149,30 +213,8 @@ impl Matrix3 {
 /// assert!((v - 1.0).abs() < 0.1);
 /// ```
 pub fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
-    let r = r as f32 / 255.0;
-    let g = g as f32 / 255.0;
-    let b = b as f32 / 255.0;
-
-    let max = r.max(g).max(b);
-    let min = r.min(g).min(b);
-    let delta = max - min;
-
-    let h = if delta == 0.0 {
-        0.0
-    } else if max == r {
-        60.0 * (((g - b) / delta) % 6.0)
-    } else if max == g {
-        60.0 * (((b - r) / delta) + 2.0)
-    } else {
-        60.0 * (((r - g) / delta) + 4.0)
-    };
-
-    let h = if h < 0.0 { h + 360.0 } else { h };
-
-    let s = if max == 0.0 { 0.0 } else { delta / max };
-
-    let v = max;
-
+    let [h, s, v] =
+        ColorSpace::Hsv.rgb_to_space([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0]);
     (h, s, v)
 }
 
@@ -199,29 +241,7 @@ pub fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
 /// assert_eq!(b, 0);
 /// ```
 pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
-    let c = v * s;
-    let h_prime = h / 60.0;
-    let x = c * (1.0 - ((h_prime % 2.0) - 1.0).abs());
-    let m = v - c;
-
-    let (r, g, b) = if h_prime < 1.0 {
-        (c, x, 0.0)
-    } else if h_prime < 2.0 {
-        (x, c, 0.0)
-    } else if h_prime < 3.0 {
-        (0.0, c, x)
-    } else if h_prime < 4.0 {
-        (0.0, x, c)
-    } else if h_prime < 5.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-
-    let r = ((r + m) * 255.0) as u8;
-    let g = ((g + m) * 255.0) as u8;
-    let b = ((b + m) * 255.0) as u8;
-
+    let [r, g, b] = rgb_to_u8(ColorSpace::Hsv.space_to_rgb([h, s, v]));
     (r, g, b)
 }
 
@@ -251,34 +271,8 @@ pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
 /// assert!((l - 0.5).abs() < 0.1);
 /// ```
 pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
-    let r = r as f32 / 255.0;
-    let g = g as f32 / 255.0;
-    let b = b as f32 / 255.0;
-
-    let max = r.max(g).max(b);
-    let min = r.min(g).min(b);
-    let delta = max - min;
-
-    let l = (max + min) / 2.0;
-
-    let s = if delta == 0.0 {
-        0.0
-    } else {
-        delta / (1.0 - (2.0 * l - 1.0).abs())
-    };
-
-    let h = if delta == 0.0 {
-        0.0
-    } else if max == r {
-        60.0 * (((g - b) / delta) % 6.0)
-    } else if max == g {
-        60.0 * (((b - r) / delta) + 2.0)
-    } else {
-        60.0 * (((r - g) / delta) + 4.0)
-    };
-
-    let h = if h < 0.0 { h + 360.0 } else { h };
-
+    let [h, s, l] =
+        ColorSpace::Hsl.rgb_to_space([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0]);
     (h, s, l)
 }
 
@@ -305,183 +299,1290 @@ pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
 /// assert_eq!(b, 0);
 /// ```
 pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
-    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-    let h_prime = h / 60.0;
-    let x = c * (1.0 - ((h_prime % 2.0) - 1.0).abs());
-    let m = l - c / 2.0;
-
-    let (r, g, b) = if h_prime < 1.0 {
-        (c, x, 0.0)
-    } else if h_prime < 2.0 {
-        (x, c, 0.0)
-    } else if h_prime < 3.0 {
-        (0.0, c, x)
-    } else if h_prime < 4.0 {
-        (0.0, x, c)
-    } else if h_prime < 5.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-
-    let r = ((r + m) * 255.0) as u8;
-    let g = ((g + m) * 255.0) as u8;
-    let b = ((b + m) * 255.0) as u8;
-
+    let [r, g, b] = rgb_to_u8(ColorSpace::Hsl.space_to_rgb([h, s, l]));
     (r, g, b)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_rgb_to_hsv_pure_colors() {
-        // Red
-        let (h, s, v) = rgb_to_hsv(255, 0, 0);
-        assert!((h - 0.0).abs() < 0.1);
-        assert!((s - 1.0).abs() < 0.01);
-        assert!((v - 1.0).abs() < 0.01);
+/// Memory layouts of raw 8-bit YUV frames produced by cameras and hardware decoders.
+///
+/// Buffers are expected to be tightly packed, without padding at the end of rows.
+/// Images with an odd width or height share the chroma of the last column or row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvFormat {
+    /// 4:2:0 with a Y plane followed by an interleaved U/V plane
+    Nv12,
+    /// 4:2:0 with a Y plane followed by an interleaved V/U plane
+    Nv21,
+    /// 4:2:0 with separate Y, U and V planes
+    I420,
+    /// 4:2:2 with separate Y, U and V planes
+    I422,
+    /// 4:4:4 with separate Y, U and V planes
+    I444,
+    /// Packed 4:2:2 with bytes in `Y0 U Y1 V` order
+    Yuyv,
+    /// Packed 4:2:2 with bytes in `U Y0 V Y1` order
+    Uyvy,
+}
 
-        // Green
-        let (h, s, v) = rgb_to_hsv(0, 255, 0);
-        assert!((h - 120.0).abs() < 0.1);
-        assert!((s - 1.0).abs() < 0.01);
-        assert!((v - 1.0).abs() < 0.01);
+impl YuvFormat {
+    /// Returns the number of bytes of a frame with the given dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::YuvFormat;
+    ///
+    /// assert_eq!(YuvFormat::Nv12.buffer_len(640, 480), 640 * 480 * 3 / 2);
+    /// assert_eq!(YuvFormat::Yuyv.buffer_len(640, 480), 640 * 480 * 2);
+    /// ```
+    pub fn buffer_len(self, width: usize, height: usize) -> usize {
+        let (chroma_width, chroma_height) = self.chroma_size(width, height);
+        match self {
+            YuvFormat::Yuyv | YuvFormat::Uyvy => 4 * chroma_width * height,
+            _ => width * height + 2 * chroma_width * chroma_height,
+        }
+    }
 
-        // Blue
-        let (h, s, v) = rgb_to_hsv(0, 0, 255);
-        assert!((h - 240.0).abs() < 0.1);
-        assert!((s - 1.0).abs() < 0.01);
-        assert!((v - 1.0).abs() < 0.01);
+    /// Dimensions of the chroma planes for an image of the given size.
+    fn chroma_size(self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            YuvFormat::Nv12 | YuvFormat::Nv21 | YuvFormat::I420 => {
+                (width.div_ceil(2), height.div_ceil(2))
+            }
+            YuvFormat::I422 | YuvFormat::Yuyv | YuvFormat::Uyvy => (width.div_ceil(2), height),
+            YuvFormat::I444 => (width, height),
+        }
     }
+}
 
-    #[test]
-    fn test_hsv_to_rgb_pure_colors() {
-        // Red
-        let (r, g, b) = hsv_to_rgb(0.0, 1.0, 1.0);
-        assert_eq!(r, 255);
-        assert_eq!(g, 0);
-        assert_eq!(b, 0);
+/// Standards defining how RGB maps to YUV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvMatrix {
+    /// ITU-R BT.601, used by standard-definition video and most webcams
+    Bt601,
+    /// ITU-R BT.709, used by HD video
+    Bt709,
+}
 
-        // Green
-        let (r, g, b) = hsv_to_rgb(120.0, 1.0, 1.0);
-        assert_eq!(r, 0);
-        assert_eq!(g, 255);
-        assert_eq!(b, 0);
+/// Value ranges of YUV samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvRange {
+    /// Samples use the full 0-255 range (as in JPEG)
+    Full,
+    /// Luma uses 16-235 and chroma 16-240 (as in most video)
+    Limited,
+}
 
-        // Blue
-        let (r, g, b) = hsv_to_rgb(240.0, 1.0, 1.0);
-        assert_eq!(r, 0);
-        assert_eq!(g, 0);
-        assert_eq!(b, 255);
+/// Error returned when a YUV buffer is too small for the given dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YuvBufferSizeError {
+    /// Number of bytes required by the layout
+    pub expected: usize,
+    /// Number of bytes in the buffer
+    pub actual: usize,
+}
+
+impl core::fmt::Display for YuvBufferSizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "YUV buffer has {} bytes but the layout requires {}",
+            self.actual, self.expected
+        )
     }
+}
 
-    #[test]
-    fn test_rgb_hsv_roundtrip() {
-        let test_colors = vec![
-            (255, 0, 0),
-            (0, 255, 0),
-            (0, 0, 255),
-            (255, 255, 0),
-            (255, 0, 255),
-            (0, 255, 255),
-            (128, 64, 192),
-        ];
+#[cfg(feature = "std")]
+impl std::error::Error for YuvBufferSizeError {}
 
-        for (r, g, b) in test_colors {
-            let (h, s, v) = rgb_to_hsv(r, g, b);
-            let (r2, g2, b2) = hsv_to_rgb(h, s, v);
-            assert!(
-                (r as i16 - r2 as i16).abs() <= 1,
-                "Red mismatch: {} vs {}",
-                r,
-                r2
-            );
-            assert!(
-                (g as i16 - g2 as i16).abs() <= 1,
-                "Green mismatch: {} vs {}",
-                g,
-                g2
-            );
-            assert!(
-                (b as i16 - b2 as i16).abs() <= 1,
-                "Blue mismatch: {} vs {}",
-                b,
-                b2
-            );
+/// Conversion constants for one matrix and range.
+#[derive(Debug, Clone, Copy)]
+struct YuvCoefficients {
+    kr: f32,
+    kb: f32,
+    y_offset: f32,
+    y_scale: f32,
+    c_scale: f32,
+}
+
+impl YuvCoefficients {
+    fn new(matrix: YuvMatrix, range: YuvRange) -> Self {
+        let (kr, kb) = match matrix {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        };
+        let (y_offset, y_scale, c_scale) = match range {
+            YuvRange::Full => (0.0, 1.0, 1.0),
+            YuvRange::Limited => (16.0, 219.0 / 255.0, 224.0 / 255.0),
+        };
+        Self {
+            kr,
+            kb,
+            y_offset,
+            y_scale,
+            c_scale,
         }
     }
 
-    #[test]
-    fn test_rgb_to_hsl_pure_colors() {
-        // Red
-        let (h, s, l) = rgb_to_hsl(255, 0, 0);
-        assert!((h - 0.0).abs() < 0.1);
-        assert!((s - 1.0).abs() < 0.01);
-        assert!((l - 0.5).abs() < 0.01);
+    fn to_rgb(self, y: u8, u: u8, v: u8) -> (u8, u8, u8) {
+        let y = (y as f32 - self.y_offset) / self.y_scale;
+        let cb = (u as f32 - 128.0) / self.c_scale;
+        let cr = (v as f32 - 128.0) / self.c_scale;
 
-        // Green
-        let (h, s, l) = rgb_to_hsl(0, 255, 0);
-        assert!((h - 120.0).abs() < 0.1);
-        assert!((s - 1.0).abs() < 0.01);
-        assert!((l - 0.5).abs() < 0.01);
+        let r = y + 2.0 * (1.0 - self.kr) * cr;
+        let b = y + 2.0 * (1.0 - self.kb) * cb;
+        let g = (y - self.kr * r - self.kb * b) / (1.0 - self.kr - self.kb);
 
-        // Blue
-        let (h, s, l) = rgb_to_hsl(0, 0, 255);
-        assert!((h - 240.0).abs() < 0.1);
-        assert!((s - 1.0).abs() < 0.01);
-        assert!((l - 0.5).abs() < 0.01);
+        (round_u8(r), round_u8(g), round_u8(b))
     }
 
-    #[test]
-    fn test_hsl_to_rgb_pure_colors() {
-        // Red
-        let (r, g, b) = hsl_to_rgb(0.0, 1.0, 0.5);
-        assert_eq!(r, 255);
-        assert_eq!(g, 0);
-        assert_eq!(b, 0);
-
-        // Green
-        let (r, g, b) = hsl_to_rgb(120.0, 1.0, 0.5);
-        assert_eq!(r, 0);
-        assert_eq!(g, 255);
-        assert_eq!(b, 0);
+    fn luma(self, r: f32, g: f32, b: f32) -> u8 {
+        let y = self.kr * r + (1.0 - self.kr - self.kb) * g + self.kb * b;
+        round_u8(self.y_offset + y * self.y_scale)
+    }
 
-        // Blue
-        let (r, g, b) = hsl_to_rgb(240.0, 1.0, 0.5);
-        assert_eq!(r, 0);
-        assert_eq!(g, 0);
-        assert_eq!(b, 255);
+    fn chroma(self, r: f32, g: f32, b: f32) -> (u8, u8) {
+        let y = self.kr * r + (1.0 - self.kr - self.kb) * g + self.kb * b;
+        let cb = (b - y) / (2.0 * (1.0 - self.kb));
+        let cr = (r - y) / (2.0 * (1.0 - self.kr));
+        (
+            round_u8(128.0 + cb * self.c_scale),
+            round_u8(128.0 + cr * self.c_scale),
+        )
     }
+}
 
-    #[test]
-    fn test_rgb_hsl_roundtrip() {
-        let test_colors = vec![
-            (255, 0, 0),
-            (0, 255, 0),
-            (0, 0, 255),
-            (255, 255, 0),
-            (255, 0, 255),
-            (0, 255, 255),
-            (128, 64, 192),
-        ];
+/// Rounds and saturates a sample to the 0-255 range.
+fn round_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 255.0) + 0.5) as u8
+}
 
-        for (r, g, b) in test_colors {
-            let (h, s, l) = rgb_to_hsl(r, g, b);
-            let (r2, g2, b2) = hsl_to_rgb(h, s, l);
-            assert!(
-                (r as i16 - r2 as i16).abs() <= 1,
-                "Red mismatch: {} vs {}",
-                r,
-                r2
-            );
-            assert!(
-                (g as i16 - g2 as i16).abs() <= 1,
-                "Green mismatch: {} vs {}",
-                g,
-                g2
-            );
+/// Converts a YUV (YCbCr) sample to RGB color space.
+///
+/// # Arguments
+///
+/// * `y` - Luma
+/// * `u` - Blue-difference chroma (Cb)
+/// * `v` - Red-difference chroma (Cr)
+/// * `matrix` - The standard the sample was encoded with
+/// * `range` - The value range of the sample
+///
+/// # Returns
+///
+/// A tuple (r, g, b) where each component is in range (0-255)
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::{yuv_to_rgb, YuvMatrix, YuvRange};
+///
+/// let (r, g, b) = yuv_to_rgb(235, 128, 128, YuvMatrix::Bt709, YuvRange::Limited); // White
+/// assert_eq!((r, g, b), (255, 255, 255));
+/// ```
+pub fn yuv_to_rgb(y: u8, u: u8, v: u8, matrix: YuvMatrix, range: YuvRange) -> (u8, u8, u8) {
+    YuvCoefficients::new(matrix, range).to_rgb(y, u, v)
+}
+
+/// Converts RGB color values to a YUV (YCbCr) sample.
+///
+/// # Arguments
+///
+/// * `r` - Red component (0-255)
+/// * `g` - Green component (0-255)
+/// * `b` - Blue component (0-255)
+/// * `matrix` - The standard to encode with
+/// * `range` - The value range of the result
+///
+/// # Returns
+///
+/// A tuple (y, u, v) with the luma and the blue- and red-difference chroma
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::{rgb_to_yuv, YuvMatrix, YuvRange};
+///
+/// let (y, u, v) = rgb_to_yuv(0, 0, 0, YuvMatrix::Bt601, YuvRange::Limited); // Black
+/// assert_eq!((y, u, v), (16, 128, 128));
+/// ```
+pub fn rgb_to_yuv(r: u8, g: u8, b: u8, matrix: YuvMatrix, range: YuvRange) -> (u8, u8, u8) {
+    let coefficients = YuvCoefficients::new(matrix, range);
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let (u, v) = coefficients.chroma(r, g, b);
+    (coefficients.luma(r, g, b), u, v)
+}
+
+/// Returns an error if `data` is too small for the layout.
+fn check_yuv_len(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    format: YuvFormat,
+) -> Result<(), YuvBufferSizeError> {
+    let expected = format.buffer_len(width, height);
+    if data.len() < expected {
+        return Err(YuvBufferSizeError {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the Y, U and V samples of pixel (x, y).
+fn yuv_sample(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    format: YuvFormat,
+    x: usize,
+    y: usize,
+) -> (u8, u8, u8) {
+    let (chroma_width, chroma_height) = format.chroma_size(width, height);
+    let cx = x / 2;
+    match format {
+        YuvFormat::Nv12 | YuvFormat::Nv21 => {
+            let c = width * height + (y / 2 * chroma_width + cx) * 2;
+            let luma = data[y * width + x];
+            if format == YuvFormat::Nv12 {
+                (luma, data[c], data[c + 1])
+            } else {
+                (luma, data[c + 1], data[c])
+            }
+        }
+        YuvFormat::I420 | YuvFormat::I422 | YuvFormat::I444 => {
+            let u_plane = width * height;
+            let v_plane = u_plane + chroma_width * chroma_height;
+            let step_x = if chroma_width == width { 1 } else { 2 };
+            let step_y = if chroma_height == height { 1 } else { 2 };
+            let c = y / step_y * chroma_width + x / step_x;
+            (data[y * width + x], data[u_plane + c], data[v_plane + c])
+        }
+        YuvFormat::Yuyv => {
+            let p = (y * chroma_width + cx) * 4;
+            (data[p + (x % 2) * 2], data[p + 1], data[p + 3])
+        }
+        YuvFormat::Uyvy => {
+            let p = (y * chroma_width + cx) * 4;
+            (data[p + 1 + (x % 2) * 2], data[p], data[p + 2])
+        }
+    }
+}
+
+/// Arranges a luma plane and interleaved U/V chroma samples in the given layout.
+fn pack_yuv(luma: &[u8], chroma: &[u8], width: usize, height: usize, format: YuvFormat) -> Vec<u8> {
+    let mut out = Vec::with_capacity(format.buffer_len(width, height));
+    match format {
+        YuvFormat::Nv12 => {
+            out.extend_from_slice(luma);
+            out.extend_from_slice(chroma);
+        }
+        YuvFormat::Nv21 => {
+            out.extend_from_slice(luma);
+            out.extend(chroma.chunks_exact(2).flat_map(|uv| [uv[1], uv[0]]));
+        }
+        YuvFormat::I420 | YuvFormat::I422 | YuvFormat::I444 => {
+            out.extend_from_slice(luma);
+            out.extend(chroma.iter().step_by(2));
+            out.extend(chroma.iter().skip(1).step_by(2));
+        }
+        YuvFormat::Yuyv | YuvFormat::Uyvy => {
+            let chroma_width = width.div_ceil(2);
+            for y in 0..height {
+                let row = &luma[y * width..(y + 1) * width];
+                for cx in 0..chroma_width {
+                    let y0 = row[cx * 2];
+                    let y1 = row[(cx * 2 + 1).min(width - 1)];
+                    let c = (y * chroma_width + cx) * 2;
+                    let (u, v) = (chroma[c], chroma[c + 1]);
+                    if format == YuvFormat::Yuyv {
+                        out.extend_from_slice(&[y0, u, y1, v]);
+                    } else {
+                        out.extend_from_slice(&[u, y0, v, y1]);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Calls `f` with the index and contents of every row of `data`.
+///
+/// Rows are processed in parallel when the `parallel` feature is enabled.
+fn for_each_row<T: Send, F>(data: &mut [T], row_len: usize, f: F)
+where
+    F: Fn(usize, &mut [T]) + Send + Sync,
+{
+    if row_len == 0 {
+        return;
+    }
+
+    #[cfg(feature = "parallel")]
+    data.par_chunks_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+}
+
+impl Matrix3 {
+    /// Converts a raw YUV frame to an RGB image.
+    ///
+    /// Rows are converted in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The frame, laid out as described by `format`
+    /// * `width` - Width of the frame in pixels
+    /// * `height` - Height of the frame in pixels
+    /// * `format` - Memory layout of the frame
+    /// * `matrix` - The standard the frame was encoded with
+    /// * `range` - The value range of the samples
+    ///
+    /// # Returns
+    ///
+    /// The RGB image, or a `YuvBufferSizeError` if `data` is shorter than
+    /// [`YuvFormat::buffer_len`]. Trailing bytes are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, YuvFormat, YuvMatrix, YuvRange};
+    ///
+    /// let frame = vec![128u8; YuvFormat::Nv12.buffer_len(4, 2)];
+    /// let image =
+    ///     Matrix3::from_yuv(&frame, 4, 2, YuvFormat::Nv12, YuvMatrix::Bt601, YuvRange::Full)
+    ///         .unwrap();
+    /// assert_eq!(image.get_pixel(0, 0), Some((128, 128, 128)));
+    /// ```
+    pub fn from_yuv(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        format: YuvFormat,
+        matrix: YuvMatrix,
+        range: YuvRange,
+    ) -> Result<Self, YuvBufferSizeError> {
+        check_yuv_len(data, width, height, format)?;
+        let coefficients = YuvCoefficients::new(matrix, range);
+
+        let mut rgb = vec![0u8; width * height * 3];
+        for_each_row(&mut rgb, width * 3, |y, row| {
+            for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+                let (luma, u, v) = yuv_sample(data, width, height, format, x, y);
+                let (r, g, b) = coefficients.to_rgb(luma, u, v);
+                pixel.copy_from_slice(&[r, g, b]);
+            }
+        });
+
+        Ok(Matrix3::new(width, height, rgb))
+    }
+
+    /// Converts the image to a raw YUV frame.
+    ///
+    /// Chroma is averaged over the pixels that share a chroma sample. Rows are
+    /// converted in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Memory layout of the frame
+    /// * `matrix` - The standard to encode with
+    /// * `range` - The value range of the samples
+    ///
+    /// # Returns
+    ///
+    /// The frame, [`YuvFormat::buffer_len`] bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, YuvFormat, YuvMatrix, YuvRange};
+    ///
+    /// let image = Matrix3::zeros(4, 2);
+    /// let frame = image.to_yuv(YuvFormat::I420, YuvMatrix::Bt709, YuvRange::Limited);
+    /// assert_eq!(frame.len(), 12);
+    /// assert_eq!(frame[0], 16);
+    /// ```
+    pub fn to_yuv(&self, format: YuvFormat, matrix: YuvMatrix, range: YuvRange) -> Vec<u8> {
+        let (width, height) = (self.width(), self.height());
+        let coefficients = YuvCoefficients::new(matrix, range);
+        let pixel = |x: usize, y: usize| {
+            let i = (y * width + x) * 3;
+            let data = self.data();
+            (data[i] as f32, data[i + 1] as f32, data[i + 2] as f32)
+        };
+
+        let mut luma = vec![0u8; width * height];
+        for_each_row(&mut luma, width, |y, row| {
+            for (x, sample) in row.iter_mut().enumerate() {
+                let (r, g, b) = pixel(x, y);
+                *sample = coefficients.luma(r, g, b);
+            }
+        });
+
+        let (chroma_width, chroma_height) = format.chroma_size(width, height);
+        let block_width = if chroma_width == width { 1 } else { 2 };
+        let block_height = if chroma_height == height { 1 } else { 2 };
+        let mut chroma = vec![0u8; chroma_width * chroma_height * 2];
+        for_each_row(&mut chroma, chroma_width * 2, |cy, row| {
+            let rows = cy * block_height..((cy + 1) * block_height).min(height);
+            for (cx, uv) in row.chunks_exact_mut(2).enumerate() {
+                let columns = cx * block_width..((cx + 1) * block_width).min(width);
+                let (mut r, mut g, mut b, mut count) = (0.0, 0.0, 0.0, 0.0);
+                for y in rows.clone() {
+                    for x in columns.clone() {
+                        let p = pixel(x, y);
+                        r += p.0;
+                        g += p.1;
+                        b += p.2;
+                        count += 1.0;
+                    }
+                }
+                let (u, v) = coefficients.chroma(r / count, g / count, b / count);
+                uv.copy_from_slice(&[u, v]);
+            }
+        });
+
+        pack_yuv(&luma, &chroma, width, height, format)
+    }
+}
+
+impl Matrix1 {
+    /// Extracts the Y (luma) plane of a raw YUV frame as a grayscale image.
+    ///
+    /// The samples are copied unchanged, so limited-range frames keep their
+    /// 16-235 luma range.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The frame, laid out as described by `format`
+    /// * `width` - Width of the frame in pixels
+    /// * `height` - Height of the frame in pixels
+    /// * `format` - Memory layout of the frame
+    ///
+    /// # Returns
+    ///
+    /// The luma plane, or a `YuvBufferSizeError` if `data` is shorter than
+    /// [`YuvFormat::buffer_len`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix1, YuvFormat};
+    ///
+    /// let frame = [10, 128, 20, 128, 30, 128, 40, 128];
+    /// let luma = Matrix1::from_yuv(&frame, 2, 2, YuvFormat::Yuyv).unwrap();
+    /// assert_eq!(luma.data(), &[10, 20, 30, 40]);
+    /// ```
+    pub fn from_yuv(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        format: YuvFormat,
+    ) -> Result<Self, YuvBufferSizeError> {
+        check_yuv_len(data, width, height, format)?;
+
+        let luma = match format {
+            YuvFormat::Nv12
+            | YuvFormat::Nv21
+            | YuvFormat::I420
+            | YuvFormat::I422
+            | YuvFormat::I444 => data[..width * height].to_vec(),
+            YuvFormat::Yuyv | YuvFormat::Uyvy => {
+                let mut luma = vec![0u8; width * height];
+                for_each_row(&mut luma, width, |y, row| {
+                    for (x, sample) in row.iter_mut().enumerate() {
+                        *sample = yuv_sample(data, width, height, format, x, y).0;
+                    }
+                });
+                luma
+            }
+        };
+
+        Ok(Matrix1::new(width, height, luma))
+    }
+
+    /// Converts the grayscale image to a raw YUV frame with neutral chroma.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Memory layout of the frame
+    /// * `range` - The value range of the samples
+    ///
+    /// # Returns
+    ///
+    /// The frame, [`YuvFormat::buffer_len`] bytes long.
+    pub fn to_yuv(&self, format: YuvFormat, range: YuvRange) -> Vec<u8> {
+        let (width, height) = (self.width(), self.height());
+        // Gray maps to the same luma under every matrix
+        let coefficients = YuvCoefficients::new(YuvMatrix::Bt601, range);
+
+        let mut luma = vec![0u8; width * height];
+        for_each_row(&mut luma, width, |y, row| {
+            let source = &self.data()[y * width..(y + 1) * width];
+            for (sample, &value) in row.iter_mut().zip(source) {
+                let value = value as f32;
+                *sample = coefficients.luma(value, value, value);
+            }
+        });
+
+        let (chroma_width, chroma_height) = format.chroma_size(width, height);
+        let chroma = vec![128u8; chroma_width * chroma_height * 2];
+        pack_yuv(&luma, &chroma, width, height, format)
+    }
+}
+
+/// Converts an sRGB-encoded sample to linear light.
+///
+/// Image bytes are gamma-encoded, so averaging them (as blurring, resizing and
+/// blending do) gives results that are too dark. Converting to linear light first
+/// makes such operations physically correct. For 8-bit samples, [`SrgbLut`] is faster.
+///
+/// # Arguments
+///
+/// * `c` - Encoded sample in the range 0.0-1.0
+///
+/// # Returns
+///
+/// The linear-light intensity in the range 0.0-1.0.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::srgb_to_linear;
+///
+/// // Mid-gray in sRGB is only about a fifth of white's light
+/// assert!((srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+/// ```
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        libm::powf((c + 0.055) / 1.055, 2.4)
+    }
+}
+
+/// Converts a linear-light intensity to sRGB encoding.
+///
+/// This is the inverse of [`srgb_to_linear`].
+///
+/// # Arguments
+///
+/// * `c` - Linear intensity in the range 0.0-1.0
+///
+/// # Returns
+///
+/// The encoded sample in the range 0.0-1.0.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::linear_to_srgb;
+///
+/// assert!((linear_to_srgb(0.214) - 0.5).abs() < 0.001);
+/// ```
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * libm::powf(c, 1.0 / 2.4) - 0.055
+    }
+}
+
+/// Lookup tables for converting 8-bit sRGB samples to and from linear light.
+///
+/// Decoding is a table lookup and encoding a binary search over the rounding
+/// thresholds, so both avoid `powf` and give the same results as rounding
+/// [`linear_to_srgb`]. Building the tables takes about 2 KB.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::SrgbLut;
+///
+/// let lut = SrgbLut::new();
+/// // Averaging black and white in linear light gives a lighter gray than 128
+/// let mid = (lut.to_linear(0) + lut.to_linear(255)) / 2.0;
+/// assert_eq!(lut.to_srgb(mid), 188);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SrgbLut {
+    decode: [f32; 256],
+    /// Linear intensity at which the encoded value rounds up from `i` to `i + 1`
+    thresholds: [f32; 255],
+}
+
+impl SrgbLut {
+    /// Builds the lookup tables.
+    pub fn new() -> Self {
+        let mut decode = [0.0; 256];
+        for (i, value) in decode.iter_mut().enumerate() {
+            *value = srgb_to_linear(i as f32 / 255.0);
+        }
+        let mut thresholds = [0.0; 255];
+        for (i, value) in thresholds.iter_mut().enumerate() {
+            *value = srgb_to_linear((i as f32 + 0.5) / 255.0);
+        }
+        Self { decode, thresholds }
+    }
+
+    /// Converts an 8-bit sRGB sample to linear light in the range 0.0-1.0.
+    #[inline]
+    pub fn to_linear(&self, value: u8) -> f32 {
+        self.decode[value as usize]
+    }
+
+    /// Converts a linear-light intensity to the nearest 8-bit sRGB sample.
+    ///
+    /// Values outside 0.0-1.0 saturate.
+    #[inline]
+    pub fn to_srgb(&self, linear: f32) -> u8 {
+        self.thresholds.partition_point(|&t| t <= linear) as u8
+    }
+}
+
+impl Default for SrgbLut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Matrix3 {
+    /// Converts the image to linear-light samples.
+    ///
+    /// Rows are converted in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Returns
+    ///
+    /// Interleaved linear intensities in the range 0.0-1.0, in the same order as
+    /// [`Matrix3::data`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let image = Matrix3::new(1, 1, vec![0, 128, 255]);
+    /// let linear = image.to_linear();
+    /// assert!((linear[1] - 0.2158).abs() < 0.001);
+    /// let back = Matrix3::from_linear(&linear, 1, 1);
+    /// assert_eq!(back.data(), image.data());
+    /// ```
+    pub fn to_linear(&self) -> Vec<f32> {
+        let lut = SrgbLut::new();
+        let mut data = vec![0f32; self.data().len()];
+        let source = self.data();
+        for_each_row(&mut data, self.width() * 3, |y, row| {
+            let start = y * row.len();
+            let end = start + row.len();
+            for (out, &value) in row.iter_mut().zip(&source[start..end]) {
+                *out = lut.to_linear(value);
+            }
+        });
+        data
+    }
+
+    /// Builds an image from linear-light samples, encoding them as sRGB.
+    ///
+    /// This is the inverse of [`Matrix3::to_linear`]. Values outside 0.0-1.0 saturate.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Interleaved linear RGB intensities
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data length doesn't match width * height * 3.
+    pub fn from_linear(data: &[f32], width: usize, height: usize) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height * 3,
+            "Data length must be width * height * 3"
+        );
+
+        let lut = SrgbLut::new();
+        let mut rgb = vec![0u8; data.len()];
+        for_each_row(&mut rgb, width * 3, |y, row| {
+            let start = y * row.len();
+            let end = start + row.len();
+            for (out, &value) in row.iter_mut().zip(&data[start..end]) {
+                *out = lut.to_srgb(value);
+            }
+        });
+        Matrix3::new(width, height, rgb)
+    }
+}
+
+/// Color spaces supported by [`Matrix3::convert_color`].
+///
+/// RGB samples are treated as sRGB. XYZ, L\*a\*b\* and L\*u\*v\* use the D65 white
+/// point and are computed from linearized sRGB. The 8-bit representation scales each
+/// channel to 0-255 the way OpenCV does; the `f32` representation keeps natural units.
+/// Because 8-bit XYZ holds linear light, it cannot resolve dark shades and should only
+/// be used for display; use the `f32` representation for measurements.
+///
+/// | Space   | `f32` channels                                   | `u8` channels                               |
+/// |---------|--------------------------------------------------|---------------------------------------------|
+/// | `Hsv`   | H 0-360°, S 0-1, V 0-1                            | H / 2, S × 255, V × 255                     |
+/// | `Hsl`   | H 0-360°, S 0-1, L 0-1                            | H / 2, S × 255, L × 255                     |
+/// | `YCbCr` | Y 0-1, Cb -0.5-0.5, Cr -0.5-0.5 (BT.601 full range) | Y × 255, Cb × 255 + 128, Cr × 255 + 128 |
+/// | `Xyz`   | X, Y, Z with Y 0-1                                | X × 255, Y × 255, Z × 255 (saturated)       |
+/// | `Lab`   | L 0-100, a and b about -128-127                   | L × 255 / 100, a + 128, b + 128             |
+/// | `Luv`   | L 0-100, u -134-220, v -140-122                   | L × 255 / 100, (u + 134) × 255 / 354, (v + 140) × 255 / 262 |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Hue, saturation, value
+    Hsv,
+    /// Hue, saturation, lightness
+    Hsl,
+    /// Full-range BT.601 luma and chroma, as used by JPEG
+    YCbCr,
+    /// CIE 1931 XYZ
+    Xyz,
+    /// CIE L\*a\*b\*
+    Lab,
+    /// CIE L\*u\*v\*
+    Luv,
+}
+
+/// D65 reference white in XYZ.
+const WHITE_D65: [f32; 3] = [0.95047, 1.0, 1.08883];
+
+/// L\*u\*v\* chromaticity (u', v') of the D65 white point.
+const WHITE_UV: (f32, f32) = (0.197_839, 0.468_336);
+
+/// Threshold between the linear and cube-root segments of the CIE lightness curve.
+const CIE_EPSILON: f32 = 216.0 / 24389.0;
+
+/// Slope of the linear segment of the CIE lightness curve.
+const CIE_KAPPA: f32 = 24389.0 / 27.0;
+
+/// Computes the hue in degrees plus the largest and smallest component of an RGB color.
+fn hue_max_min(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    (if h < 0.0 { h + 360.0 } else { h }, max, min)
+}
+
+/// Builds an RGB color from a hue in degrees, a chroma and the smallest component.
+fn rgb_from_hue(h: f32, c: f32, m: f32) -> [f32; 3] {
+    let h = h % 360.0;
+    let h_prime = if h < 0.0 { h + 360.0 } else { h } / 60.0;
+    let x = c * (1.0 - ((h_prime % 2.0) - 1.0).abs());
+
+    let (r, g, b) = if h_prime < 1.0 {
+        (c, x, 0.0)
+    } else if h_prime < 2.0 {
+        (x, c, 0.0)
+    } else if h_prime < 3.0 {
+        (0.0, c, x)
+    } else if h_prime < 4.0 {
+        (0.0, x, c)
+    } else if h_prime < 5.0 {
+        (x, 0.0, c)
+    } else {
+        (c, 0.0, x)
+    };
+
+    [r + m, g + m, b + m]
+}
+
+/// The CIE lightness companding function.
+fn cie_f(t: f32) -> f32 {
+    if t > CIE_EPSILON {
+        libm::cbrtf(t)
+    } else {
+        (CIE_KAPPA * t + 16.0) / 116.0
+    }
+}
+
+/// The inverse of [`cie_f`].
+fn cie_f_inv(t: f32) -> f32 {
+    let cube = t * t * t;
+    if cube > CIE_EPSILON {
+        cube
+    } else {
+        (116.0 * t - 16.0) / CIE_KAPPA
+    }
+}
+
+impl ColorSpace {
+    /// Converts an sRGB color with components in 0-1 to this space, in `f32` units.
+    fn rgb_to_space(self, [r, g, b]: [f32; 3]) -> [f32; 3] {
+        match self {
+            ColorSpace::Hsv => {
+                let (h, max, min) = hue_max_min(r, g, b);
+                let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+                [h, s, max]
+            }
+            ColorSpace::Hsl => {
+                let (h, max, min) = hue_max_min(r, g, b);
+                let l = (max + min) / 2.0;
+                let s = if max == min {
+                    0.0
+                } else {
+                    (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+                };
+                [h, s, l]
+            }
+            ColorSpace::YCbCr => {
+                let y = 0.299 * r + 0.587 * g + 0.114 * b;
+                [y, (b - y) / 1.772, (r - y) / 1.402]
+            }
+            ColorSpace::Xyz => {
+                let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+                [
+                    0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+                    0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+                    0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+                ]
+            }
+            ColorSpace::Lab => {
+                let [x, y, z] = ColorSpace::Xyz.rgb_to_space([r, g, b]);
+                let fx = cie_f(x / WHITE_D65[0]);
+                let fy = cie_f(y / WHITE_D65[1]);
+                let fz = cie_f(z / WHITE_D65[2]);
+                [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+            }
+            ColorSpace::Luv => {
+                let [x, y, z] = ColorSpace::Xyz.rgb_to_space([r, g, b]);
+                let l = 116.0 * cie_f(y) - 16.0;
+                let denom = x + 15.0 * y + 3.0 * z;
+                if denom == 0.0 {
+                    return [l, 0.0, 0.0];
+                }
+                let u = 4.0 * x / denom;
+                let v = 9.0 * y / denom;
+                [l, 13.0 * l * (u - WHITE_UV.0), 13.0 * l * (v - WHITE_UV.1)]
+            }
+        }
+    }
+
+    /// Converts a color in this space, in `f32` units, to sRGB with components in 0-1.
+    ///
+    /// Colors outside the sRGB gamut are not clamped.
+    fn space_to_rgb(self, [c0, c1, c2]: [f32; 3]) -> [f32; 3] {
+        match self {
+            ColorSpace::Hsv => {
+                let c = c2 * c1;
+                rgb_from_hue(c0, c, c2 - c)
+            }
+            ColorSpace::Hsl => {
+                let c = (1.0 - (2.0 * c2 - 1.0).abs()) * c1;
+                rgb_from_hue(c0, c, c2 - c / 2.0)
+            }
+            ColorSpace::YCbCr => {
+                let r = c0 + 1.402 * c2;
+                let b = c0 + 1.772 * c1;
+                let g = (c0 - 0.299 * r - 0.114 * b) / 0.587;
+                [r, g, b]
+            }
+            ColorSpace::Xyz => {
+                let r = 3.240_454_2 * c0 - 1.537_138_5 * c1 - 0.498_531_4 * c2;
+                let g = -0.969_266 * c0 + 1.876_010_8 * c1 + 0.041_556 * c2;
+                let b = 0.055_643_4 * c0 - 0.204_025_9 * c1 + 1.057_225_2 * c2;
+                [
+                    linear_to_srgb(r.max(0.0)),
+                    linear_to_srgb(g.max(0.0)),
+                    linear_to_srgb(b.max(0.0)),
+                ]
+            }
+            ColorSpace::Lab => {
+                let fy = (c0 + 16.0) / 116.0;
+                let fx = fy + c1 / 500.0;
+                let fz = fy - c2 / 200.0;
+                ColorSpace::Xyz.space_to_rgb([
+                    cie_f_inv(fx) * WHITE_D65[0],
+                    cie_f_inv(fy) * WHITE_D65[1],
+                    cie_f_inv(fz) * WHITE_D65[2],
+                ])
+            }
+            ColorSpace::Luv => {
+                if c0 <= 0.0 {
+                    return [0.0; 3];
+                }
+                let u = c1 / (13.0 * c0) + WHITE_UV.0;
+                let v = c2 / (13.0 * c0) + WHITE_UV.1;
+                let y = cie_f_inv((c0 + 16.0) / 116.0);
+                let x = y * 9.0 * u / (4.0 * v);
+                let z = y * (12.0 - 3.0 * u - 20.0 * v) / (4.0 * v);
+                ColorSpace::Xyz.space_to_rgb([x, y, z])
+            }
+        }
+    }
+
+    /// Scales a color in `f32` units to the 8-bit representation.
+    fn scale_u8(self, [c0, c1, c2]: [f32; 3]) -> [u8; 3] {
+        let [c0, c1, c2] = match self {
+            ColorSpace::Hsv | ColorSpace::Hsl => [c0 / 2.0, c1 * 255.0, c2 * 255.0],
+            ColorSpace::YCbCr => [c0 * 255.0, c1 * 255.0 + 128.0, c2 * 255.0 + 128.0],
+            ColorSpace::Xyz => [c0 * 255.0, c1 * 255.0, c2 * 255.0],
+            ColorSpace::Lab => [c0 * 255.0 / 100.0, c1 + 128.0, c2 + 128.0],
+            ColorSpace::Luv => [
+                c0 * 255.0 / 100.0,
+                (c1 + 134.0) * 255.0 / 354.0,
+                (c2 + 140.0) * 255.0 / 262.0,
+            ],
+        };
+        [round_u8(c0), round_u8(c1), round_u8(c2)]
+    }
+
+    /// Converts an 8-bit color back to `f32` units.
+    fn unscale_u8(self, [c0, c1, c2]: [u8; 3]) -> [f32; 3] {
+        let (c0, c1, c2) = (c0 as f32, c1 as f32, c2 as f32);
+        match self {
+            ColorSpace::Hsv | ColorSpace::Hsl => [c0 * 2.0, c1 / 255.0, c2 / 255.0],
+            ColorSpace::YCbCr => [c0 / 255.0, (c1 - 128.0) / 255.0, (c2 - 128.0) / 255.0],
+            ColorSpace::Xyz => [c0 / 255.0, c1 / 255.0, c2 / 255.0],
+            ColorSpace::Lab => [c0 * 100.0 / 255.0, c1 - 128.0, c2 - 128.0],
+            ColorSpace::Luv => [
+                c0 * 100.0 / 255.0,
+                c1 * 354.0 / 255.0 - 134.0,
+                c2 * 262.0 / 255.0 - 140.0,
+            ],
+        }
+    }
+}
+
+/// Converts an sRGB color with components in 0-1 to 8-bit samples.
+fn rgb_to_u8([r, g, b]: [f32; 3]) -> [u8; 3] {
+    [
+        round_u8(r * 255.0),
+        round_u8(g * 255.0),
+        round_u8(b * 255.0),
+    ]
+}
+
+impl Matrix3 {
+    /// Converts an RGB image to another color space with 8-bit channels.
+    ///
+    /// Channels are scaled to 0-255 as described in [`ColorSpace`], matching OpenCV's
+    /// 8-bit conversions, so the result can be thresholded or filtered like any other
+    /// image. Rows are converted in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The target color space
+    ///
+    /// # Returns
+    ///
+    /// A new image holding the converted channels in the order given by `space`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{ColorSpace, Matrix3};
+    ///
+    /// let mut image = Matrix3::zeros(2, 1);
+    /// image.set_pixel(0, 0, 0, 0, 255);
+    /// let hsv = image.convert_color(ColorSpace::Hsv);
+    /// assert_eq!(hsv.get_pixel(0, 0), Some((120, 255, 255))); // 240° / 2
+    /// ```
+    pub fn convert_color(&self, space: ColorSpace) -> Matrix3 {
+        let mut data = vec![0u8; self.data().len()];
+        let source = self.data();
+        for_each_row(&mut data, self.width() * 3, |y, row| {
+            let start = y * row.len();
+            let pixels = source[start..start + row.len()].chunks_exact(3);
+            for (out, rgb) in row.chunks_exact_mut(3).zip(pixels) {
+                let color = [rgb[0], rgb[1], rgb[2]].map(|c| c as f32 / 255.0);
+                out.copy_from_slice(&space.scale_u8(space.rgb_to_space(color)));
+            }
+        });
+        Matrix3::new(self.width(), self.height(), data)
+    }
+
+    /// Converts an RGB image to another color space with `f32` channels.
+    ///
+    /// Unlike [`Matrix3::convert_color`], channels keep their natural units (for
+    /// example hue in degrees and L\* in 0-100, see [`ColorSpace`]), which avoids
+    /// quantization when measuring color differences.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The target color space
+    ///
+    /// # Returns
+    ///
+    /// The converted channels, interleaved in row-major order like [`Matrix3::data`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{ColorSpace, Matrix3};
+    ///
+    /// let white = Matrix3::new(1, 1, vec![255, 255, 255]);
+    /// let lab = white.convert_color_f32(ColorSpace::Lab);
+    /// assert!((lab[0] - 100.0).abs() < 0.01);
+    /// assert!(lab[1].abs() < 0.01 && lab[2].abs() < 0.01);
+    /// ```
+    pub fn convert_color_f32(&self, space: ColorSpace) -> Vec<f32> {
+        let mut data = vec![0f32; self.data().len()];
+        let source = self.data();
+        for_each_row(&mut data, self.width() * 3, |y, row| {
+            let start = y * row.len();
+            let pixels = source[start..start + row.len()].chunks_exact(3);
+            for (out, rgb) in row.chunks_exact_mut(3).zip(pixels) {
+                let color = [rgb[0], rgb[1], rgb[2]].map(|c| c as f32 / 255.0);
+                out.copy_from_slice(&space.rgb_to_space(color));
+            }
+        });
+        data
+    }
+
+    /// Converts an image with 8-bit channels in another color space back to RGB.
+    ///
+    /// This is the inverse of [`Matrix3::convert_color`]. Colors outside the sRGB gamut
+    /// are saturated.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The color space the channels of this image are in
+    ///
+    /// # Returns
+    ///
+    /// A new RGB image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{ColorSpace, Matrix3};
+    ///
+    /// let image = Matrix3::new(1, 1, vec![200, 120, 40]);
+    /// let lab = image.convert_color(ColorSpace::Lab);
+    /// let back = lab.convert_color_to_rgb(ColorSpace::Lab);
+    /// let (r, g, b) = back.get_pixel(0, 0).unwrap();
+    /// assert!(r.abs_diff(200) <= 2 && g.abs_diff(120) <= 2 && b.abs_diff(40) <= 2);
+    /// ```
+    pub fn convert_color_to_rgb(&self, space: ColorSpace) -> Matrix3 {
+        let mut data = vec![0u8; self.data().len()];
+        let source = self.data();
+        for_each_row(&mut data, self.width() * 3, |y, row| {
+            let start = y * row.len();
+            let pixels = source[start..start + row.len()].chunks_exact(3);
+            for (out, color) in row.chunks_exact_mut(3).zip(pixels) {
+                let color = space.unscale_u8([color[0], color[1], color[2]]);
+                out.copy_from_slice(&rgb_to_u8(space.space_to_rgb(color)));
+            }
+        });
+        Matrix3::new(self.width(), self.height(), data)
+    }
+
+    /// Builds an RGB image from `f32` channels in another color space.
+    ///
+    /// This is the inverse of [`Matrix3::convert_color_f32`]. Colors outside the sRGB
+    /// gamut are saturated.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Interleaved channels in the units described by [`ColorSpace`]
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `space` - The color space of `data`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data length doesn't match width * height * 3.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{ColorSpace, Matrix3};
+    ///
+    /// // Fully saturated green at half brightness
+    /// let image = Matrix3::from_color_f32(&[120.0, 1.0, 0.5], 1, 1, ColorSpace::Hsv);
+    /// assert_eq!(image.get_pixel(0, 0), Some((0, 128, 0)));
+    /// ```
+    pub fn from_color_f32(data: &[f32], width: usize, height: usize, space: ColorSpace) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height * 3,
+            "Data length must be width * height * 3"
+        );
+
+        let mut rgb = vec![0u8; data.len()];
+        for_each_row(&mut rgb, width * 3, |y, row| {
+            let start = y * row.len();
+            let pixels = data[start..start + row.len()].chunks_exact(3);
+            for (out, color) in row.chunks_exact_mut(3).zip(pixels) {
+                let color = space.space_to_rgb([color[0], color[1], color[2]]);
+                out.copy_from_slice(&rgb_to_u8(color));
+            }
+        });
+        Matrix3::new(width, height, rgb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb_to_hsv_pure_colors() {
+        // Red
+        let (h, s, v) = rgb_to_hsv(255, 0, 0);
+        assert!((h - 0.0).abs() < 0.1);
+        assert!((s - 1.0).abs() < 0.01);
+        assert!((v - 1.0).abs() < 0.01);
+
+        // Green
+        let (h, s, v) = rgb_to_hsv(0, 255, 0);
+        assert!((h - 120.0).abs() < 0.1);
+        assert!((s - 1.0).abs() < 0.01);
+        assert!((v - 1.0).abs() < 0.01);
+
+        // Blue
+        let (h, s, v) = rgb_to_hsv(0, 0, 255);
+        assert!((h - 240.0).abs() < 0.1);
+        assert!((s - 1.0).abs() < 0.01);
+        assert!((v - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_hsv_to_rgb_pure_colors() {
+        // Red
+        let (r, g, b) = hsv_to_rgb(0.0, 1.0, 1.0);
+        assert_eq!(r, 255);
+        assert_eq!(g, 0);
+        assert_eq!(b, 0);
+
+        // Green
+        let (r, g, b) = hsv_to_rgb(120.0, 1.0, 1.0);
+        assert_eq!(r, 0);
+        assert_eq!(g, 255);
+        assert_eq!(b, 0);
+
+        // Blue
+        let (r, g, b) = hsv_to_rgb(240.0, 1.0, 1.0);
+        assert_eq!(r, 0);
+        assert_eq!(g, 0);
+        assert_eq!(b, 255);
+    }
+
+    #[test]
+    fn test_rgb_hsv_roundtrip() {
+        let test_colors = vec![
+            (255, 0, 0),
+            (0, 255, 0),
+            (0, 0, 255),
+            (255, 255, 0),
+            (255, 0, 255),
+            (0, 255, 255),
+            (128, 64, 192),
+        ];
+
+        for (r, g, b) in test_colors {
+            let (h, s, v) = rgb_to_hsv(r, g, b);
+            let (r2, g2, b2) = hsv_to_rgb(h, s, v);
+            assert!(
+                (r as i16 - r2 as i16).abs() <= 1,
+                "Red mismatch: {} vs {}",
+                r,
+                r2
+            );
+            assert!(
+                (g as i16 - g2 as i16).abs() <= 1,
+                "Green mismatch: {} vs {}",
+                g,
+                g2
+            );
+            assert!(
+                (b as i16 - b2 as i16).abs() <= 1,
+                "Blue mismatch: {} vs {}",
+                b,
+                b2
+            );
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_hsl_pure_colors() {
+        // Red
+        let (h, s, l) = rgb_to_hsl(255, 0, 0);
+        assert!((h - 0.0).abs() < 0.1);
+        assert!((s - 1.0).abs() < 0.01);
+        assert!((l - 0.5).abs() < 0.01);
+
+        // Green
+        let (h, s, l) = rgb_to_hsl(0, 255, 0);
+        assert!((h - 120.0).abs() < 0.1);
+        assert!((s - 1.0).abs() < 0.01);
+        assert!((l - 0.5).abs() < 0.01);
+
+        // Blue
+        let (h, s, l) = rgb_to_hsl(0, 0, 255);
+        assert!((h - 240.0).abs() < 0.1);
+        assert!((s - 1.0).abs() < 0.01);
+        assert!((l - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_hsl_to_rgb_pure_colors() {
+        // Red
+        let (r, g, b) = hsl_to_rgb(0.0, 1.0, 0.5);
+        assert_eq!(r, 255);
+        assert_eq!(g, 0);
+        assert_eq!(b, 0);
+
+        // Green
+        let (r, g, b) = hsl_to_rgb(120.0, 1.0, 0.5);
+        assert_eq!(r, 0);
+        assert_eq!(g, 255);
+        assert_eq!(b, 0);
+
+        // Blue
+        let (r, g, b) = hsl_to_rgb(240.0, 1.0, 0.5);
+        assert_eq!(r, 0);
+        assert_eq!(g, 0);
+        assert_eq!(b, 255);
+    }
+
+    #[test]
+    fn test_rgb_hsl_roundtrip() {
+        let test_colors = vec![
+            (255, 0, 0),
+            (0, 255, 0),
+            (0, 0, 255),
+            (255, 255, 0),
+            (255, 0, 255),
+            (0, 255, 255),
+            (128, 64, 192),
+        ];
+
+        for (r, g, b) in test_colors {
+            let (h, s, l) = rgb_to_hsl(r, g, b);
+            let (r2, g2, b2) = hsl_to_rgb(h, s, l);
+            assert!(
+                (r as i16 - r2 as i16).abs() <= 1,
+                "Red mismatch: {} vs {}",
+                r,
+                r2
+            );
+            assert!(
+                (g as i16 - g2 as i16).abs() <= 1,
+                "Green mismatch: {} vs {}",
+                g,
+                g2
+            );
             assert!(
                 (b as i16 - b2 as i16).abs() <= 1,
                 "Blue mismatch: {} vs {}",
@@ -512,4 +1613,278 @@ mod tests {
         assert_eq!(gray_avg.get_pixel(1, 1), Some(255));
         assert_eq!(gray_light.get_pixel(1, 1), Some(255));
     }
+
+    #[test]
+    fn test_yuv_sample_conversion() {
+        let full = (YuvMatrix::Bt601, YuvRange::Full);
+        assert_eq!(rgb_to_yuv(255, 255, 255, full.0, full.1), (255, 128, 128));
+        assert_eq!(rgb_to_yuv(255, 0, 0, full.0, full.1), (76, 85, 255));
+        assert_eq!(
+            rgb_to_yuv(255, 255, 255, YuvMatrix::Bt709, YuvRange::Limited),
+            (235, 128, 128)
+        );
+
+        for matrix in [YuvMatrix::Bt601, YuvMatrix::Bt709] {
+            for range in [YuvRange::Full, YuvRange::Limited] {
+                for (r, g, b) in [(255, 0, 0), (12, 200, 90), (40, 40, 40), (0, 0, 255)] {
+                    let (y, u, v) = rgb_to_yuv(r, g, b, matrix, range);
+                    let (r2, g2, b2) = yuv_to_rgb(y, u, v, matrix, range);
+                    assert!((r as i16 - r2 as i16).abs() <= 2);
+                    assert!((g as i16 - g2 as i16).abs() <= 2);
+                    assert!((b as i16 - b2 as i16).abs() <= 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_yuv_layouts() {
+        // 2x2 NV12 frame with luma 10..40, U = 100 and V = 200
+        let nv12 = [10, 20, 30, 40, 100, 200];
+        let nv21 = [10, 20, 30, 40, 200, 100];
+        let i420 = [10, 20, 30, 40, 100, 200];
+        let expected = [
+            (10, 100, 200),
+            (20, 100, 200),
+            (30, 100, 200),
+            (40, 100, 200),
+        ];
+        for (format, data) in [
+            (YuvFormat::Nv12, &nv12),
+            (YuvFormat::Nv21, &nv21),
+            (YuvFormat::I420, &i420),
+        ] {
+            for (i, &sample) in expected.iter().enumerate() {
+                assert_eq!(yuv_sample(data, 2, 2, format, i % 2, i / 2), sample);
+            }
+        }
+
+        let i422 = [10, 20, 30, 40, 1, 2, 5, 6];
+        assert_eq!(yuv_sample(&i422, 2, 2, YuvFormat::I422, 1, 1), (40, 2, 6));
+        let i444 = [10, 20, 30, 40, 1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(yuv_sample(&i444, 2, 2, YuvFormat::I444, 1, 0), (20, 2, 6));
+
+        let yuyv = [10, 100, 20, 200];
+        let uyvy = [100, 10, 200, 20];
+        assert_eq!(
+            yuv_sample(&yuyv, 2, 1, YuvFormat::Yuyv, 1, 0),
+            (20, 100, 200)
+        );
+        assert_eq!(
+            yuv_sample(&uyvy, 2, 1, YuvFormat::Uyvy, 1, 0),
+            (20, 100, 200)
+        );
+    }
+
+    #[test]
+    fn test_yuv_frame_roundtrip() {
+        // Odd dimensions with colors that are constant within each 2x2 block
+        let (width, height) = (5, 3);
+        let mut image = Matrix3::zeros(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let (bx, by) = ((x / 2) as u8, (y / 2) as u8);
+                image.set_pixel(x, y, 40 + bx * 60, 200 - by * 90, 30 + bx * 20 + by * 50);
+            }
+        }
+
+        for format in [
+            YuvFormat::Nv12,
+            YuvFormat::Nv21,
+            YuvFormat::I420,
+            YuvFormat::I422,
+            YuvFormat::I444,
+            YuvFormat::Yuyv,
+            YuvFormat::Uyvy,
+        ] {
+            let frame = image.to_yuv(format, YuvMatrix::Bt709, YuvRange::Limited);
+            assert_eq!(frame.len(), format.buffer_len(width, height));
+
+            let decoded = Matrix3::from_yuv(
+                &frame,
+                width,
+                height,
+                format,
+                YuvMatrix::Bt709,
+                YuvRange::Limited,
+            )
+            .unwrap();
+            for (a, b) in image.data().iter().zip(decoded.data()) {
+                assert!(
+                    (*a as i16 - *b as i16).abs() <= 3,
+                    "{:?}: {} vs {}",
+                    format,
+                    a,
+                    b
+                );
+            }
+
+            let luma = Matrix1::from_yuv(&frame, width, height, format).unwrap();
+            assert_eq!(
+                luma.data(),
+                &image.to_yuv(YuvFormat::I420, YuvMatrix::Bt709, YuvRange::Limited)[..15]
+            );
+        }
+    }
+
+    #[test]
+    fn test_gray_yuv() {
+        let gray = Matrix1::new(3, 1, vec![0, 128, 255]);
+        let frame = gray.to_yuv(YuvFormat::Uyvy, YuvRange::Limited);
+        assert_eq!(frame, vec![128, 16, 128, 126, 128, 235, 128, 235]);
+        assert_eq!(
+            gray.to_yuv(YuvFormat::Nv12, YuvRange::Full)[..3],
+            [0, 128, 255]
+        );
+    }
+
+    #[test]
+    fn test_yuv_buffer_too_small() {
+        let error = Matrix3::from_yuv(
+            &[0; 5],
+            2,
+            2,
+            YuvFormat::Nv12,
+            YuvMatrix::Bt601,
+            YuvRange::Full,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            YuvBufferSizeError {
+                expected: 6,
+                actual: 5
+            }
+        );
+        assert!(Matrix1::from_yuv(&[0; 7], 2, 2, YuvFormat::Yuyv).is_err());
+    }
+
+    #[test]
+    fn test_convert_color_reference_values() {
+        let red = Matrix3::new(1, 1, vec![255, 0, 0]);
+        let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 0.05);
+
+        assert!(close(
+            &red.convert_color_f32(ColorSpace::Lab),
+            &[53.24, 80.09, 67.20]
+        ));
+        assert!(close(
+            &red.convert_color_f32(ColorSpace::Luv),
+            &[53.24, 175.01, 37.76]
+        ));
+        assert!(close(
+            &red.convert_color_f32(ColorSpace::Xyz),
+            &[0.4125, 0.2127, 0.0193]
+        ));
+        assert!(close(
+            &red.convert_color_f32(ColorSpace::Hsl),
+            &[0.0, 1.0, 0.5]
+        ));
+        assert!(close(
+            &red.convert_color_f32(ColorSpace::YCbCr),
+            &[0.299, -0.1687, 0.5]
+        ));
+
+        assert_eq!(
+            red.convert_color(ColorSpace::YCbCr).get_pixel(0, 0),
+            Some((76, 85, 255))
+        );
+        assert_eq!(
+            red.convert_color(ColorSpace::Lab).get_pixel(0, 0),
+            Some((136, 208, 195))
+        );
+    }
+
+    #[test]
+    fn test_convert_color_roundtrip() {
+        let mut data = Vec::new();
+        for i in 0..64u32 {
+            data.extend_from_slice(&[(i * 4) as u8, (255 - i * 3) as u8, ((i * 37) % 256) as u8]);
+        }
+        let image = Matrix3::new(8, 8, data);
+
+        for space in [
+            ColorSpace::Hsv,
+            ColorSpace::Hsl,
+            ColorSpace::YCbCr,
+            ColorSpace::Xyz,
+            ColorSpace::Lab,
+            ColorSpace::Luv,
+        ] {
+            let floats = image.convert_color_f32(space);
+            let back = Matrix3::from_color_f32(&floats, 8, 8, space);
+            assert_eq!(back.data(), image.data(), "{:?}", space);
+
+            // Near-black channels of saturated colors sit on the steep part of the sRGB
+            // curve, so 8-bit CIE spaces lose a few levels there
+            let tolerance = match space {
+                ColorSpace::Xyz | ColorSpace::Lab | ColorSpace::Luv => 16,
+                _ => 4,
+            };
+            let back = image.convert_color(space).convert_color_to_rgb(space);
+            let mut total = 0;
+            for (a, b) in back.data().iter().zip(image.data()) {
+                assert!(a.abs_diff(*b) <= tolerance, "{:?}: {} vs {}", space, a, b);
+                total += a.abs_diff(*b) as usize;
+            }
+            assert!(total < image.data().len(), "{:?}", space);
+        }
+    }
+
+    #[test]
+    fn test_convert_color_matches_pixel_functions() {
+        let image = Matrix3::new(2, 1, vec![10, 200, 90, 250, 30, 140]);
+        let hsv = image.convert_color_f32(ColorSpace::Hsv);
+        let hsl = image.convert_color_f32(ColorSpace::Hsl);
+        for (pixel, (hsv, hsl)) in image
+            .data()
+            .chunks_exact(3)
+            .zip(hsv.chunks_exact(3).zip(hsl.chunks_exact(3)))
+        {
+            let (h, s, v) = rgb_to_hsv(pixel[0], pixel[1], pixel[2]);
+            assert_eq!([h, s, v], hsv);
+            let (h, s, l) = rgb_to_hsl(pixel[0], pixel[1], pixel[2]);
+            assert_eq!([h, s, l], hsl);
+        }
+
+        // Components landing halfway between two levels round like the image path
+        let colors = [30.0, 0.5, 0.5, 200.0, 0.3, 0.7];
+        for space in [ColorSpace::Hsv, ColorSpace::Hsl] {
+            let image = Matrix3::from_color_f32(&colors, 2, 1, space);
+            for (color, rgb) in colors.chunks_exact(3).zip(image.data().chunks_exact(3)) {
+                let pixel = match space {
+                    ColorSpace::Hsv => hsv_to_rgb(color[0], color[1], color[2]),
+                    _ => hsl_to_rgb(color[0], color[1], color[2]),
+                };
+                assert_eq!([pixel.0, pixel.1, pixel.2], rgb, "{:?}", space);
+            }
+        }
+        assert_eq!(hsv_to_rgb(30.0, 0.5, 0.5), (128, 96, 64));
+    }
+
+    #[test]
+    fn test_srgb_lut_matches_transfer_functions() {
+        let lut = SrgbLut::new();
+        for value in 0..=255u8 {
+            let linear = srgb_to_linear(value as f32 / 255.0);
+            assert_eq!(lut.to_linear(value), linear);
+            assert_eq!(lut.to_srgb(linear), value);
+        }
+        for i in 0..=1000 {
+            let linear = i as f32 / 1000.0;
+            let expected = round_u8(linear_to_srgb(linear) * 255.0);
+            assert!(lut.to_srgb(linear).abs_diff(expected) <= 1);
+        }
+        assert_eq!(lut.to_srgb(-0.5), 0);
+        assert_eq!(lut.to_srgb(2.0), 255);
+    }
+
+    #[test]
+    fn test_linear_roundtrip() {
+        let data: Vec<u8> = (0..=255).chain(0..=255).chain(0..=255).collect();
+        let image = Matrix3::new(16, 16, data);
+        let linear = image.to_linear();
+        assert!(linear.iter().all(|v| (0.0..=1.0).contains(v)));
+        assert_eq!(Matrix3::from_linear(&linear, 16, 16).data(), image.data());
+    }
 }
@@ -566,6 +566,86 @@ impl Matrix3 {
         temp.convolve_vertical(kernel_y, border_mode)
     }
 
+    /// Applies a convolution kernel to the RGB image in linear light.
+    ///
+    /// [`Matrix3::convolve`] averages the gamma-encoded sRGB bytes, which darkens
+    /// blurred edges and highlights. This variant decodes the samples to linear light
+    /// first and encodes the result again, so blurs keep the image's brightness.
+    ///
+    /// # Arguments
+    ///
+    /// * `kernel` - The convolution kernel to apply
+    /// * `border_mode` - How to handle borders
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the convolution applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{BorderMode, Kernel, Matrix3};
+    ///
+    /// let edge = Matrix3::new(2, 1, vec![0, 0, 0, 255, 255, 255]);
+    /// let kernel = Kernel::new(3, 1, vec![0.5, 0.5, 0.0]);
+    /// let blurred = edge.convolve_linear_light(&kernel, BorderMode::Replicate);
+    /// assert_eq!(blurred.get_pixel(1, 0), Some((188, 188, 188)));
+    /// ```
+    pub fn convolve_linear_light(&self, kernel: &Kernel, border_mode: BorderMode) -> Self {
+        let linear = convolve_rgb_f32(
+            &self.to_linear(),
+            self.width(),
+            self.height(),
+            kernel.data(),
+            (kernel.width(), kernel.height()),
+            border_mode,
+        );
+        Matrix3::from_linear(&linear, self.width(), self.height())
+    }
+
+    /// Applies a separable convolution to the RGB image in linear light.
+    ///
+    /// See [`Matrix3::convolve_linear_light`]. Intermediate results are kept in
+    /// floating point, so no precision is lost between the two passes.
+    ///
+    /// # Arguments
+    ///
+    /// * `kernel_x` - Horizontal 1D kernel
+    /// * `kernel_y` - Vertical 1D kernel
+    /// * `border_mode` - How to handle borders
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the convolution applied.
+    pub fn convolve_separable_linear_light(
+        &self,
+        kernel_x: &[f32],
+        kernel_y: &[f32],
+        border_mode: BorderMode,
+    ) -> Self {
+        assert!(kernel_x.len() % 2 == 1, "Kernel length must be odd");
+        assert!(kernel_y.len() % 2 == 1, "Kernel length must be odd");
+
+        let (width, height) = self.dimensions();
+        let horizontal = convolve_rgb_f32(
+            &self.to_linear(),
+            width,
+            height,
+            kernel_x,
+            (kernel_x.len(), 1),
+            border_mode,
+        );
+        let linear = convolve_rgb_f32(
+            &horizontal,
+            width,
+            height,
+            kernel_y,
+            (1, kernel_y.len()),
+            border_mode,
+        );
+        Matrix3::from_linear(&linear, width, height)
+    }
+
     /// Applies horizontal 1D convolution.
     fn convolve_horizontal(&self, kernel: &[f32], border_mode: BorderMode) -> Self {
         let width = self.width();
@@ -695,6 +775,80 @@ impl Matrix3 {
     }
 }
 
+/// Convolves interleaved RGB `f32` samples with a kernel of the given (width, height).
+fn convolve_rgb_f32(
+    data: &[f32],
+    width: usize,
+    height: usize,
+    weights: &[f32],
+    (k_width, k_height): (usize, usize),
+    border_mode: BorderMode,
+) -> Vec<f32> {
+    let mut result = vec![0f32; data.len()];
+    if width == 0 {
+        return result;
+    }
+
+    let k_half_w = (k_width / 2) as i32;
+    let k_half_h = (k_height / 2) as i32;
+    let convolve_row = |y: usize, row: &mut [f32]| {
+        for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+            let mut sum = [0.0f32; 3];
+            for ky in 0..k_height {
+                let img_y = y as i32 + ky as i32 - k_half_h;
+                let Some(img_y) = border_coordinate(img_y, height as i32, border_mode) else {
+                    continue;
+                };
+                for kx in 0..k_width {
+                    let img_x = x as i32 + kx as i32 - k_half_w;
+                    let Some(img_x) = border_coordinate(img_x, width as i32, border_mode) else {
+                        continue;
+                    };
+                    let kval = weights[ky * k_width + kx];
+                    let idx = (img_y * width + img_x) * 3;
+                    for c in 0..3 {
+                        sum[c] += data[idx + c] * kval;
+                    }
+                }
+            }
+            pixel.copy_from_slice(&sum);
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    result
+        .par_chunks_mut(width * 3)
+        .enumerate()
+        .for_each(|(y, row)| convolve_row(y, row));
+
+    #[cfg(not(feature = "parallel"))]
+    result
+        .chunks_mut(width * 3)
+        .enumerate()
+        .for_each(|(y, row)| convolve_row(y, row));
+
+    result
+}
+
+/// Maps a coordinate to the source coordinate given by the border mode.
+///
+/// Returns `None` for coordinates outside the image with [`BorderMode::Zero`].
+#[inline]
+fn border_coordinate(coord: i32, size: i32, border_mode: BorderMode) -> Option<usize> {
+    let coord = match border_mode {
+        BorderMode::Zero => {
+            if coord < 0 || coord >= size {
+                return None;
+            }
+            coord
+        }
+        BorderMode::Replicate => coord.max(0).min(size - 1),
+        BorderMode::Reflect => reflect_coordinate(coord, size),
+        BorderMode::Wrap => wrap_coordinate(coord, size),
+    };
+    Some(coord as usize)
+}
+
 /// Reflects a coordinate around the image boundary.
 #[inline]
 fn reflect_coordinate(coord: i32, size: i32) -> i32 {
@@ -828,4 +982,39 @@ mod tests {
         assert_eq!(wrap_coordinate(10, 10), 0);
         assert_eq!(wrap_coordinate(11, 10), 1);
     }
+
+    #[test]
+    fn test_convolve_linear_light() {
+        let mut image = Matrix3::zeros(6, 4);
+        for y in 0..4 {
+            for x in 3..6 {
+                image.set_pixel(x, y, 255, 255, 255);
+            }
+        }
+
+        // The identity kernel leaves the image untouched
+        let identity = Kernel::new(3, 3, vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        for mode in [
+            BorderMode::Zero,
+            BorderMode::Replicate,
+            BorderMode::Reflect,
+            BorderMode::Wrap,
+        ] {
+            let result = image.convolve_linear_light(&identity, mode);
+            assert_eq!(result.data(), image.data());
+        }
+
+        // Blurred edges are brighter than with gamma-encoded averaging
+        let kernel = Kernel::box_blur(3);
+        let linear = image.convolve_linear_light(&kernel, BorderMode::Replicate);
+        let gamma = image.convolve(&kernel, BorderMode::Replicate);
+        assert!(linear.get_pixel(2, 1).unwrap().0 > gamma.get_pixel(2, 1).unwrap().0 + 40);
+        assert_eq!(linear.get_pixel(0, 1), Some((0, 0, 0)));
+
+        let row = [1.0 / 3.0; 3];
+        let separable = image.convolve_separable_linear_light(&row, &row, BorderMode::Replicate);
+        for (a, b) in separable.data().iter().zip(linear.data()) {
+            assert!(a.abs_diff(*b) <= 1);
+        }
+    }
 }
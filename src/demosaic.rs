@@ -0,0 +1,330 @@
+//! Bayer demosaicing module.
+//!
+//! This module reconstructs RGB images from raw sensor frames in which every pixel
+//! holds a single color sample arranged in a Bayer color filter pattern.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+
+use crate::matrix::{Matrix1, Matrix3};
+
+/// Arrangement of the 2x2 color filter tile, read left-to-right, top-to-bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BayerPattern {
+    /// Red, green / green, blue
+    Rggb,
+    /// Blue, green / green, red
+    Bggr,
+    /// Green, red / blue, green
+    Grbg,
+    /// Green, blue / red, green
+    Gbrg,
+}
+
+/// Algorithms for interpolating the missing color samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemosaicMethod {
+    /// Copies the missing samples from the same 2x2 tile. Fastest, but produces
+    /// blocky edges
+    Nearest,
+    /// Averages the nearest samples of each color
+    Bilinear,
+    /// Malvar-He-Cutler gradient-corrected interpolation, which uses the known
+    /// channel to sharpen edges at little extra cost
+    MalvarHeCutler,
+}
+
+/// Color sample present at a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Site {
+    Red,
+    /// Green with red samples to the left and right
+    GreenRedRow,
+    /// Green with blue samples to the left and right
+    GreenBlueRow,
+    Blue,
+}
+
+impl BayerPattern {
+    /// Position of the red sample within each 2x2 tile.
+    fn red_offset(self) -> (usize, usize) {
+        match self {
+            BayerPattern::Rggb => (0, 0),
+            BayerPattern::Grbg => (1, 0),
+            BayerPattern::Gbrg => (0, 1),
+            BayerPattern::Bggr => (1, 1),
+        }
+    }
+
+    fn site(self, x: usize, y: usize) -> Site {
+        let (rx, ry) = self.red_offset();
+        match (x % 2 == rx, y % 2 == ry) {
+            (true, true) => Site::Red,
+            (false, true) => Site::GreenRedRow,
+            (true, false) => Site::GreenBlueRow,
+            (false, false) => Site::Blue,
+        }
+    }
+}
+
+/// A 5x5 Malvar-He-Cutler filter as (dx, dy, weight) taps, scaled by 16.
+type Filter = [(isize, isize, i32)];
+
+/// Green at red and blue sites.
+const GREEN_AT_RB: &Filter = &[
+    (0, 0, 8),
+    (-1, 0, 4),
+    (1, 0, 4),
+    (0, -1, 4),
+    (0, 1, 4),
+    (-2, 0, -2),
+    (2, 0, -2),
+    (0, -2, -2),
+    (0, 2, -2),
+];
+
+/// Red or blue at green sites where that color lies to the left and right.
+const RB_AT_G_HORIZONTAL: &Filter = &[
+    (0, 0, 10),
+    (-1, 0, 8),
+    (1, 0, 8),
+    (-2, 0, -2),
+    (2, 0, -2),
+    (-1, -1, -2),
+    (1, -1, -2),
+    (-1, 1, -2),
+    (1, 1, -2),
+    (0, -2, 1),
+    (0, 2, 1),
+];
+
+/// Red or blue at green sites where that color lies above and below.
+const RB_AT_G_VERTICAL: &Filter = &[
+    (0, 0, 10),
+    (0, -1, 8),
+    (0, 1, 8),
+    (0, -2, -2),
+    (0, 2, -2),
+    (-1, -1, -2),
+    (1, -1, -2),
+    (-1, 1, -2),
+    (1, 1, -2),
+    (-2, 0, 1),
+    (2, 0, 1),
+];
+
+/// Red at blue sites and blue at red sites.
+const RB_AT_BR: &Filter = &[
+    (0, 0, 12),
+    (-1, -1, 4),
+    (1, -1, 4),
+    (-1, 1, 4),
+    (1, 1, 4),
+    (-2, 0, -3),
+    (2, 0, -3),
+    (0, -2, -3),
+    (0, 2, -3),
+];
+
+/// Mirrors an out-of-bounds coordinate back into the image.
+///
+/// Mirroring without repeating the edge keeps the parity, so the sample at the
+/// mirrored position has the same color as the one that was requested.
+fn reflect(i: isize, len: usize) -> usize {
+    let last = len as isize - 1;
+    let i = if i < 0 {
+        -i
+    } else if i > last {
+        2 * last - i
+    } else {
+        i
+    };
+    i.clamp(0, last) as usize
+}
+
+impl Matrix1 {
+    /// Reconstructs an RGB image from a raw Bayer sensor frame.
+    ///
+    /// Borders are handled by mirroring the frame, which keeps the color pattern
+    /// intact at the edges.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The color filter arrangement of the sensor
+    /// * `method` - The interpolation algorithm
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the same dimensions as the frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{BayerPattern, DemosaicMethod, Matrix1};
+    ///
+    /// let raw = Matrix1::new(4, 4, vec![128; 16]);
+    /// let rgb = raw.demosaic(BayerPattern::Rggb, DemosaicMethod::MalvarHeCutler);
+    /// assert_eq!(rgb.get_pixel(1, 2), Some((128, 128, 128)));
+    /// ```
+    pub fn demosaic(&self, pattern: BayerPattern, method: DemosaicMethod) -> Matrix3 {
+        let (width, height) = (self.width(), self.height());
+        let mut rgb = vec![0u8; width * height * 3];
+
+        for (i, pixel) in rgb.chunks_exact_mut(3).enumerate() {
+            let (x, y) = (i % width, i / width);
+            let sample = |dx: isize, dy: isize| {
+                let sx = reflect(x as isize + dx, width);
+                let sy = reflect(y as isize + dy, height);
+                self.data()[sy * width + sx] as i32
+            };
+
+            let site = pattern.site(x, y);
+            let (r, g, b) = match method {
+                DemosaicMethod::Nearest => nearest(&sample, site, x, y),
+                DemosaicMethod::Bilinear => bilinear(&sample, site),
+                DemosaicMethod::MalvarHeCutler => malvar_he_cutler(&sample, site),
+            };
+            pixel.copy_from_slice(&[r, g, b]);
+        }
+
+        Matrix3::new(width, height, rgb)
+    }
+}
+
+/// Copies the missing samples from the neighbors in the same 2x2 tile.
+fn nearest<F: Fn(isize, isize) -> i32>(sample: &F, site: Site, x: usize, y: usize) -> (u8, u8, u8) {
+    // Offset to the other column and row of the 2x2 tile
+    let ox = 1 - 2 * (x % 2) as isize;
+    let oy = 1 - 2 * (y % 2) as isize;
+    let (r, g, b) = match site {
+        Site::Red => (sample(0, 0), sample(ox, 0), sample(ox, oy)),
+        Site::Blue => (sample(ox, oy), sample(ox, 0), sample(0, 0)),
+        Site::GreenRedRow => (sample(ox, 0), sample(0, 0), sample(0, oy)),
+        Site::GreenBlueRow => (sample(0, oy), sample(0, 0), sample(ox, 0)),
+    };
+    (r as u8, g as u8, b as u8)
+}
+
+/// Averages the nearest samples of each missing color.
+fn bilinear<F: Fn(isize, isize) -> i32>(sample: &F, site: Site) -> (u8, u8, u8) {
+    let center = sample(0, 0);
+    let horizontal = (sample(-1, 0) + sample(1, 0) + 1) / 2;
+    let vertical = (sample(0, -1) + sample(0, 1) + 1) / 2;
+    let cross = (sample(-1, 0) + sample(1, 0) + sample(0, -1) + sample(0, 1) + 2) / 4;
+    let diagonal = (sample(-1, -1) + sample(1, -1) + sample(-1, 1) + sample(1, 1) + 2) / 4;
+
+    let (r, g, b) = match site {
+        Site::Red => (center, cross, diagonal),
+        Site::Blue => (diagonal, cross, center),
+        Site::GreenRedRow => (horizontal, center, vertical),
+        Site::GreenBlueRow => (vertical, center, horizontal),
+    };
+    (r as u8, g as u8, b as u8)
+}
+
+/// Interpolates the missing colors with the Malvar-He-Cutler filters.
+fn malvar_he_cutler<F: Fn(isize, isize) -> i32>(sample: &F, site: Site) -> (u8, u8, u8) {
+    let filter = |taps: &Filter| {
+        let sum: i32 = taps.iter().map(|&(dx, dy, w)| w * sample(dx, dy)).sum();
+        (sum + 8).div_euclid(16).clamp(0, 255) as u8
+    };
+    let center = sample(0, 0) as u8;
+
+    match site {
+        Site::Red => (center, filter(GREEN_AT_RB), filter(RB_AT_BR)),
+        Site::Blue => (filter(RB_AT_BR), filter(GREEN_AT_RB), center),
+        Site::GreenRedRow => (filter(RB_AT_G_HORIZONTAL), center, filter(RB_AT_G_VERTICAL)),
+        Site::GreenBlueRow => (filter(RB_AT_G_VERTICAL), center, filter(RB_AT_G_HORIZONTAL)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERNS: [BayerPattern; 4] = [
+        BayerPattern::Rggb,
+        BayerPattern::Bggr,
+        BayerPattern::Grbg,
+        BayerPattern::Gbrg,
+    ];
+    const METHODS: [DemosaicMethod; 3] = [
+        DemosaicMethod::Nearest,
+        DemosaicMethod::Bilinear,
+        DemosaicMethod::MalvarHeCutler,
+    ];
+
+    /// Samples an RGB image through a Bayer color filter.
+    fn mosaic(image: &Matrix3, pattern: BayerPattern) -> Matrix1 {
+        let mut raw = Matrix1::zeros(image.width(), image.height());
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let (r, g, b) = image.get_pixel(x, y).unwrap();
+                let value = match pattern.site(x, y) {
+                    Site::Red => r,
+                    Site::Blue => b,
+                    Site::GreenRedRow | Site::GreenBlueRow => g,
+                };
+                raw.set_pixel(x, y, value);
+            }
+        }
+        raw
+    }
+
+    #[test]
+    fn test_pattern_sites() {
+        assert_eq!(BayerPattern::Rggb.site(0, 0), Site::Red);
+        assert_eq!(BayerPattern::Rggb.site(1, 0), Site::GreenRedRow);
+        assert_eq!(BayerPattern::Rggb.site(0, 1), Site::GreenBlueRow);
+        assert_eq!(BayerPattern::Rggb.site(1, 1), Site::Blue);
+        assert_eq!(BayerPattern::Bggr.site(2, 2), Site::Blue);
+        assert_eq!(BayerPattern::Grbg.site(1, 0), Site::Red);
+        assert_eq!(BayerPattern::Gbrg.site(0, 1), Site::Red);
+    }
+
+    #[test]
+    fn test_solid_color_is_reconstructed_exactly() {
+        let image = Matrix3::new(7, 5, [200u8, 100, 50].repeat(35));
+        for pattern in PATTERNS {
+            let raw = mosaic(&image, pattern);
+            for method in METHODS {
+                let rgb = raw.demosaic(pattern, method);
+                assert_eq!(rgb.data(), image.data(), "{:?} {:?}", pattern, method);
+            }
+        }
+    }
+
+    #[test]
+    fn test_edge_aware_beats_bilinear_on_edges() {
+        // Vertical edge between a dark and a bright gray half
+        let mut image = Matrix3::zeros(12, 12);
+        for y in 0..12 {
+            for x in 6..12 {
+                image.set_pixel(x, y, 220, 220, 220);
+            }
+        }
+        let raw = mosaic(&image, BayerPattern::Rggb);
+
+        let error = |method| {
+            let rgb = raw.demosaic(BayerPattern::Rggb, method);
+            rgb.data()
+                .iter()
+                .zip(image.data())
+                .map(|(&a, &b)| (a as i32 - b as i32).abs())
+                .sum::<i32>()
+        };
+        assert!(error(DemosaicMethod::MalvarHeCutler) < error(DemosaicMethod::Bilinear));
+    }
+
+    #[test]
+    fn test_tiny_frames() {
+        for method in METHODS {
+            let rgb = Matrix1::new(1, 1, vec![42]).demosaic(BayerPattern::Rggb, method);
+            assert_eq!(rgb.get_pixel(0, 0), Some((42, 42, 42)));
+            let rgb = Matrix1::new(3, 1, vec![10, 20, 30]).demosaic(BayerPattern::Gbrg, method);
+            assert_eq!(rgb.dimensions(), (3, 1));
+        }
+    }
+}
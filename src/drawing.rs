@@ -34,6 +34,7 @@ use alloc::string::String;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+use crate::color::SrgbLut;
 use crate::{Matrix1, Matrix3};
 use core::fmt;
 use core::str::FromStr;
@@ -366,6 +367,103 @@ impl DrawTarget for Matrix3 {
     }
 }
 
+/// A drawing target that blends semi-transparent colors in linear light.
+///
+/// Drawing directly on a `Matrix1` or `Matrix3` blends the gamma-encoded sRGB bytes,
+/// which makes translucent overlays and antialiased edges look too dark. Wrapping the
+/// image in `LinearLight` decodes both colors to linear light before mixing them.
+/// Opaque colors are written unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::{draw_circle, Color, LinearLight, Matrix3};
+///
+/// let mut image = Matrix3::zeros(100, 100);
+/// draw_circle(
+///     &mut LinearLight::new(&mut image),
+///     50.0, 50.0,
+///     20.0,
+///     None,
+///     Some(Color::rgb_with_opacity(255, 255, 255, 0.5)),
+/// );
+/// // Half of white's light on black encodes as 188, not 128
+/// assert_eq!(image.get_pixel(50, 50), Some((188, 188, 188)));
+/// ```
+pub struct LinearLight<'a, T> {
+    image: &'a mut T,
+    lut: SrgbLut,
+}
+
+impl<'a, T> LinearLight<'a, T> {
+    /// Wraps an image for linear-light drawing.
+    pub fn new(image: &'a mut T) -> Self {
+        Self {
+            image,
+            lut: SrgbLut::new(),
+        }
+    }
+
+    /// Blends one sRGB sample over another in linear light.
+    fn blend(&self, existing: u8, value: u8, opacity: f32) -> u8 {
+        let existing = self.lut.to_linear(existing);
+        let value = self.lut.to_linear(value);
+        self.lut
+            .to_srgb(existing * (1.0 - opacity) + value * opacity)
+    }
+}
+
+impl DrawTarget for LinearLight<'_, Matrix1> {
+    fn width(&self) -> usize {
+        self.image.width()
+    }
+
+    fn height(&self) -> usize {
+        self.image.height()
+    }
+
+    fn set_pixel_color(&mut self, x: usize, y: usize, color: Color) -> bool {
+        let opacity = color.opacity();
+        if opacity >= 1.0 || opacity <= 0.0 {
+            return self.image.set_pixel_color(x, y, color);
+        }
+
+        if let Some(existing) = self.image.get_pixel(x, y) {
+            let blended = self.blend(existing, color.to_gray(), opacity);
+            self.image.set_pixel(x, y, blended)
+        } else {
+            false
+        }
+    }
+}
+
+impl DrawTarget for LinearLight<'_, Matrix3> {
+    fn width(&self) -> usize {
+        self.image.width()
+    }
+
+    fn height(&self) -> usize {
+        self.image.height()
+    }
+
+    fn set_pixel_color(&mut self, x: usize, y: usize, color: Color) -> bool {
+        let opacity = color.opacity();
+        if opacity >= 1.0 || opacity <= 0.0 {
+            return self.image.set_pixel_color(x, y, color);
+        }
+
+        if let Some((er, eg, eb)) = self.image.get_pixel(x, y) {
+            let (r, g, b) = color.to_rgb();
+            let blended_r = self.blend(er, r, opacity);
+            let blended_g = self.blend(eg, g, opacity);
+            let blended_b = self.blend(eb, b, opacity);
+            self.image.set_pixel(x, y, blended_r, blended_g, blended_b)
+        } else {
+            false
+        }
+    }
+}
+
 /// Draws a rectangle on any image type (Matrix1 or Matrix3).
 ///
 /// # Arguments
@@ -956,4 +1054,19 @@ mod tests {
         assert_eq!(g, 100); // 0 * 0.5 + 200 * 0.5 = 100
         assert_eq!(b, 100); // 0 * 0.5 + 200 * 0.5 = 100
     }
+
+    #[test]
+    fn test_linear_light_blending() {
+        let mut gray = Matrix1::zeros(2, 1);
+        let mut target = LinearLight::new(&mut gray);
+        assert_eq!((target.width(), target.height()), (2, 1));
+        assert!(target.set_pixel_color(0, 0, Color::gray_with_opacity(255, 0.5)));
+        assert!(target.set_pixel_color(1, 0, Color::gray(200)));
+        assert!(!target.set_pixel_color(2, 0, Color::gray_with_opacity(255, 0.5)));
+        assert_eq!(gray.data(), &[188, 200]);
+
+        let mut rgb = Matrix3::new(1, 1, vec![255, 0, 0]);
+        LinearLight::new(&mut rgb).set_pixel_color(0, 0, Color::rgb_with_opacity(0, 255, 0, 0.5));
+        assert_eq!(rgb.get_pixel(0, 0), Some((188, 188, 0)));
+    }
 }
@@ -0,0 +1,441 @@
+//! Histogram module.
+//!
+//! This module computes intensity histograms and improves the contrast of dim or
+//! washed-out images with global histogram equalization and contrast-limited
+//! adaptive histogram equalization (CLAHE). Color images are equalized on their
+//! luminance only, which keeps their hues unchanged.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::color::ColorSpace;
+use crate::matrix::{Matrix1, Matrix3};
+
+/// Counts the samples of one channel into `bins` equal-width bins.
+fn count_samples<I>(samples: I, bins: usize, mask: Option<&Matrix1>) -> Vec<u32>
+where
+    I: Iterator<Item = u8>,
+{
+    assert!(
+        (1..=256).contains(&bins),
+        "Bin count must be between 1 and 256"
+    );
+
+    let mut histogram = vec![0u32; bins];
+    let mut count = |value: u8| histogram[value as usize * bins / 256] += 1;
+    match mask {
+        Some(mask) => samples
+            .zip(mask.data())
+            .filter(|(_, &m)| m != 0)
+            .for_each(|(value, _)| count(value)),
+        None => samples.for_each(count),
+    }
+    histogram
+}
+
+/// Checks that a mask covers an image of the given size.
+fn check_mask(mask: Option<&Matrix1>, width: usize, height: usize) {
+    if let Some(mask) = mask {
+        assert_eq!(
+            mask.dimensions(),
+            (width, height),
+            "Mask must have the same dimensions as the image"
+        );
+    }
+}
+
+/// Builds the lookup table that equalizes a 256-bin histogram.
+///
+/// Returns `None` if all counted pixels share one value.
+fn equalization_lut(histogram: &[u32]) -> Option<[u8; 256]> {
+    let total: u64 = histogram.iter().map(|&c| c as u64).sum();
+    let first = histogram.iter().copied().find(|&c| c > 0)? as u64;
+    if total == first {
+        return None;
+    }
+
+    let mut lut = [0u8; 256];
+    let mut cdf = 0u64;
+    for (value, &count) in lut.iter_mut().zip(histogram) {
+        cdf += count as u64;
+        let scaled = (cdf.saturating_sub(first) * 255 + (total - first) / 2) / (total - first);
+        *value = scaled as u8;
+    }
+    Some(lut)
+}
+
+/// Builds the clipped and equalized lookup table of one CLAHE tile.
+fn clahe_tile_lut(histogram: &mut [u32; 256], area: u32, clip_limit: f32) -> [u8; 256] {
+    if clip_limit > 0.0 && area > 0 {
+        let limit = ((clip_limit * area as f32 / 256.0) as u32).max(1);
+        let mut excess = 0;
+        for count in histogram.iter_mut() {
+            if *count > limit {
+                excess += *count - limit;
+                *count = limit;
+            }
+        }
+
+        // Hand the clipped counts back evenly, spreading the remainder across the range
+        let share = excess / 256;
+        let remainder = (excess % 256) as usize;
+        for count in histogram.iter_mut() {
+            *count += share;
+        }
+        if let Some(step) = 256usize.checked_div(remainder) {
+            for i in 0..remainder {
+                histogram[i * step] += 1;
+            }
+        }
+    }
+
+    let mut lut = [0u8; 256];
+    let mut cdf = 0u32;
+    for (value, &count) in lut.iter_mut().zip(histogram.iter()) {
+        cdf += count;
+        *value = ((cdf as u64 * 255 + area as u64 / 2) / (area as u64).max(1)) as u8;
+    }
+    lut
+}
+
+/// Finds the two tiles around `coord` and the interpolation weight of the second one.
+fn tile_neighbors(coord: usize, tile_size: f32, tiles: usize) -> (usize, usize, f32) {
+    let position = (coord as f32 + 0.5) / tile_size - 0.5;
+    if position <= 0.0 {
+        return (0, 0, 0.0);
+    }
+    let first = (position as usize).min(tiles - 1);
+    let second = (first + 1).min(tiles - 1);
+    (first, second, position - first as f32)
+}
+
+impl Matrix1 {
+    /// Computes the intensity histogram.
+    ///
+    /// # Arguments
+    ///
+    /// * `bins` - Number of equal-width bins covering 0-255 (1 to 256)
+    /// * `mask` - Optional mask; only pixels where the mask is non-zero are counted
+    ///
+    /// # Returns
+    ///
+    /// The pixel count of each bin. Bin `i` holds the values `v` with
+    /// `v * bins / 256 == i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bins` is outside 1-256 or the mask has different dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix1;
+    ///
+    /// let image = Matrix1::new(4, 1, vec![0, 10, 200, 255]);
+    /// assert_eq!(image.histogram(2, None), vec![2, 2]);
+    ///
+    /// let mask = Matrix1::new(4, 1, vec![0, 0, 255, 255]);
+    /// assert_eq!(image.histogram(2, Some(&mask)), vec![0, 2]);
+    /// ```
+    pub fn histogram(&self, bins: usize, mask: Option<&Matrix1>) -> Vec<u32> {
+        check_mask(mask, self.width(), self.height());
+        count_samples(self.data().iter().copied(), bins, mask)
+    }
+
+    /// Spreads the intensities over the full range with global histogram equalization.
+    ///
+    /// Images with a single intensity are returned unchanged.
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 whose cumulative histogram is approximately linear.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix1;
+    ///
+    /// let dim = Matrix1::new(4, 1, vec![10, 20, 30, 40]);
+    /// assert_eq!(dim.equalize_histogram().data(), &[0, 85, 170, 255]);
+    /// ```
+    pub fn equalize_histogram(&self) -> Matrix1 {
+        match equalization_lut(&self.histogram(256, None)) {
+            Some(lut) => self.apply_lut(&lut),
+            None => self.clone(),
+        }
+    }
+
+    /// Applies contrast-limited adaptive histogram equalization (CLAHE).
+    ///
+    /// The image is divided into a grid of tiles that are equalized separately, and
+    /// the results are blended bilinearly between tile centers so no seams appear.
+    /// Clipping each tile's histogram limits how much noise in flat regions, such as
+    /// a dark sky, is amplified. Rows are processed in parallel when the `parallel`
+    /// feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `clip_limit` - Maximum height of a tile's histogram bins, relative to a flat
+    ///   histogram. Typical values are 2.0-4.0; 0.0 disables clipping
+    /// * `tile_grid` - Number of tiles across and down, for example (8, 8)
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 with locally enhanced contrast.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either tile count is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix1;
+    ///
+    /// let frame = Matrix1::new(64, 64, (0..64 * 64).map(|i| (i % 32) as u8).collect());
+    /// let enhanced = frame.clahe(2.0, (8, 8));
+    /// assert_eq!(enhanced.dimensions(), (64, 64));
+    /// ```
+    pub fn clahe(&self, clip_limit: f32, tile_grid: (usize, usize)) -> Matrix1 {
+        assert!(
+            tile_grid.0 > 0 && tile_grid.1 > 0,
+            "Tile grid must have at least one tile"
+        );
+        let (width, height) = self.dimensions();
+        if width == 0 || height == 0 {
+            return self.clone();
+        }
+        let tiles_x = tile_grid.0.min(width);
+        let tiles_y = tile_grid.1.min(height);
+
+        let mut histograms = vec![[0u32; 256]; tiles_x * tiles_y];
+        let mut areas = vec![0u32; tiles_x * tiles_y];
+        for (y, row) in self.data().chunks_exact(width).enumerate() {
+            let tile_row = y * tiles_y / height * tiles_x;
+            for (x, &value) in row.iter().enumerate() {
+                let tile = tile_row + x * tiles_x / width;
+                histograms[tile][value as usize] += 1;
+                areas[tile] += 1;
+            }
+        }
+        let luts: Vec<[u8; 256]> = histograms
+            .iter_mut()
+            .zip(&areas)
+            .map(|(histogram, &area)| clahe_tile_lut(histogram, area, clip_limit))
+            .collect();
+
+        let tile_width = width as f32 / tiles_x as f32;
+        let tile_height = height as f32 / tiles_y as f32;
+        let columns: Vec<(usize, usize, f32)> = (0..width)
+            .map(|x| tile_neighbors(x, tile_width, tiles_x))
+            .collect();
+
+        let source = self.data();
+        let map_row = |(y, row): (usize, &mut [u8])| {
+            let (top, bottom, wy) = tile_neighbors(y, tile_height, tiles_y);
+            let top = &luts[top * tiles_x..(top + 1) * tiles_x];
+            let bottom = &luts[bottom * tiles_x..(bottom + 1) * tiles_x];
+            for (x, out) in row.iter_mut().enumerate() {
+                let value = source[y * width + x] as usize;
+                let (left, right, wx) = columns[x];
+                let upper = top[left][value] as f32 * (1.0 - wx) + top[right][value] as f32 * wx;
+                let lower =
+                    bottom[left][value] as f32 * (1.0 - wx) + bottom[right][value] as f32 * wx;
+                *out = (upper * (1.0 - wy) + lower * wy + 0.5) as u8;
+            }
+        };
+
+        let mut data = vec![0u8; source.len()];
+
+        #[cfg(feature = "parallel")]
+        data.par_chunks_mut(width).enumerate().for_each(map_row);
+
+        #[cfg(not(feature = "parallel"))]
+        data.chunks_mut(width).enumerate().for_each(map_row);
+
+        Matrix1::new(width, height, data)
+    }
+}
+
+impl Matrix3 {
+    /// Computes the histogram of each channel.
+    ///
+    /// See [`Matrix1::histogram`] for the arguments.
+    ///
+    /// # Returns
+    ///
+    /// The red, green and blue histograms, in channel order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bins` is outside 1-256 or the mask has different dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let image = Matrix3::new(2, 1, vec![255, 0, 0, 255, 255, 0]);
+    /// let [red, green, blue] = image.histogram(4, None);
+    /// assert_eq!(red, vec![0, 0, 0, 2]);
+    /// assert_eq!(green, vec![1, 0, 0, 1]);
+    /// assert_eq!(blue, vec![2, 0, 0, 0]);
+    /// ```
+    pub fn histogram(&self, bins: usize, mask: Option<&Matrix1>) -> [Vec<u32>; 3] {
+        check_mask(mask, self.width(), self.height());
+        let channel = |c: usize| {
+            let samples = self.data().iter().skip(c).step_by(3).copied();
+            count_samples(samples, bins, mask)
+        };
+        [channel(0), channel(1), channel(2)]
+    }
+
+    /// Equalizes the histogram of the image's luminance.
+    ///
+    /// The image is converted to YCbCr, the luma channel is equalized as in
+    /// [`Matrix1::equalize_histogram`], and the chroma channels are left untouched,
+    /// so colors brighten without shifting hue.
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with equalized brightness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let dim = Matrix3::new(2, 1, vec![20, 10, 10, 40, 30, 30]);
+    /// let equalized = dim.equalize_histogram();
+    /// assert!(equalized.get_pixel(1, 0).unwrap().0 > 200);
+    /// ```
+    pub fn equalize_histogram(&self) -> Matrix3 {
+        self.map_luminance(Matrix1::equalize_histogram)
+    }
+
+    /// Applies contrast-limited adaptive histogram equalization to the luminance.
+    ///
+    /// See [`Matrix1::clahe`] for the arguments. Like
+    /// [`Matrix3::equalize_histogram`], only the luma channel is changed.
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with locally enhanced contrast.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let frame = Matrix3::zeros(320, 240);
+    /// let enhanced = frame.clahe(3.0, (8, 8));
+    /// ```
+    pub fn clahe(&self, clip_limit: f32, tile_grid: (usize, usize)) -> Matrix3 {
+        self.map_luminance(|luma| luma.clahe(clip_limit, tile_grid))
+    }
+
+    /// Transforms the luma channel of the image, keeping its chroma.
+    fn map_luminance<F: FnOnce(&Matrix1) -> Matrix1>(&self, f: F) -> Matrix3 {
+        let mut ycbcr = self.convert_color(ColorSpace::YCbCr);
+        let luma = Matrix1::new(
+            self.width(),
+            self.height(),
+            ycbcr.data().iter().step_by(3).copied().collect(),
+        );
+
+        let luma = f(&luma);
+        for (pixel, &y) in ycbcr.data_mut().chunks_exact_mut(3).zip(luma.data()) {
+            pixel[0] = y;
+        }
+        ycbcr.convert_color_to_rgb(ColorSpace::YCbCr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_bins_and_mask() {
+        let image = Matrix1::new(3, 2, vec![0, 63, 64, 128, 255, 255]);
+        assert_eq!(image.histogram(4, None), vec![2, 1, 1, 2]);
+        assert_eq!(image.histogram(1, None), vec![6]);
+        assert_eq!(image.histogram(256, None).iter().sum::<u32>(), 6);
+        assert_eq!(image.histogram(256, None)[255], 2);
+
+        let mask = Matrix1::new(3, 2, vec![255, 0, 0, 0, 1, 0]);
+        assert_eq!(image.histogram(4, Some(&mask)), vec![1, 0, 0, 1]);
+
+        let rgb = Matrix3::new(1, 2, vec![10, 20, 30, 200, 20, 30]);
+        let mask = Matrix1::new(1, 2, vec![0, 255]);
+        let [red, green, _] = rgb.histogram(2, Some(&mask));
+        assert_eq!(red, vec![0, 1]);
+        assert_eq!(green, vec![1, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bin count")]
+    fn test_histogram_rejects_zero_bins() {
+        Matrix1::zeros(2, 2).histogram(0, None);
+    }
+
+    #[test]
+    fn test_equalize_histogram() {
+        let image = Matrix1::new(4, 2, vec![50, 50, 50, 50, 51, 51, 52, 53]);
+        let equalized = image.equalize_histogram();
+        assert_eq!(equalized.data(), &[0, 0, 0, 0, 128, 128, 191, 255]);
+
+        let flat = Matrix1::new(2, 2, vec![90; 4]);
+        assert_eq!(flat.equalize_histogram().data(), flat.data());
+
+        // Gray stays gray when equalizing color images
+        let rgb = Matrix3::new(2, 1, vec![40, 40, 40, 60, 60, 60]);
+        let equalized = rgb.equalize_histogram();
+        assert_eq!(equalized.data(), &[0, 0, 0, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_clahe() {
+        // A dim left half and a bright right half, each with slight texture
+        let (width, height) = (32, 16);
+        let data: Vec<u8> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let base = if x < width / 2 { 20 } else { 200 };
+                base + ((x + y) % 4) as u8 * 3
+            })
+            .collect();
+        let image = Matrix1::new(width, height, data);
+
+        let enhanced = image.clahe(0.0, (4, 2));
+        let spread = |img: &Matrix1, x0: usize| {
+            let values: Vec<u8> = (0..height)
+                .flat_map(|y| (x0..x0 + 4).map(move |x| (x, y)))
+                .map(|(x, y)| img.get_pixel(x, y).unwrap())
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        // Local contrast in the middle of each half grows well beyond 9
+        assert!(spread(&enhanced, 2) > 100);
+        assert!(spread(&enhanced, 26) > 100);
+
+        // Clipping limits the amplification
+        let clipped = image.clahe(1.5, (4, 2));
+        assert!(spread(&clipped, 2) < spread(&enhanced, 2));
+
+        // Uniform images stay uniform, with any tile grid
+        let flat = Matrix1::new(7, 5, vec![128; 35]);
+        let result = flat.clahe(2.0, (16, 16));
+        assert!(result.data().iter().all(|&v| v == result.data()[0]));
+
+        let rgb = Matrix3::new(8, 8, vec![100; 192]);
+        assert_eq!(rgb.clahe(2.0, (2, 2)).dimensions(), (8, 8));
+    }
+}
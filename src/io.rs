@@ -2,12 +2,23 @@
 //!
 //! This module requires the `std` feature to be enabled.
 
-use crate::matrix::Matrix3;
+use crate::codec::exif::{self, ExifMetadata};
+use crate::codec::netpbm::{self, PnmEncoding};
+use crate::codec::tga::{self, TgaCompression};
+use crate::codec::CodecError;
+use crate::codec::{bmp, qoi};
+use crate::color::{yuv_to_rgb, GrayscaleMethod, YuvFormat, YuvMatrix, YuvRange};
+pub use crate::matrix::WritableImage;
+use crate::matrix::{DynamicImage, Matrix1, Matrix1U16, Matrix3, Matrix3U16};
+use crate::quantize::{DitherMethod, QuantizeMethod};
 use jpeg_decoder::{Decoder, PixelFormat};
-use png::{ColorType, Decoder as PngDecoder};
+use png::{BitDepth, ColorType, Decoder as PngDecoder, Transformations};
 use std::fs::File;
-use std::io::{self, BufReader};
+use std::io::{self, BufReader, Cursor, Read, Seek, Write};
 use std::path::Path;
+use tiff::decoder::{Decoder as TiffDecoder, DecodingResult};
+use tiff::encoder::compression::{Deflate, Lzw, Uncompressed};
+use tiff::encoder::{colortype, TiffEncoder, TiffValue};
 
 /// Errors that can occur during image I/O operations.
 #[derive(Debug)]
@@ -22,8 +33,22 @@ pub enum ImageError {
     JpegEncode(String),
     /// PNG encoding error
     PngEncode(String),
+    /// TIFF decoding error
+    TiffDecode(String),
+    /// TIFF encoding error
+    TiffEncode(String),
+    /// GIF decoding error
+    GifDecode(String),
+    /// GIF encoding error
+    GifEncode(String),
+    /// Y4M decoding error
+    Y4mDecode(String),
+    /// Y4M encoding error
+    Y4mEncode(String),
     /// Unsupported pixel format
     UnsupportedFormat(String),
+    /// Error from one of the in-memory codecs in [`crate::codec`]
+    Codec(CodecError),
 }
 
 impl std::fmt::Display for ImageError {
@@ -34,7 +59,14 @@ impl std::fmt::Display for ImageError {
             ImageError::PngDecode(e) => write!(f, "PNG decode error: {}", e),
             ImageError::JpegEncode(e) => write!(f, "JPEG encode error: {}", e),
             ImageError::PngEncode(e) => write!(f, "PNG encode error: {}", e),
+            ImageError::TiffDecode(e) => write!(f, "TIFF decode error: {}", e),
+            ImageError::TiffEncode(e) => write!(f, "TIFF encode error: {}", e),
+            ImageError::GifDecode(e) => write!(f, "GIF decode error: {}", e),
+            ImageError::GifEncode(e) => write!(f, "GIF encode error: {}", e),
+            ImageError::Y4mDecode(e) => write!(f, "Y4M decode error: {}", e),
+            ImageError::Y4mEncode(e) => write!(f, "Y4M encode error: {}", e),
             ImageError::UnsupportedFormat(e) => write!(f, "Unsupported format: {}", e),
+            ImageError::Codec(e) => write!(f, "Codec error: {}", e),
         }
     }
 }
@@ -47,6 +79,302 @@ impl From<io::Error> for ImageError {
     }
 }
 
+impl From<CodecError> for ImageError {
+    fn from(error: CodecError) -> Self {
+        ImageError::Codec(error)
+    }
+}
+
+/// Image file formats understood by [`read_image`] and [`write_image`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// JPEG / JFIF
+    Jpeg,
+    /// Portable Network Graphics
+    Png,
+    /// Netpbm family (PBM, PGM, PPM, PAM)
+    Netpbm,
+    /// Windows bitmap
+    Bmp,
+    /// Tagged Image File Format
+    Tiff,
+    /// Quite OK Image format
+    Qoi,
+    /// Truevision TGA (detected by extension only, as it has no signature)
+    Tga,
+    /// Graphics Interchange Format
+    Gif,
+}
+
+impl ImageFormat {
+    /// Detects the format from the leading magic bytes of an encoded image.
+    ///
+    /// Returns `None` if the signature is not recognized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::io::ImageFormat;
+    ///
+    /// assert_eq!(ImageFormat::from_magic(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(ImageFormat::Jpeg));
+    /// assert_eq!(ImageFormat::from_magic(b"hello"), None);
+    /// ```
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if bytes.len() >= 2 && bytes[0] == b'P' && (b'1'..=b'7').contains(&bytes[1]) {
+            Some(ImageFormat::Netpbm)
+        } else if bytes.starts_with(b"BM") {
+            Some(ImageFormat::Bmp)
+        } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+            Some(ImageFormat::Tiff)
+        } else if bytes.starts_with(b"qoif") {
+            Some(ImageFormat::Qoi)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else {
+            None
+        }
+    }
+
+    /// Determines the format from a file extension (case-insensitive, without the dot).
+    ///
+    /// Returns `None` if the extension is not recognized.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" | "jpe" | "jfif" => Some(ImageFormat::Jpeg),
+            "png" => Some(ImageFormat::Png),
+            "pbm" | "pgm" | "ppm" | "pnm" | "pam" => Some(ImageFormat::Netpbm),
+            "bmp" | "dib" => Some(ImageFormat::Bmp),
+            "tif" | "tiff" => Some(ImageFormat::Tiff),
+            "qoi" => Some(ImageFormat::Qoi),
+            "tga" => Some(ImageFormat::Tga),
+            "gif" => Some(ImageFormat::Gif),
+            _ => None,
+        }
+    }
+
+    /// Determines the format from the extension of a path.
+    ///
+    /// Returns `None` if the path has no extension or it is not recognized.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+}
+
+/// Per-format options used by [`write_image_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Encoder settings used for JPEG files
+    pub jpeg: JpegOptions,
+    /// Compression used for TIFF files
+    pub tiff_compression: TiffCompression,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            jpeg: JpegOptions::default(),
+            tiff_compression: TiffCompression::Lzw,
+        }
+    }
+}
+
+impl WriteOptions {
+    /// Returns a copy of the options with the given JPEG quality.
+    pub fn with_jpeg_quality(mut self, quality: u8) -> Self {
+        self.jpeg.quality = quality;
+        self
+    }
+
+    /// Returns a copy of the options with the given JPEG encoder settings.
+    pub fn with_jpeg(mut self, jpeg: JpegOptions) -> Self {
+        self.jpeg = jpeg;
+        self
+    }
+
+    /// Returns a copy of the options with the given TIFF compression.
+    pub fn with_tiff_compression(mut self, compression: TiffCompression) -> Self {
+        self.tiff_compression = compression;
+        self
+    }
+}
+
+/// Options used by [`read_image_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReadOptions {
+    /// Rotate and mirror JPEG images so they appear upright, according to their
+    /// EXIF orientation tag
+    pub apply_orientation: bool,
+}
+
+impl ReadOptions {
+    /// Returns a copy of the options with EXIF orientation handling enabled or disabled.
+    pub fn with_apply_orientation(mut self, apply: bool) -> Self {
+        self.apply_orientation = apply;
+        self
+    }
+}
+
+/// Lossless compression schemes for writing TIFF files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiffCompression {
+    /// Uncompressed strips
+    None,
+    /// Lempel-Ziv-Welch compression
+    Lzw,
+    /// Deflate (zlib) compression
+    Deflate,
+}
+
+/// Chroma subsampling schemes for writing JPEG files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChromaSubsampling {
+    /// Full-resolution chroma, which keeps thin colored lines and text sharp
+    Yuv444,
+    /// Chroma halved horizontally
+    Yuv422,
+    /// Chroma halved horizontally and vertically, giving the smallest files
+    Yuv420,
+}
+
+/// Encoder settings used by [`write_jpeg_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::io::{ChromaSubsampling, JpegOptions};
+///
+/// let options = JpegOptions::default()
+///     .with_quality(85)
+///     .with_subsampling(ChromaSubsampling::Yuv444)
+///     .with_progressive(true)
+///     .with_comment("annotated by cv-rusty");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JpegOptions {
+    /// JPEG quality (1-100, where 100 is best quality)
+    pub quality: u8,
+    /// Chroma subsampling. `None` uses 4:2:0 below quality 90 and 4:4:4 otherwise
+    pub subsampling: Option<ChromaSubsampling>,
+    /// Write a progressive instead of a baseline JPEG
+    pub progressive: bool,
+    /// Compute image-specific Huffman tables, which makes files smaller but encoding slower
+    pub optimize_huffman: bool,
+    /// Insert a restart marker every this many MCUs, limiting the damage of corrupted data
+    pub restart_interval: Option<u16>,
+    /// Convert color images to a single-channel grayscale JPEG
+    pub grayscale: bool,
+    /// EXIF payload to embed in an APP1 segment, with or without the `Exif\0\0` prefix
+    pub exif: Option<Vec<u8>>,
+    /// ICC color profile to embed in APP2 segments
+    pub icc_profile: Option<Vec<u8>>,
+    /// Text to embed in a COM segment
+    pub comment: Option<String>,
+}
+
+impl Default for JpegOptions {
+    fn default() -> Self {
+        Self {
+            quality: 90,
+            subsampling: None,
+            progressive: false,
+            optimize_huffman: false,
+            restart_interval: None,
+            grayscale: false,
+            exif: None,
+            icc_profile: None,
+            comment: None,
+        }
+    }
+}
+
+impl JpegOptions {
+    /// Returns a copy of the options with the given quality.
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Returns a copy of the options with the given chroma subsampling.
+    pub fn with_subsampling(mut self, subsampling: ChromaSubsampling) -> Self {
+        self.subsampling = Some(subsampling);
+        self
+    }
+
+    /// Returns a copy of the options with progressive encoding enabled or disabled.
+    pub fn with_progressive(mut self, progressive: bool) -> Self {
+        self.progressive = progressive;
+        self
+    }
+
+    /// Returns a copy of the options with Huffman table optimization enabled or disabled.
+    pub fn with_optimized_huffman(mut self, optimize: bool) -> Self {
+        self.optimize_huffman = optimize;
+        self
+    }
+
+    /// Returns a copy of the options with the given restart interval in MCUs.
+    pub fn with_restart_interval(mut self, interval: u16) -> Self {
+        self.restart_interval = Some(interval);
+        self
+    }
+
+    /// Returns a copy of the options with grayscale output enabled or disabled.
+    pub fn with_grayscale(mut self, grayscale: bool) -> Self {
+        self.grayscale = grayscale;
+        self
+    }
+
+    /// Returns a copy of the options that embeds the given EXIF payload.
+    pub fn with_exif(mut self, exif: Vec<u8>) -> Self {
+        self.exif = Some(exif);
+        self
+    }
+
+    /// Returns a copy of the options that embeds the given ICC profile.
+    pub fn with_icc_profile(mut self, profile: Vec<u8>) -> Self {
+        self.icc_profile = Some(profile);
+        self
+    }
+
+    /// Returns a copy of the options that embeds the given comment.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+}
+
+/// Copies an 8-bit writable image into an RGB matrix, expanding grayscale.
+fn writable_to_rgb8<I: WritableImage>(image: &I) -> Result<Matrix3, ImageError> {
+    if image.bit_depth() != 8 {
+        return Err(ImageError::UnsupportedFormat(format!(
+            "{}-bit image where 8-bit is required",
+            image.bit_depth()
+        )));
+    }
+    let samples = image.sample_bytes();
+    let data = if image.channels() == 1 {
+        samples.iter().flat_map(|&v| [v, v, v]).collect()
+    } else {
+        samples.into_owned()
+    };
+    Ok(Matrix3::new(image.width(), image.height(), data))
+}
+
+/// Parses big-endian byte pairs into 16-bit samples.
+fn be_bytes_to_u16(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
 /// Reads a JPEG image file and returns it as a three-channel RGB matrix.
 ///
 /// # Arguments
@@ -67,10 +395,143 @@ impl From<io::Error> for ImageError {
 /// println!("Image dimensions: {}x{}", image.width(), image.height());
 /// ```
 pub fn read_jpeg<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
-    // Open the file
     let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    read_jpeg_from(BufReader::new(file))
+}
+
+/// Decodes a JPEG image held in memory into a three-channel RGB matrix.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded JPEG data
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::decode_jpeg;
+///
+/// let bytes = std::fs::read("photo.jpg").unwrap();
+/// let image = decode_jpeg(&bytes).expect("Failed to decode JPEG");
+/// ```
+pub fn decode_jpeg(bytes: &[u8]) -> Result<Matrix3, ImageError> {
+    read_jpeg_from(Cursor::new(bytes))
+}
+
+/// Reads a JPEG image from any reader and returns it as a three-channel RGB matrix.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the encoded JPEG data (e.g. a socket or file)
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+pub fn read_jpeg_from<R: Read>(reader: R) -> Result<Matrix3, ImageError> {
+    let (width, height, pixel_format, pixels) = decode_jpeg_pixels(reader)?;
+    let rgb_data = jpeg_pixels_to_rgb(pixel_format, pixels)?;
+
+    Ok(Matrix3::new(width, height, rgb_data))
+}
+
+/// Reads a JPEG image file as a single-channel grayscale matrix.
+///
+/// Grayscale JPEGs are returned as-is without expansion; color JPEGs are converted
+/// using the given `method`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the JPEG file
+/// * `method` - Grayscale conversion method applied to color sources
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix1` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{GrayscaleMethod, io::read_jpeg_gray};
+///
+/// let image = read_jpeg_gray("mono.jpg", GrayscaleMethod::Luminosity).expect("Failed to read JPEG");
+/// println!("Image dimensions: {}x{}", image.width(), image.height());
+/// ```
+pub fn read_jpeg_gray<P: AsRef<Path>>(
+    path: P,
+    method: GrayscaleMethod,
+) -> Result<Matrix1, ImageError> {
+    let file = File::open(path)?;
+    read_jpeg_gray_from(BufReader::new(file), method)
+}
+
+/// Decodes an in-memory JPEG image as a single-channel grayscale matrix.
+///
+/// See [`read_jpeg_gray`] for how color sources are handled.
+pub fn decode_jpeg_gray(bytes: &[u8], method: GrayscaleMethod) -> Result<Matrix1, ImageError> {
+    read_jpeg_gray_from(Cursor::new(bytes), method)
+}
+
+/// Reads a JPEG image from any reader as a single-channel grayscale matrix.
+///
+/// See [`read_jpeg_gray`] for how color sources are handled.
+pub fn read_jpeg_gray_from<R: Read>(
+    reader: R,
+    method: GrayscaleMethod,
+) -> Result<Matrix1, ImageError> {
+    let (width, height, pixel_format, pixels) = decode_jpeg_pixels(reader)?;
+
+    if pixel_format == PixelFormat::L8 {
+        return Ok(Matrix1::new(width, height, pixels));
+    }
 
+    let rgb_data = jpeg_pixels_to_rgb(pixel_format, pixels)?;
+    Ok(Matrix3::new(width, height, rgb_data).to_grayscale_with_method(method))
+}
+
+/// Reads the EXIF metadata of a JPEG file without decoding the pixels.
+///
+/// # Arguments
+///
+/// * `path` - Path to the JPEG file
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `ExifMetadata` on success, or an `ImageError` on
+/// failure. Files without an EXIF block yield empty metadata.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_jpeg_metadata;
+///
+/// let metadata = read_jpeg_metadata("photo.jpg").expect("Failed to read metadata");
+/// if let Some(gps) = metadata.gps {
+///     println!("Taken at {}, {}", gps.latitude, gps.longitude);
+/// }
+/// ```
+pub fn read_jpeg_metadata<P: AsRef<Path>>(path: P) -> Result<ExifMetadata, ImageError> {
+    decode_jpeg_metadata(&std::fs::read(path)?)
+}
+
+/// Reads the EXIF metadata of an in-memory JPEG image.
+///
+/// See [`read_jpeg_metadata`] for details.
+pub fn decode_jpeg_metadata(bytes: &[u8]) -> Result<ExifMetadata, ImageError> {
+    match exif::find_in_jpeg(bytes) {
+        Some(block) => Ok(exif::parse(block)?),
+        None => Ok(ExifMetadata::default()),
+    }
+}
+
+/// Decodes a JPEG stream into its native pixel layout.
+fn decode_jpeg_pixels<R: Read>(
+    reader: R,
+) -> Result<(usize, usize, PixelFormat, Vec<u8>), ImageError> {
     // Create decoder
     let mut decoder = Decoder::new(reader);
 
@@ -84,11 +545,17 @@ pub fn read_jpeg<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
         .info()
         .ok_or_else(|| ImageError::JpegDecode("Failed to get image info".to_string()))?;
 
-    let width = info.width as usize;
-    let height = info.height as usize;
+    Ok((
+        info.width as usize,
+        info.height as usize,
+        info.pixel_format,
+        pixels,
+    ))
+}
 
-    // Convert to RGB if necessary
-    let rgb_data = match info.pixel_format {
+/// Converts decoded JPEG pixels to interleaved RGB.
+fn jpeg_pixels_to_rgb(pixel_format: PixelFormat, pixels: Vec<u8>) -> Result<Vec<u8>, ImageError> {
+    let rgb_data = match pixel_format {
         PixelFormat::RGB24 => {
             // Already in RGB format
             pixels
@@ -105,7 +572,7 @@ pub fn read_jpeg<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
         }
         PixelFormat::CMYK32 => {
             // CMYK - convert to RGB
-            let mut rgb = Vec::with_capacity(width * height * 3);
+            let mut rgb = Vec::with_capacity(pixels.len() / 4 * 3);
             for chunk in pixels.chunks_exact(4) {
                 let c = chunk[0] as f32 / 255.0;
                 let m = chunk[1] as f32 / 255.0;
@@ -125,12 +592,12 @@ pub fn read_jpeg<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
         _ => {
             return Err(ImageError::UnsupportedFormat(format!(
                 "Unsupported pixel format: {:?}",
-                info.pixel_format
+                pixel_format
             )));
         }
     };
 
-    Ok(Matrix3::new(width, height, rgb_data))
+    Ok(rgb_data)
 }
 
 /// Reads a PNG image file and returns it as a three-channel RGB matrix.
@@ -153,135 +620,3078 @@ pub fn read_jpeg<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
 /// println!("Image dimensions: {}x{}", image.width(), image.height());
 /// ```
 pub fn read_png<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
-    // Open the file
     let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    read_png_from(BufReader::new(file))
+}
 
-    // Create decoder
-    let decoder = PngDecoder::new(reader);
-    let mut reader = decoder
-        .read_info()
-        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+/// Decodes a PNG image held in memory into a three-channel RGB matrix.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded PNG data
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::decode_png;
+///
+/// let bytes = std::fs::read("photo.png").unwrap();
+/// let image = decode_png(&bytes).expect("Failed to decode PNG");
+/// ```
+pub fn decode_png(bytes: &[u8]) -> Result<Matrix3, ImageError> {
+    read_png_from(Cursor::new(bytes))
+}
 
-    // Get image metadata
-    let info = reader.info();
-    let width = info.width as usize;
-    let height = info.height as usize;
-    let color_type = info.color_type;
+/// Reads a PNG image from any reader and returns it as a three-channel RGB matrix.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the encoded PNG data (e.g. a socket or file)
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+pub fn read_png_from<R: Read>(reader: R) -> Result<Matrix3, ImageError> {
+    let (width, height, color_type, buf) = decode_png_pixels(reader)?;
+    let rgb_data = png_pixels_to_rgb(color_type, buf)?;
 
-    // Allocate buffer for image data
-    let mut buf = vec![0u8; reader.output_buffer_size()];
-    let info = reader
-        .next_frame(&mut buf)
-        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+    Ok(Matrix3::new(width, height, rgb_data))
+}
 
-    // Resize buffer to actual data size
-    buf.truncate(info.buffer_size());
+/// Reads a PNG image file as a single-channel grayscale matrix.
+///
+/// Grayscale PNGs are returned without expansion (any alpha channel is dropped);
+/// color PNGs are converted using the given `method`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the PNG file
+/// * `method` - Grayscale conversion method applied to color sources
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix1` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{GrayscaleMethod, io::read_png_gray};
+///
+/// let image = read_png_gray("mono.png", GrayscaleMethod::Luminosity).expect("Failed to read PNG");
+/// println!("Image dimensions: {}x{}", image.width(), image.height());
+/// ```
+pub fn read_png_gray<P: AsRef<Path>>(
+    path: P,
+    method: GrayscaleMethod,
+) -> Result<Matrix1, ImageError> {
+    let file = File::open(path)?;
+    read_png_gray_from(BufReader::new(file), method)
+}
 
-    // Convert to RGB if necessary
-    let rgb_data = match color_type {
-        ColorType::Rgb => {
-            // Already in RGB format
-            buf
-        }
-        ColorType::Rgba => {
-            // RGBA - strip alpha channel
-            let mut rgb = Vec::with_capacity(width * height * 3);
-            for chunk in buf.chunks_exact(4) {
-                rgb.push(chunk[0]);
-                rgb.push(chunk[1]);
-                rgb.push(chunk[2]);
-            }
-            rgb
-        }
-        ColorType::Grayscale => {
-            // Grayscale - convert to RGB by duplicating the channel
-            let mut rgb = Vec::with_capacity(buf.len() * 3);
-            for &gray in &buf {
-                rgb.push(gray);
-                rgb.push(gray);
-                rgb.push(gray);
-            }
-            rgb
-        }
-        ColorType::GrayscaleAlpha => {
-            // Grayscale with alpha - convert to RGB and strip alpha
-            let mut rgb = Vec::with_capacity(width * height * 3);
-            for chunk in buf.chunks_exact(2) {
-                let gray = chunk[0];
-                rgb.push(gray);
-                rgb.push(gray);
-                rgb.push(gray);
-            }
-            rgb
-        }
-        ColorType::Indexed => {
-            // Indexed color should be expanded by the decoder
-            return Err(ImageError::UnsupportedFormat(
-                "Indexed PNG color type not fully supported. Try converting to RGB first."
-                    .to_string(),
-            ));
+/// Decodes an in-memory PNG image as a single-channel grayscale matrix.
+///
+/// See [`read_png_gray`] for how color sources are handled.
+pub fn decode_png_gray(bytes: &[u8], method: GrayscaleMethod) -> Result<Matrix1, ImageError> {
+    read_png_gray_from(Cursor::new(bytes), method)
+}
+
+/// Reads a PNG image from any reader as a single-channel grayscale matrix.
+///
+/// See [`read_png_gray`] for how color sources are handled.
+pub fn read_png_gray_from<R: Read>(
+    reader: R,
+    method: GrayscaleMethod,
+) -> Result<Matrix1, ImageError> {
+    let (width, height, color_type, buf) = decode_png_pixels(reader)?;
+
+    let gray_data = match color_type {
+        ColorType::Grayscale => buf,
+        ColorType::GrayscaleAlpha => buf.chunks_exact(2).map(|chunk| chunk[0]).collect(),
+        _ => {
+            let rgb_data = png_pixels_to_rgb(color_type, buf)?;
+            return Ok(Matrix3::new(width, height, rgb_data).to_grayscale_with_method(method));
         }
     };
 
-    Ok(Matrix3::new(width, height, rgb_data))
+    Ok(Matrix1::new(width, height, gray_data))
+}
+
+/// Reads a PNG image file as a three-channel matrix with 16-bit samples.
+///
+/// 16-bit PNGs are read losslessly; 8-bit PNGs are scaled to the full 16-bit range.
+/// Use [`read_png`] instead to get an 8-bit `Matrix3`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the PNG file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3U16` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_png_16;
+///
+/// let image = read_png_16("scan.png").expect("Failed to read PNG");
+/// println!("Image dimensions: {}x{}", image.width(), image.height());
+/// ```
+pub fn read_png_16<P: AsRef<Path>>(path: P) -> Result<Matrix3U16, ImageError> {
+    let file = File::open(path)?;
+    read_png_16_from(BufReader::new(file))
+}
+
+/// Decodes an in-memory PNG image as a three-channel matrix with 16-bit samples.
+///
+/// See [`read_png_16`] for how bit depths are handled.
+pub fn decode_png_16(bytes: &[u8]) -> Result<Matrix3U16, ImageError> {
+    read_png_16_from(Cursor::new(bytes))
+}
+
+/// Reads a PNG image from any reader as a three-channel matrix with 16-bit samples.
+///
+/// See [`read_png_16`] for how bit depths are handled.
+pub fn read_png_16_from<R: Read>(reader: R) -> Result<Matrix3U16, ImageError> {
+    let (width, height, color_type, samples) = decode_png_pixels_16(reader)?;
+    let rgb_data = match color_type {
+        ColorType::Rgb => samples,
+        ColorType::Rgba => samples
+            .chunks_exact(4)
+            .flat_map(|chunk| [chunk[0], chunk[1], chunk[2]])
+            .collect(),
+        ColorType::Grayscale => samples.iter().flat_map(|&v| [v, v, v]).collect(),
+        ColorType::GrayscaleAlpha => samples
+            .chunks_exact(2)
+            .flat_map(|chunk| [chunk[0], chunk[0], chunk[0]])
+            .collect(),
+        ColorType::Indexed => {
+            return Err(ImageError::PngDecode(
+                "Indexed PNG was not expanded".to_string(),
+            ));
+        }
+    };
+
+    Ok(Matrix3U16::new(width, height, rgb_data))
+}
+
+/// Reads a PNG image file as a single-channel matrix with 16-bit samples.
+///
+/// This is the natural representation for depth maps and other 16-bit grayscale data.
+/// Color PNGs are converted using the given `method`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the PNG file
+/// * `method` - Grayscale conversion method applied to color sources
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix1U16` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{GrayscaleMethod, io::{read_png_gray_16, write_png}};
+///
+/// let depth = read_png_gray_16("depth.png", GrayscaleMethod::Luminosity).expect("Failed to read PNG");
+/// write_png(&depth, "depth_copy.png").expect("Failed to write PNG");
+/// ```
+pub fn read_png_gray_16<P: AsRef<Path>>(
+    path: P,
+    method: GrayscaleMethod,
+) -> Result<Matrix1U16, ImageError> {
+    let file = File::open(path)?;
+    read_png_gray_16_from(BufReader::new(file), method)
+}
+
+/// Decodes an in-memory PNG image as a single-channel matrix with 16-bit samples.
+///
+/// See [`read_png_gray_16`] for how color sources are handled.
+pub fn decode_png_gray_16(bytes: &[u8], method: GrayscaleMethod) -> Result<Matrix1U16, ImageError> {
+    read_png_gray_16_from(Cursor::new(bytes), method)
+}
+
+/// Reads a PNG image from any reader as a single-channel matrix with 16-bit samples.
+///
+/// See [`read_png_gray_16`] for how color sources are handled.
+pub fn read_png_gray_16_from<R: Read>(
+    reader: R,
+    method: GrayscaleMethod,
+) -> Result<Matrix1U16, ImageError> {
+    let (width, height, color_type, samples) = decode_png_pixels_16(reader)?;
+    let gray_data = match color_type {
+        ColorType::Grayscale => samples,
+        ColorType::GrayscaleAlpha => samples.chunks_exact(2).map(|chunk| chunk[0]).collect(),
+        ColorType::Rgb => {
+            return Ok(Matrix3U16::new(width, height, samples).to_grayscale_with_method(method));
+        }
+        ColorType::Rgba => {
+            let rgb = samples
+                .chunks_exact(4)
+                .flat_map(|chunk| [chunk[0], chunk[1], chunk[2]])
+                .collect();
+            return Ok(Matrix3U16::new(width, height, rgb).to_grayscale_with_method(method));
+        }
+        ColorType::Indexed => {
+            return Err(ImageError::PngDecode(
+                "Indexed PNG was not expanded".to_string(),
+            ));
+        }
+    };
+
+    Ok(Matrix1U16::new(width, height, gray_data))
+}
+
+/// Decodes a PNG stream into its native pixel layout.
+///
+/// Palettes (including tRNS transparency) and bit depths below 8 are expanded,
+/// and 16-bit images are reduced to 8 bits per sample.
+fn decode_png_pixels<R: Read>(reader: R) -> Result<(usize, usize, ColorType, Vec<u8>), ImageError> {
+    let (width, height, color_type, bit_depth, buf) =
+        decode_png_frame(reader, Transformations::normalize_to_color8())?;
+
+    if bit_depth != BitDepth::Eight {
+        return Err(ImageError::UnsupportedFormat(format!(
+            "Unsupported PNG bit depth: {:?}",
+            bit_depth
+        )));
+    }
+
+    Ok((width, height, color_type, buf))
+}
+
+/// Decodes a PNG stream into 16-bit samples.
+///
+/// Palettes are expanded and images with fewer than 16 bits per sample are scaled
+/// to the full 16-bit range.
+fn decode_png_pixels_16<R: Read>(
+    reader: R,
+) -> Result<(usize, usize, ColorType, Vec<u16>), ImageError> {
+    let (width, height, color_type, bit_depth, buf) =
+        decode_png_frame(reader, Transformations::EXPAND)?;
+
+    let samples = match bit_depth {
+        BitDepth::Sixteen => be_bytes_to_u16(&buf),
+        BitDepth::Eight => buf.iter().map(|&v| v as u16 * 257).collect(),
+        _ => {
+            return Err(ImageError::UnsupportedFormat(format!(
+                "Unsupported PNG bit depth: {:?}",
+                bit_depth
+            )));
+        }
+    };
+
+    Ok((width, height, color_type, samples))
+}
+
+/// Decodes the first frame of a PNG stream with the given decoder transformations.
+fn decode_png_frame<R: Read>(
+    reader: R,
+    transformations: Transformations,
+) -> Result<(usize, usize, ColorType, BitDepth, Vec<u8>), ImageError> {
+    // Create decoder
+    let mut decoder = PngDecoder::new(reader);
+    decoder.set_transformations(transformations);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+
+    // Get image metadata
+    let info = reader.info();
+    let width = info.width as usize;
+    let height = info.height as usize;
+    let (color_type, bit_depth) = reader.output_color_type();
+
+    // Allocate buffer for image data
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+
+    // Resize buffer to actual data size
+    buf.truncate(info.buffer_size());
+
+    Ok((width, height, color_type, bit_depth, buf))
+}
+
+/// Converts decoded PNG pixels to interleaved RGB.
+fn png_pixels_to_rgb(color_type: ColorType, buf: Vec<u8>) -> Result<Vec<u8>, ImageError> {
+    let rgb_data = match color_type {
+        ColorType::Rgb => {
+            // Already in RGB format
+            buf
+        }
+        ColorType::Rgba => {
+            // RGBA - strip alpha channel
+            let mut rgb = Vec::with_capacity(buf.len() / 4 * 3);
+            for chunk in buf.chunks_exact(4) {
+                rgb.push(chunk[0]);
+                rgb.push(chunk[1]);
+                rgb.push(chunk[2]);
+            }
+            rgb
+        }
+        ColorType::Grayscale => {
+            // Grayscale - convert to RGB by duplicating the channel
+            let mut rgb = Vec::with_capacity(buf.len() * 3);
+            for &gray in &buf {
+                rgb.push(gray);
+                rgb.push(gray);
+                rgb.push(gray);
+            }
+            rgb
+        }
+        ColorType::GrayscaleAlpha => {
+            // Grayscale with alpha - convert to RGB and strip alpha
+            let mut rgb = Vec::with_capacity(buf.len() / 2 * 3);
+            for chunk in buf.chunks_exact(2) {
+                let gray = chunk[0];
+                rgb.push(gray);
+                rgb.push(gray);
+                rgb.push(gray);
+            }
+            rgb
+        }
+        ColorType::Indexed => {
+            // Indexed color is expanded by the decoder
+            return Err(ImageError::PngDecode(
+                "Indexed PNG was not expanded".to_string(),
+            ));
+        }
+    };
+
+    Ok(rgb_data)
+}
+
+/// Writes an image as a JPEG file.
+///
+/// `Matrix3` images are written as RGB JPEGs and `Matrix1` images as single-channel
+/// grayscale JPEGs.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `path` - Path where the JPEG file should be written
+/// * `quality` - JPEG quality (1-100, where 100 is best quality)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::write_jpeg};
+///
+/// let image = Matrix3::zeros(640, 480);
+/// write_jpeg(&image, "output.jpg", 90).expect("Failed to write JPEG");
+/// ```
+pub fn write_jpeg<I: WritableImage, P: AsRef<Path>>(
+    matrix: &I,
+    path: P,
+    quality: u8,
+) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_jpeg_to(matrix, io::BufWriter::new(file), quality)
+}
+
+/// Encodes an image as JPEG into an in-memory buffer.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to encode (`Matrix1` or `Matrix3`)
+/// * `quality` - JPEG quality (1-100, where 100 is best quality)
+///
+/// # Returns
+///
+/// Returns the encoded JPEG bytes on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::{Matrix3, io::encode_jpeg};
+///
+/// let image = Matrix3::zeros(64, 48);
+/// let bytes = encode_jpeg(&image, 90).expect("Failed to encode JPEG");
+/// assert_eq!(&bytes[..2], &[0xFF, 0xD8]);
+/// ```
+pub fn encode_jpeg<I: WritableImage>(matrix: &I, quality: u8) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+    write_jpeg_to(matrix, &mut bytes, quality)?;
+    Ok(bytes)
+}
+
+/// Writes an image as JPEG to any writer.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `writer` - Destination for the encoded JPEG data (e.g. a socket or file)
+/// * `quality` - JPEG quality (1-100, where 100 is best quality)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+pub fn write_jpeg_to<I: WritableImage, W: Write>(
+    matrix: &I,
+    writer: W,
+    quality: u8,
+) -> Result<(), ImageError> {
+    write_jpeg_with_options_to(
+        matrix,
+        writer,
+        &JpegOptions::default().with_quality(quality),
+    )
+}
+
+/// Writes an image as a JPEG file with explicit encoder settings.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `path` - Path where the JPEG file should be written
+/// * `options` - Encoder settings
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::{write_jpeg_with_options, ChromaSubsampling, JpegOptions}};
+///
+/// let image = Matrix3::zeros(640, 480);
+/// let options = JpegOptions::default().with_subsampling(ChromaSubsampling::Yuv444);
+/// write_jpeg_with_options(&image, "annotated.jpg", &options).expect("Failed to write JPEG");
+/// ```
+pub fn write_jpeg_with_options<I: WritableImage, P: AsRef<Path>>(
+    matrix: &I,
+    path: P,
+    options: &JpegOptions,
+) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_jpeg_with_options_to(matrix, io::BufWriter::new(file), options)
+}
+
+/// Encodes an image as JPEG into an in-memory buffer with explicit encoder settings.
+///
+/// See [`write_jpeg_with_options`] for details.
+pub fn encode_jpeg_with_options<I: WritableImage>(
+    matrix: &I,
+    options: &JpegOptions,
+) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+    write_jpeg_with_options_to(matrix, &mut bytes, options)?;
+    Ok(bytes)
+}
+
+/// Writes an image as JPEG to any writer with explicit encoder settings.
+///
+/// See [`write_jpeg_with_options`] for details.
+pub fn write_jpeg_with_options_to<I: WritableImage, W: Write>(
+    matrix: &I,
+    mut writer: W,
+    options: &JpegOptions,
+) -> Result<(), ImageError> {
+    use jpeg_encoder::ColorType as JpegColorType;
+
+    if matrix.bit_depth() != 8 {
+        return Err(ImageError::UnsupportedFormat(
+            "JPEG only supports 8-bit samples".to_string(),
+        ));
+    }
+
+    let samples = matrix.sample_bytes();
+    let gray;
+    let (samples, color_type) = if matrix.channels() == 1 {
+        (&*samples, JpegColorType::Luma)
+    } else if options.grayscale {
+        gray = Matrix3::new(matrix.width(), matrix.height(), samples.into_owned()).to_grayscale();
+        (gray.data(), JpegColorType::Luma)
+    } else {
+        (&*samples, JpegColorType::Rgb)
+    };
+
+    let size = (matrix.width() as u16, matrix.height() as u16);
+    match &options.comment {
+        // The comment has no encoder API, so it is spliced into the finished stream
+        Some(comment) => {
+            let mut buffer = Vec::new();
+            encode_jpeg_stream(&mut buffer, samples, size, color_type, options)?;
+            insert_jpeg_comment(&mut buffer, comment)?;
+            writer.write_all(&buffer)?;
+        }
+        None => encode_jpeg_stream(&mut writer, samples, size, color_type, options)?,
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Runs the JPEG encoder with every setting in `options` except the comment.
+fn encode_jpeg_stream<W: Write>(
+    writer: W,
+    samples: &[u8],
+    (width, height): (u16, u16),
+    color_type: jpeg_encoder::ColorType,
+    options: &JpegOptions,
+) -> Result<(), ImageError> {
+    use jpeg_encoder::{Encoder, SamplingFactor};
+
+    let encode_error = |e: jpeg_encoder::EncodingError| ImageError::JpegEncode(format!("{}", e));
+
+    let mut encoder = Encoder::new(writer, options.quality.clamp(1, 100));
+
+    if let Some(subsampling) = options.subsampling {
+        encoder.set_sampling_factor(match subsampling {
+            ChromaSubsampling::Yuv444 => SamplingFactor::R_4_4_4,
+            ChromaSubsampling::Yuv422 => SamplingFactor::R_4_2_2,
+            ChromaSubsampling::Yuv420 => SamplingFactor::R_4_2_0,
+        });
+    }
+    encoder.set_progressive(options.progressive);
+    encoder.set_optimized_huffman_tables(options.optimize_huffman);
+    if let Some(interval) = options.restart_interval {
+        encoder.set_restart_interval(interval);
+    }
+    if let Some(exif) = &options.exif {
+        let mut segment = Vec::with_capacity(exif.len() + 6);
+        if !exif.starts_with(b"Exif\0\0") {
+            segment.extend_from_slice(b"Exif\0\0");
+        }
+        segment.extend_from_slice(exif);
+        encoder.add_app_segment(1, &segment).map_err(encode_error)?;
+    }
+    if let Some(profile) = &options.icc_profile {
+        encoder.add_icc_profile(profile).map_err(encode_error)?;
+    }
+
+    encoder
+        .encode(samples, width, height, color_type)
+        .map_err(encode_error)
+}
+
+/// Inserts a COM segment after the SOI marker and any APPn segments.
+fn insert_jpeg_comment(jpeg: &mut Vec<u8>, comment: &str) -> Result<(), ImageError> {
+    let length = u16::try_from(comment.len() + 2)
+        .map_err(|_| ImageError::JpegEncode("JPEG comment is too long".to_string()))?;
+
+    let mut pos = 2;
+    while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF && (0xE0..=0xEF).contains(&jpeg[pos + 1]) {
+        pos += 2 + u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+    }
+
+    let mut segment = vec![0xFF, 0xFE];
+    segment.extend_from_slice(&length.to_be_bytes());
+    segment.extend_from_slice(comment.as_bytes());
+    jpeg.splice(pos..pos, segment);
+
+    Ok(())
+}
+
+/// Writes an image as a PNG file.
+///
+/// `Matrix3` images are written as RGB PNGs and `Matrix1` images as single-channel
+/// grayscale PNGs. `Matrix1U16` and `Matrix3U16` images are written losslessly as
+/// 16-bit PNGs.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `path` - Path where the PNG file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::write_png};
+///
+/// let image = Matrix3::zeros(640, 480);
+/// write_png(&image, "output.png").expect("Failed to write PNG");
+/// ```
+pub fn write_png<I: WritableImage, P: AsRef<Path>>(matrix: &I, path: P) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_png_to(matrix, io::BufWriter::new(file))
+}
+
+/// Encodes an image as PNG into an in-memory buffer.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to encode (`Matrix1` or `Matrix3`)
+///
+/// # Returns
+///
+/// Returns the encoded PNG bytes on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::{Matrix3, io::{decode_png, encode_png}};
+///
+/// let image = Matrix3::zeros(64, 48);
+/// let bytes = encode_png(&image).expect("Failed to encode PNG");
+/// let decoded = decode_png(&bytes).expect("Failed to decode PNG");
+/// assert_eq!(decoded.dimensions(), (64, 48));
+/// ```
+pub fn encode_png<I: WritableImage>(matrix: &I) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+    write_png_to(matrix, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes an image as PNG to any writer.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `writer` - Destination for the encoded PNG data (e.g. a socket or file)
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+pub fn write_png_to<I: WritableImage, W: Write>(matrix: &I, writer: W) -> Result<(), ImageError> {
+    use png::Encoder;
+
+    // Create encoder
+    let mut encoder = Encoder::new(writer, matrix.width() as u32, matrix.height() as u32);
+    encoder.set_color(if matrix.channels() == 1 {
+        ColorType::Grayscale
+    } else {
+        ColorType::Rgb
+    });
+    encoder.set_depth(if matrix.bit_depth() == 16 {
+        BitDepth::Sixteen
+    } else {
+        BitDepth::Eight
+    });
+
+    // Write the PNG header
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
+
+    // Write the image data
+    writer
+        .write_image_data(&matrix.sample_bytes())
+        .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
+
+    Ok(())
+}
+
+/// Decodes a PNG stream one row at a time.
+///
+/// Only a single row of pixels is held in memory, so images far larger than the
+/// available RAM can be converted or downscaled as their rows arrive. Grayscale PNGs
+/// produce single-channel rows and all other PNGs produce RGB rows: palettes and bit
+/// depths other than 8 are converted to 8-bit samples and alpha is dropped. Interlaced
+/// PNGs store their rows out of order and cannot be streamed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::{PngRowReader, PngRowWriter};
+/// use cv_rusty::GrayscaleMethod;
+///
+/// // Convert a large PNG to grayscale while holding only one row in memory
+/// let mut reader = PngRowReader::open("scan.png").expect("Failed to open PNG");
+/// let mut writer = PngRowWriter::create("scan_gray.png", reader.width(), reader.height(), 1)
+///     .expect("Failed to create PNG");
+/// let mut gray = vec![0u8; reader.width()];
+/// while let Some(row) = reader.next_row().expect("Failed to read row") {
+///     if row.len() == gray.len() {
+///         gray.copy_from_slice(row);
+///     } else {
+///         GrayscaleMethod::Luminosity.convert_row(row, &mut gray);
+///     }
+///     writer.write_row(&gray).expect("Failed to write row");
+/// }
+/// writer.finish().expect("Failed to finish PNG");
+/// ```
+pub struct PngRowReader<R: Read> {
+    reader: png::Reader<R>,
+    color_type: ColorType,
+    row: Vec<u8>,
+}
+
+impl PngRowReader<BufReader<File>> {
+    /// Opens a PNG file and reads its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the PNG file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the reader on success, or an `ImageError` if the
+    /// file cannot be opened, the header is invalid or the image is interlaced.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> PngRowReader<R> {
+    /// Creates a row reader over any PNG stream and reads its header.
+    ///
+    /// See [`PngRowReader::open`] for the possible errors.
+    pub fn new(reader: R) -> Result<Self, ImageError> {
+        let mut decoder = PngDecoder::new(reader);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+        let reader = decoder
+            .read_info()
+            .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+
+        if reader.info().interlaced {
+            return Err(ImageError::UnsupportedFormat(
+                "Interlaced PNGs cannot be decoded row by row".to_string(),
+            ));
+        }
+        let (color_type, bit_depth) = reader.output_color_type();
+        if bit_depth != BitDepth::Eight || color_type == ColorType::Indexed {
+            return Err(ImageError::UnsupportedFormat(format!(
+                "Unsupported PNG pixel format: {:?} {:?}",
+                color_type, bit_depth
+            )));
+        }
+
+        let row = Vec::with_capacity(reader.info().width as usize * 3);
+        Ok(Self {
+            reader,
+            color_type,
+            row,
+        })
+    }
+
+    /// Returns the image width in pixels.
+    pub fn width(&self) -> usize {
+        self.reader.info().width as usize
+    }
+
+    /// Returns the image height in pixels.
+    pub fn height(&self) -> usize {
+        self.reader.info().height as usize
+    }
+
+    /// Returns the number of samples per pixel in the rows: 1 for grayscale, 3 for RGB.
+    pub fn channels(&self) -> usize {
+        match self.color_type {
+            ColorType::Grayscale | ColorType::GrayscaleAlpha => 1,
+            _ => 3,
+        }
+    }
+
+    /// Decodes the next row, top to bottom.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(row))` with `width * channels` samples, `Ok(None)` after the
+    /// last row, or an `ImageError` if the data is corrupt.
+    pub fn next_row(&mut self) -> Result<Option<&[u8]>, ImageError> {
+        let Some(row) = self
+            .reader
+            .next_row()
+            .map_err(|e| ImageError::PngDecode(format!("{}", e)))?
+        else {
+            return Ok(None);
+        };
+
+        let data = row.data();
+        self.row.clear();
+        match self.color_type {
+            ColorType::GrayscaleAlpha => self.row.extend(data.iter().step_by(2)),
+            ColorType::Rgba => self
+                .row
+                .extend(data.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]])),
+            _ => self.row.extend_from_slice(data),
+        }
+        Ok(Some(&self.row))
+    }
+}
+
+/// Encodes a PNG image one row at a time.
+///
+/// Rows are compressed as they are written, so an image can be produced without ever
+/// holding it in memory. Every row must be written before calling
+/// [`PngRowWriter::finish`]. The output must be an owned writer such as a file or
+/// socket. See [`PngRowReader`] for an example.
+pub struct PngRowWriter<W: Write + 'static> {
+    writer: png::StreamWriter<'static, W>,
+    row_len: usize,
+    rows_left: usize,
+}
+
+impl PngRowWriter<io::BufWriter<File>> {
+    /// Creates a PNG file and writes its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path where the PNG file should be written
+    /// * `width` - Image width in pixels
+    /// * `height` - Image height in pixels
+    /// * `channels` - 1 for grayscale or 3 for RGB rows
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the writer on success, or an `ImageError` on failure.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        width: usize,
+        height: usize,
+        channels: usize,
+    ) -> Result<Self, ImageError> {
+        let file = File::create(path)?;
+        Self::new(io::BufWriter::new(file), width, height, channels)
+    }
+}
+
+impl<W: Write + 'static> PngRowWriter<W> {
+    /// Creates a row writer over any output and writes the PNG header.
+    ///
+    /// See [`PngRowWriter::create`] for the arguments.
+    pub fn new(
+        writer: W,
+        width: usize,
+        height: usize,
+        channels: usize,
+    ) -> Result<Self, ImageError> {
+        let color_type = match channels {
+            1 => ColorType::Grayscale,
+            3 => ColorType::Rgb,
+            _ => {
+                return Err(ImageError::UnsupportedFormat(format!(
+                    "Cannot stream {}-channel rows",
+                    channels
+                )));
+            }
+        };
+
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(color_type);
+        encoder.set_depth(BitDepth::Eight);
+        let writer = encoder
+            .write_header()
+            .and_then(|writer| writer.into_stream_writer())
+            .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
+
+        Ok(Self {
+            writer,
+            row_len: width * channels,
+            rows_left: height,
+        })
+    }
+
+    /// Appends the next row, top to bottom.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - `width * channels` samples
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `ImageError` if the row has the wrong length,
+    /// all rows have already been written, or writing fails.
+    pub fn write_row(&mut self, row: &[u8]) -> Result<(), ImageError> {
+        check_stream_row(row, self.row_len, self.rows_left).map_err(ImageError::PngEncode)?;
+        self.writer.write_all(row)?;
+        self.rows_left -= 1;
+        Ok(())
+    }
+
+    /// Completes the PNG stream.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `ImageError` if rows are missing or writing fails.
+    pub fn finish(self) -> Result<(), ImageError> {
+        if self.rows_left > 0 {
+            return Err(ImageError::PngEncode(format!(
+                "{} rows were never written",
+                self.rows_left
+            )));
+        }
+        self.writer
+            .finish()
+            .map_err(|e| ImageError::PngEncode(format!("{}", e)))
+    }
+}
+
+/// Checks a row handed to one of the streaming writers.
+fn check_stream_row(row: &[u8], row_len: usize, rows_left: usize) -> Result<(), String> {
+    if rows_left == 0 {
+        return Err("All rows have already been written".to_string());
+    }
+    if row.len() != row_len {
+        return Err(format!(
+            "Row has {} samples but {} were expected",
+            row.len(),
+            row_len
+        ));
+    }
+    Ok(())
+}
+
+/// A palette-based image: one palette index per pixel plus the palette itself.
+///
+/// This is the natural representation for label and segmentation masks, and is
+/// written to disk as a compact indexed PNG by [`write_png_indexed`].
+#[derive(Debug, Clone)]
+pub struct IndexedImage {
+    /// Palette index of every pixel
+    pub indices: Matrix1,
+    /// Palette entries as (r, g, b)
+    pub palette: Vec<(u8, u8, u8)>,
+    /// Optional per-entry opacity (tRNS); entries without a value are opaque
+    pub alpha: Vec<u8>,
+}
+
+impl IndexedImage {
+    /// Creates an indexed image from an index map and an explicit palette.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - Palette index of every pixel
+    /// * `palette` - Palette entries as (r, g, b), at most 256
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix1, io::IndexedImage};
+    ///
+    /// // Background, road and car labels
+    /// let labels = Matrix1::zeros(320, 240);
+    /// let mask = IndexedImage::new(labels, vec![(0, 0, 0), (128, 64, 128), (0, 0, 142)]);
+    /// ```
+    pub fn new(indices: Matrix1, palette: Vec<(u8, u8, u8)>) -> Self {
+        Self {
+            indices,
+            palette,
+            alpha: Vec::new(),
+        }
+    }
+
+    /// Builds an indexed image from an RGB image using at most `max_colors` colors.
+    ///
+    /// Images with no more than `max_colors` distinct colors (such as rendered masks)
+    /// are converted losslessly. Otherwise the palette is chosen with median-cut
+    /// quantization and pixels are mapped to their nearest palette color. See
+    /// [`IndexedImage::quantize_with`] for other algorithms and dithering.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The RGB image to quantize
+    /// * `max_colors` - Maximum palette size (clamped to 1-256)
+    pub fn quantize(image: &Matrix3, max_colors: usize) -> Self {
+        Self::quantize_with(
+            image,
+            max_colors,
+            QuantizeMethod::MedianCut,
+            DitherMethod::None,
+        )
+    }
+
+    /// Builds an indexed image from an RGB image with a chosen quantization algorithm
+    /// and dithering.
+    ///
+    /// Images with no more than `max_colors` distinct colors are still converted
+    /// losslessly, without dithering.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The RGB image to quantize
+    /// * `max_colors` - Maximum palette size (clamped to 1-256)
+    /// * `method` - Algorithm used to choose the palette
+    /// * `dither` - Dithering applied when mapping pixels onto the palette
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{DitherMethod, Matrix3, QuantizeMethod, io::IndexedImage};
+    ///
+    /// let image = Matrix3::zeros(320, 240);
+    /// let indexed =
+    ///     IndexedImage::quantize_with(&image, 64, QuantizeMethod::KMeans, DitherMethod::FloydSteinberg);
+    /// ```
+    pub fn quantize_with(
+        image: &Matrix3,
+        max_colors: usize,
+        method: QuantizeMethod,
+        dither: DitherMethod,
+    ) -> Self {
+        let max_colors = max_colors.clamp(1, 256);
+
+        let mut lookup = std::collections::HashMap::new();
+        let mut palette = Vec::new();
+        let mut indices = Vec::with_capacity(image.width() * image.height());
+        for rgb in image.data().chunks_exact(3) {
+            let color = (rgb[0], rgb[1], rgb[2]);
+            let next = palette.len();
+            let index = *lookup.entry(color).or_insert(next);
+            if index == next {
+                if next == max_colors {
+                    let (palette, indices) = image.quantize(max_colors, method, dither);
+                    return Self::new(indices, palette);
+                }
+                palette.push(color);
+            }
+            indices.push(index as u8);
+        }
+
+        Self::new(
+            Matrix1::new(image.width(), image.height(), indices),
+            palette,
+        )
+    }
+
+    /// Expands the palette into an RGB image.
+    ///
+    /// Indices outside the palette are rendered black.
+    pub fn to_rgb(&self) -> Matrix3 {
+        let data = self
+            .indices
+            .data()
+            .iter()
+            .flat_map(|&i| {
+                let (r, g, b) = self.palette.get(i as usize).copied().unwrap_or((0, 0, 0));
+                [r, g, b]
+            })
+            .collect();
+        Matrix3::new(self.indices.width(), self.indices.height(), data)
+    }
+}
+
+/// Reads an indexed PNG file without expanding its palette.
+///
+/// # Arguments
+///
+/// * `path` - Path to the indexed PNG file
+///
+/// # Returns
+///
+/// Returns a `Result` containing an `IndexedImage` on success, or an `ImageError`
+/// on failure. Non-indexed PNGs produce `ImageError::UnsupportedFormat`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_png_indexed;
+///
+/// let mask = read_png_indexed("labels.png").expect("Failed to read PNG");
+/// println!("{} classes", mask.palette.len());
+/// ```
+pub fn read_png_indexed<P: AsRef<Path>>(path: P) -> Result<IndexedImage, ImageError> {
+    let file = File::open(path)?;
+    read_png_indexed_from(BufReader::new(file))
+}
+
+/// Reads an indexed PNG image from any reader without expanding its palette.
+///
+/// See [`read_png_indexed`] for details.
+pub fn read_png_indexed_from<R: Read>(reader: R) -> Result<IndexedImage, ImageError> {
+    let mut decoder = PngDecoder::new(reader);
+    decoder.set_transformations(Transformations::IDENTITY);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+
+    let info = reader.info();
+    if info.color_type != ColorType::Indexed {
+        return Err(ImageError::UnsupportedFormat(format!(
+            "PNG is not indexed: {:?}",
+            info.color_type
+        )));
+    }
+
+    let width = info.width as usize;
+    let height = info.height as usize;
+    let bits = info.bit_depth as usize;
+    let palette = info
+        .palette
+        .as_ref()
+        .ok_or_else(|| ImageError::PngDecode("Indexed PNG has no palette".to_string()))?
+        .chunks_exact(3)
+        .map(|rgb| (rgb[0], rgb[1], rgb[2]))
+        .collect();
+    let alpha = info.trns.as_ref().map(|t| t.to_vec()).unwrap_or_default();
+
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    reader
+        .next_frame(&mut buf)
+        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+
+    // Unpack sub-byte indices (rows are padded to whole bytes)
+    let row_bytes = (width * bits).div_ceil(8);
+    let mask = ((1u16 << bits) - 1) as u8;
+    let mut indices = Vec::with_capacity(width * height);
+    for row in buf.chunks_exact(row_bytes).take(height) {
+        for x in 0..width {
+            let bit = x * bits;
+            let shift = 8 - bits - bit % 8;
+            indices.push((row[bit / 8] >> shift) & mask);
+        }
+    }
+
+    Ok(IndexedImage {
+        indices: Matrix1::new(width, height, indices),
+        palette,
+        alpha,
+    })
+}
+
+/// Writes an indexed image as a palette PNG file.
+///
+/// The smallest bit depth (1, 2, 4 or 8) that can hold the palette is used,
+/// which keeps label and segmentation masks very small.
+///
+/// # Arguments
+///
+/// * `image` - The indexed image to write (see [`IndexedImage::new`] and
+///   [`IndexedImage::quantize`])
+/// * `path` - Path where the PNG file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::{write_png_indexed, IndexedImage}};
+///
+/// let image = Matrix3::zeros(640, 480);
+/// write_png_indexed(&IndexedImage::quantize(&image, 16), "output.png").expect("Failed to write PNG");
+/// ```
+pub fn write_png_indexed<P: AsRef<Path>>(image: &IndexedImage, path: P) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_png_indexed_to(image, io::BufWriter::new(file))
+}
+
+/// Encodes an indexed image as a palette PNG into an in-memory buffer.
+///
+/// See [`write_png_indexed`] for details.
+pub fn encode_png_indexed(image: &IndexedImage) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+    write_png_indexed_to(image, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes an indexed image as a palette PNG to any writer.
+///
+/// See [`write_png_indexed`] for details.
+pub fn write_png_indexed_to<W: Write>(image: &IndexedImage, writer: W) -> Result<(), ImageError> {
+    use png::Encoder;
+
+    let palette_len = image.palette.len();
+    if palette_len == 0 || palette_len > 256 {
+        return Err(ImageError::PngEncode(format!(
+            "Palette must have 1-256 entries, got {}",
+            palette_len
+        )));
+    }
+    if image.alpha.len() > palette_len {
+        return Err(ImageError::PngEncode(
+            "More alpha values than palette entries".to_string(),
+        ));
+    }
+    if let Some(&index) = image
+        .indices
+        .data()
+        .iter()
+        .find(|&&i| i as usize >= palette_len)
+    {
+        return Err(ImageError::PngEncode(format!(
+            "Index {} is outside the palette",
+            index
+        )));
+    }
+
+    let (bits, bit_depth) = match palette_len {
+        0..=2 => (1, BitDepth::One),
+        3..=4 => (2, BitDepth::Two),
+        5..=16 => (4, BitDepth::Four),
+        _ => (8, BitDepth::Eight),
+    };
+
+    let width = image.indices.width();
+    let mut encoder = Encoder::new(writer, width as u32, image.indices.height() as u32);
+    encoder.set_color(ColorType::Indexed);
+    encoder.set_depth(bit_depth);
+    encoder.set_palette(
+        image
+            .palette
+            .iter()
+            .flat_map(|&(r, g, b)| [r, g, b])
+            .collect::<Vec<u8>>(),
+    );
+    if !image.alpha.is_empty() {
+        encoder.set_trns(image.alpha.clone());
+    }
+
+    // Pack indices into rows padded to whole bytes
+    let row_bytes = (width * bits).div_ceil(8);
+    let mut packed = vec![0u8; row_bytes * image.indices.height()];
+    if width > 0 {
+        for (row, out) in image
+            .indices
+            .data()
+            .chunks_exact(width)
+            .zip(packed.chunks_exact_mut(row_bytes))
+        {
+            for (x, &index) in row.iter().enumerate() {
+                let bit = x * bits;
+                out[bit / 8] |= index << (8 - bits - bit % 8);
+            }
+        }
+    }
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
+    writer
+        .write_image_data(&packed)
+        .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
+
+    Ok(())
+}
+
+/// Reads a Netpbm image file (PBM, PGM, PPM or PAM, ASCII or binary).
+///
+/// Grayscale files decode to `DynamicImage::Gray8`/`Gray16` and color files to
+/// `DynamicImage::Rgb8`/`Rgb16`, depending on the file's `maxval`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the Netpbm file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `DynamicImage` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_pnm;
+///
+/// let image = read_pnm("depth.pgm").expect("Failed to read PGM");
+/// let (width, height) = image.dimensions();
+/// ```
+pub fn read_pnm<P: AsRef<Path>>(path: P) -> Result<DynamicImage, ImageError> {
+    let bytes = std::fs::read(path)?;
+    Ok(netpbm::decode(&bytes)?)
+}
+
+/// Writes an image as a PGM (single-channel) or PPM (RGB) file.
+///
+/// 16-bit images are written with `maxval` 65535.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1`, `Matrix3` or their 16-bit variants)
+/// * `path` - Path where the file should be written
+/// * `encoding` - ASCII (`P2`/`P3`) or binary (`P5`/`P6`) samples
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, codec::netpbm::PnmEncoding, io::write_pnm};
+///
+/// let image = Matrix3::zeros(640, 480);
+/// write_pnm(&image, "output.ppm", PnmEncoding::Binary).expect("Failed to write PPM");
+/// ```
+pub fn write_pnm<I: WritableImage, P: AsRef<Path>>(
+    matrix: &I,
+    path: P,
+    encoding: PnmEncoding,
+) -> Result<(), ImageError> {
+    std::fs::write(path, netpbm::encode(matrix, encoding))?;
+    Ok(())
+}
+
+/// Writes a grayscale image as a bilevel PBM file.
+///
+/// Pixels darker than `threshold` are written as black.
+///
+/// # Arguments
+///
+/// * `matrix` - The grayscale image to write
+/// * `path` - Path where the file should be written
+/// * `threshold` - Gray level at or above which a pixel is white
+/// * `encoding` - ASCII (`P1`) or packed binary (`P4`) bits
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+pub fn write_pbm<P: AsRef<Path>>(
+    matrix: &Matrix1,
+    path: P,
+    threshold: u8,
+    encoding: PnmEncoding,
+) -> Result<(), ImageError> {
+    std::fs::write(path, netpbm::encode_pbm(matrix, threshold, encoding))?;
+    Ok(())
+}
+
+/// Writes an image as a PAM (`P7`) file.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1`, `Matrix3` or their 16-bit variants)
+/// * `path` - Path where the file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+pub fn write_pam<I: WritableImage, P: AsRef<Path>>(matrix: &I, path: P) -> Result<(), ImageError> {
+    std::fs::write(path, netpbm::encode_pam(matrix))?;
+    Ok(())
+}
+
+/// Decodes a binary Netpbm stream (`P4`, `P5` or `P6`) one row at a time.
+///
+/// Only a single row of pixels is held in memory. PBM and PGM files produce
+/// single-channel rows and PPM files produce RGB rows. Samples are always scaled to
+/// 8 bits, including in files with a `maxval` above 255, and PBM pixels decode to
+/// 0 (black) and 255 (white). ASCII and PAM files cannot be streamed; use
+/// [`read_pnm`] for those.
+///
+/// This is a `std::io` wrapper around [`netpbm::RowDecoder`], which `no_std` targets
+/// can use directly with their own byte source.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::{PnmRowReader, PnmRowWriter};
+///
+/// // Halve the width of a large PGM, one row at a time
+/// let mut reader = PnmRowReader::open("map.pgm").expect("Failed to open PGM");
+/// let width = reader.width() / 2;
+/// let mut writer = PnmRowWriter::create("map_half.pgm", width, reader.height(), 1)
+///     .expect("Failed to create PGM");
+/// let mut half = vec![0u8; width];
+/// while let Some(row) = reader.next_row().expect("Failed to read row") {
+///     for (x, pixel) in half.iter_mut().enumerate() {
+///         *pixel = ((row[2 * x] as u16 + row[2 * x + 1] as u16) / 2) as u8;
+///     }
+///     writer.write_row(&half).expect("Failed to write row");
+/// }
+/// writer.finish().expect("Failed to finish PGM");
+/// ```
+pub struct PnmRowReader<R: Read> {
+    reader: R,
+    decoder: netpbm::RowDecoder,
+}
+
+impl PnmRowReader<BufReader<File>> {
+    /// Opens a binary Netpbm file and reads its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the PBM, PGM or PPM file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the reader on success, or an `ImageError` if the
+    /// file cannot be opened, the header is invalid or the variant cannot be streamed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> PnmRowReader<R> {
+    /// Creates a row reader over any binary Netpbm stream and reads its header.
+    ///
+    /// The header is read byte by byte, so wrap unbuffered sources in a `BufReader`.
+    /// See [`PnmRowReader::open`] for the possible errors.
+    pub fn new(mut reader: R) -> Result<Self, ImageError> {
+        // The header ends with a whitespace byte, so parsing is only retried after
+        // each one until the header is complete
+        let mut header = vec![0u8; 2];
+        read_pnm_exact(&mut reader, &mut header)?;
+        loop {
+            let last = *header.last().unwrap_or(&0);
+            if header.len() == 2 || last.is_ascii_whitespace() {
+                match netpbm::RowDecoder::new(&header) {
+                    Ok((decoder, _)) => return Ok(Self { reader, decoder }),
+                    Err(CodecError::UnexpectedEof) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            if header.len() >= MAX_PNM_HEADER {
+                return Err(
+                    CodecError::InvalidData("Netpbm header is too long".to_string()).into(),
+                );
+            }
+            let mut byte = [0u8; 1];
+            read_pnm_exact(&mut reader, &mut byte)?;
+            header.push(byte[0]);
+        }
+    }
+
+    /// Returns the image width in pixels.
+    pub fn width(&self) -> usize {
+        self.decoder.width()
+    }
+
+    /// Returns the image height in pixels.
+    pub fn height(&self) -> usize {
+        self.decoder.height()
+    }
+
+    /// Returns the number of samples per pixel in the rows: 1 for PBM and PGM, 3 for PPM.
+    pub fn channels(&self) -> usize {
+        self.decoder.channels()
+    }
+
+    /// Decodes the next row, top to bottom.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(row))` with `width * channels` samples, `Ok(None)` after the
+    /// last row, or an `ImageError` if the stream ends early or a sample exceeds the
+    /// `maxval`.
+    pub fn next_row(&mut self) -> Result<Option<&[u8]>, ImageError> {
+        let reader = &mut self.reader;
+        self.decoder.next_row(|raw| read_pnm_exact(reader, raw))
+    }
+}
+
+/// Encodes a binary PGM (`P5`) or PPM (`P6`) image one row at a time.
+///
+/// The header is written up front and each row is passed straight through to the
+/// output. Every row must be written before calling [`PnmRowWriter::finish`]. See
+/// [`PnmRowReader`] for an example, and [`netpbm::RowEncoder`] for `no_std` targets.
+pub struct PnmRowWriter<W: Write> {
+    writer: W,
+    encoder: netpbm::RowEncoder,
+}
+
+impl PnmRowWriter<io::BufWriter<File>> {
+    /// Creates a PGM or PPM file and writes its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path where the file should be written
+    /// * `width` - Image width in pixels
+    /// * `height` - Image height in pixels
+    /// * `channels` - 1 for a PGM or 3 for a PPM
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the writer on success, or an `ImageError` on failure.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        width: usize,
+        height: usize,
+        channels: usize,
+    ) -> Result<Self, ImageError> {
+        let file = File::create(path)?;
+        Self::new(io::BufWriter::new(file), width, height, channels)
+    }
+}
+
+impl<W: Write> PnmRowWriter<W> {
+    /// Creates a row writer over any output and writes the PGM or PPM header.
+    ///
+    /// See [`PnmRowWriter::create`] for the arguments.
+    pub fn new(
+        mut writer: W,
+        width: usize,
+        height: usize,
+        channels: usize,
+    ) -> Result<Self, ImageError> {
+        let encoder = netpbm::RowEncoder::new(width, height, channels)?;
+        encoder.write_header(|bytes| writer.write_all(bytes))?;
+        Ok(Self { writer, encoder })
+    }
+
+    /// Appends the next row, top to bottom.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - `width * channels` samples
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `ImageError` if the row has the wrong length,
+    /// all rows have already been written, or writing fails.
+    pub fn write_row(&mut self, row: &[u8]) -> Result<(), ImageError> {
+        let writer = &mut self.writer;
+        self.encoder.write_row(row, |bytes| {
+            writer.write_all(bytes).map_err(ImageError::from)
+        })
+    }
+
+    /// Flushes the output and returns the underlying writer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the writer on success, or an `ImageError` if rows are missing or
+    /// flushing fails.
+    pub fn finish(mut self) -> Result<W, ImageError> {
+        self.encoder.finish()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Longest Netpbm header, including comments, accepted by [`PnmRowReader`].
+const MAX_PNM_HEADER: usize = 4096;
+
+/// Fills `buf` from a Netpbm stream, reporting a truncated stream as a codec error.
+fn read_pnm_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), ImageError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => ImageError::Codec(CodecError::UnexpectedEof),
+        _ => ImageError::Io(e),
+    })
+}
+
+/// Reads a Windows bitmap (BMP) file.
+///
+/// Paletted bitmaps with a gray palette (as written by most frame grabbers) decode
+/// to `DynamicImage::Gray8`; all other bitmaps decode to `DynamicImage::Rgb8`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the BMP file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `DynamicImage` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{io::read_bmp, GrayscaleMethod};
+///
+/// let frame = read_bmp("frame_0001.bmp").expect("Failed to read BMP");
+/// let gray = frame.into_gray8(GrayscaleMethod::Luminosity);
+/// ```
+pub fn read_bmp<P: AsRef<Path>>(path: P) -> Result<DynamicImage, ImageError> {
+    let bytes = std::fs::read(path)?;
+    Ok(bmp::decode(&bytes)?)
+}
+
+/// Writes an image as a Windows bitmap (BMP) file.
+///
+/// Grayscale images are written as 8-bit paletted bitmaps and RGB images as
+/// 24-bit bitmaps.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `path` - Path where the file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::write_bmp};
+///
+/// let image = Matrix3::zeros(640, 480);
+/// write_bmp(&image, "output.bmp").expect("Failed to write BMP");
+/// ```
+pub fn write_bmp<I: WritableImage, P: AsRef<Path>>(matrix: &I, path: P) -> Result<(), ImageError> {
+    std::fs::write(path, bmp::encode(matrix)?)?;
+    Ok(())
+}
+
+/// Reads the first page of a TIFF file.
+///
+/// Uncompressed, LZW, Deflate and PackBits files with 8 or 16-bit gray or RGB samples
+/// are supported. Alpha channels are dropped.
+///
+/// # Arguments
+///
+/// * `path` - Path to the TIFF file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `DynamicImage` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_tiff;
+///
+/// let scan = read_tiff("scan.tif").expect("Failed to read TIFF");
+/// let (width, height) = scan.dimensions();
+/// ```
+pub fn read_tiff<P: AsRef<Path>>(path: P) -> Result<DynamicImage, ImageError> {
+    let file = File::open(path)?;
+    read_tiff_from(BufReader::new(file))
+}
+
+/// Decodes the first page of a TIFF image held in memory.
+///
+/// See [`read_tiff`] for details.
+pub fn decode_tiff(bytes: &[u8]) -> Result<DynamicImage, ImageError> {
+    read_tiff_from(Cursor::new(bytes))
+}
+
+/// Reads the first page of a TIFF image from any seekable reader.
+///
+/// See [`read_tiff`] for details.
+pub fn read_tiff_from<R: Read + Seek>(reader: R) -> Result<DynamicImage, ImageError> {
+    let mut decoder =
+        TiffDecoder::new(reader).map_err(|e| ImageError::TiffDecode(format!("{}", e)))?;
+    decode_tiff_page(&mut decoder)
+}
+
+/// Reads every page of a multi-page TIFF file, such as a microscopy stack.
+///
+/// # Arguments
+///
+/// * `path` - Path to the TIFF file
+///
+/// # Returns
+///
+/// Returns a `Result` containing one `DynamicImage` per page, in file order,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{io::read_tiff_pages, GrayscaleMethod};
+///
+/// let stack = read_tiff_pages("stack.tif").expect("Failed to read TIFF");
+/// let slices: Vec<_> = stack
+///     .into_iter()
+///     .map(|page| page.into_gray8(GrayscaleMethod::Luminosity))
+///     .collect();
+/// ```
+pub fn read_tiff_pages<P: AsRef<Path>>(path: P) -> Result<Vec<DynamicImage>, ImageError> {
+    let file = File::open(path)?;
+    read_tiff_pages_from(BufReader::new(file))
+}
+
+/// Decodes every page of a multi-page TIFF image held in memory.
+///
+/// See [`read_tiff_pages`] for details.
+pub fn decode_tiff_pages(bytes: &[u8]) -> Result<Vec<DynamicImage>, ImageError> {
+    read_tiff_pages_from(Cursor::new(bytes))
+}
+
+/// Reads every page of a multi-page TIFF image from any seekable reader.
+///
+/// See [`read_tiff_pages`] for details.
+pub fn read_tiff_pages_from<R: Read + Seek>(reader: R) -> Result<Vec<DynamicImage>, ImageError> {
+    let mut decoder =
+        TiffDecoder::new(reader).map_err(|e| ImageError::TiffDecode(format!("{}", e)))?;
+    let mut pages = vec![decode_tiff_page(&mut decoder)?];
+    while decoder.more_images() {
+        decoder
+            .next_image()
+            .map_err(|e| ImageError::TiffDecode(format!("{}", e)))?;
+        pages.push(decode_tiff_page(&mut decoder)?);
+    }
+    Ok(pages)
+}
+
+/// Decodes the page the decoder is currently positioned at.
+fn decode_tiff_page<R: Read + Seek>(
+    decoder: &mut TiffDecoder<R>,
+) -> Result<DynamicImage, ImageError> {
+    use tiff::ColorType as TiffColor;
+
+    let (width, height) = decoder
+        .dimensions()
+        .map_err(|e| ImageError::TiffDecode(format!("{}", e)))?;
+    let (width, height) = (width as usize, height as usize);
+    let color_type = decoder
+        .colortype()
+        .map_err(|e| ImageError::TiffDecode(format!("{}", e)))?;
+
+    // (color channels, samples per pixel including alpha)
+    let (channels, stride) = match color_type {
+        TiffColor::Gray(8 | 16) => (1, 1),
+        TiffColor::GrayA(8 | 16) => (1, 2),
+        TiffColor::RGB(8 | 16) => (3, 3),
+        TiffColor::RGBA(8 | 16) => (3, 4),
+        other => {
+            return Err(ImageError::UnsupportedFormat(format!(
+                "TIFF color type: {:?}",
+                other
+            )))
+        }
+    };
+
+    let image = match decoder
+        .read_image()
+        .map_err(|e| ImageError::TiffDecode(format!("{}", e)))?
+    {
+        DecodingResult::U8(buf) => {
+            let data = drop_alpha(buf, channels, stride);
+            if channels == 1 {
+                DynamicImage::Gray8(Matrix1::new(width, height, data))
+            } else {
+                DynamicImage::Rgb8(Matrix3::new(width, height, data))
+            }
+        }
+        DecodingResult::U16(buf) => {
+            let data = drop_alpha(buf, channels, stride);
+            if channels == 1 {
+                DynamicImage::Gray16(Matrix1U16::new(width, height, data))
+            } else {
+                DynamicImage::Rgb16(Matrix3U16::new(width, height, data))
+            }
+        }
+        _ => {
+            return Err(ImageError::UnsupportedFormat(
+                "TIFF sample format".to_string(),
+            ))
+        }
+    };
+
+    Ok(image)
+}
+
+/// Keeps the first `channels` samples of every `stride` samples.
+fn drop_alpha<T: Copy>(samples: Vec<T>, channels: usize, stride: usize) -> Vec<T> {
+    if channels == stride {
+        return samples;
+    }
+    samples
+        .chunks_exact(stride)
+        .flat_map(|pixel| pixel[..channels].iter().copied())
+        .collect()
+}
+
+/// Writes an image as a single-page TIFF file.
+///
+/// Grayscale and RGB images are written with 8 or 16 bits per sample, matching the
+/// input type.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1`, `Matrix3` or their 16-bit variants)
+/// * `path` - Path where the TIFF file should be written
+/// * `compression` - Lossless compression scheme
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix1U16, io::{write_tiff, TiffCompression}};
+///
+/// let image = Matrix1U16::zeros(512, 512);
+/// write_tiff(&image, "output.tif", TiffCompression::Deflate).expect("Failed to write TIFF");
+/// ```
+pub fn write_tiff<I: WritableImage, P: AsRef<Path>>(
+    matrix: &I,
+    path: P,
+    compression: TiffCompression,
+) -> Result<(), ImageError> {
+    write_tiff_pages(std::slice::from_ref(matrix), path, compression)
+}
+
+/// Encodes an image as a single-page TIFF into an in-memory buffer.
+///
+/// See [`write_tiff`] for details.
+pub fn encode_tiff<I: WritableImage>(
+    matrix: &I,
+    compression: TiffCompression,
+) -> Result<Vec<u8>, ImageError> {
+    encode_tiff_pages(std::slice::from_ref(matrix), compression)
+}
+
+/// Writes a sequence of images as a multi-page TIFF file.
+///
+/// Pages may differ in size. To also mix channel counts and bit depths, pass a slice
+/// of [`DynamicImage`] values.
+///
+/// # Arguments
+///
+/// * `pages` - The images to write, in page order (at least one)
+/// * `path` - Path where the TIFF file should be written
+/// * `compression` - Lossless compression scheme applied to every page
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix1, io::{write_tiff_pages, TiffCompression}};
+///
+/// let stack = vec![Matrix1::zeros(256, 256); 10];
+/// write_tiff_pages(&stack, "stack.tif", TiffCompression::Lzw).expect("Failed to write TIFF");
+/// ```
+pub fn write_tiff_pages<I: WritableImage, P: AsRef<Path>>(
+    pages: &[I],
+    path: P,
+    compression: TiffCompression,
+) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_tiff_pages_to(pages, io::BufWriter::new(file), compression)
+}
+
+/// Encodes a sequence of images as a multi-page TIFF into an in-memory buffer.
+///
+/// See [`write_tiff_pages`] for details.
+pub fn encode_tiff_pages<I: WritableImage>(
+    pages: &[I],
+    compression: TiffCompression,
+) -> Result<Vec<u8>, ImageError> {
+    let mut cursor = Cursor::new(Vec::new());
+    write_tiff_pages_to(pages, &mut cursor, compression)?;
+    Ok(cursor.into_inner())
+}
+
+/// Writes a sequence of images as a multi-page TIFF to any seekable writer.
+///
+/// See [`write_tiff_pages`] for details.
+pub fn write_tiff_pages_to<I: WritableImage, W: Write + Seek>(
+    pages: &[I],
+    writer: W,
+    compression: TiffCompression,
+) -> Result<(), ImageError> {
+    if pages.is_empty() {
+        return Err(ImageError::TiffEncode(
+            "A TIFF file needs at least one page".to_string(),
+        ));
+    }
+
+    let mut encoder =
+        TiffEncoder::new(writer).map_err(|e| ImageError::TiffEncode(format!("{}", e)))?;
+    for page in pages {
+        let width = page.width() as u32;
+        let height = page.height() as u32;
+        let bytes = page.sample_bytes();
+        match (page.channels(), page.bit_depth()) {
+            (1, 16) => write_tiff_page::<_, colortype::Gray16>(
+                &mut encoder,
+                width,
+                height,
+                &be_bytes_to_u16(&bytes),
+                compression,
+            ),
+            (1, _) => write_tiff_page::<_, colortype::Gray8>(
+                &mut encoder,
+                width,
+                height,
+                &bytes,
+                compression,
+            ),
+            (_, 16) => write_tiff_page::<_, colortype::RGB16>(
+                &mut encoder,
+                width,
+                height,
+                &be_bytes_to_u16(&bytes),
+                compression,
+            ),
+            _ => write_tiff_page::<_, colortype::RGB8>(
+                &mut encoder,
+                width,
+                height,
+                &bytes,
+                compression,
+            ),
+        }?;
+    }
+
+    Ok(())
+}
+
+/// Writes one page with the given color type and compression.
+fn write_tiff_page<W: Write + Seek, C: colortype::ColorType>(
+    encoder: &mut TiffEncoder<W>,
+    width: u32,
+    height: u32,
+    data: &[C::Inner],
+    compression: TiffCompression,
+) -> Result<(), ImageError>
+where
+    [C::Inner]: TiffValue,
+{
+    match compression {
+        TiffCompression::None => {
+            encoder.write_image_with_compression::<C, _>(width, height, Uncompressed, data)
+        }
+        TiffCompression::Lzw => {
+            encoder.write_image_with_compression::<C, _>(width, height, Lzw, data)
+        }
+        TiffCompression::Deflate => {
+            encoder.write_image_with_compression::<C, _>(width, height, Deflate::default(), data)
+        }
+    }
+    .map_err(|e| ImageError::TiffEncode(format!("{}", e)))
+}
+
+/// Reads a QOI image file as a three-channel RGB matrix.
+///
+/// # Arguments
+///
+/// * `path` - Path to the QOI file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_qoi;
+///
+/// let frame = read_qoi("debug_frame.qoi").expect("Failed to read QOI");
+/// ```
+pub fn read_qoi<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
+    let bytes = std::fs::read(path)?;
+    Ok(qoi::decode(&bytes)?)
+}
+
+/// Writes an image as a QOI file.
+///
+/// Grayscale images are expanded to RGB, as QOI has no grayscale mode.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `path` - Path where the file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::write_qoi};
+///
+/// let frame = Matrix3::zeros(640, 480);
+/// write_qoi(&frame, "debug_frame.qoi").expect("Failed to write QOI");
+/// ```
+pub fn write_qoi<I: WritableImage, P: AsRef<Path>>(matrix: &I, path: P) -> Result<(), ImageError> {
+    std::fs::write(path, qoi::encode(matrix)?)?;
+    Ok(())
+}
+
+/// Reads a TGA image file.
+///
+/// Grayscale files decode to `DynamicImage::Gray8` and all others to
+/// `DynamicImage::Rgb8`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the TGA file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `DynamicImage` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_tga;
+///
+/// let texture = read_tga("texture.tga").expect("Failed to read TGA").into_rgb8();
+/// ```
+pub fn read_tga<P: AsRef<Path>>(path: P) -> Result<DynamicImage, ImageError> {
+    let bytes = std::fs::read(path)?;
+    Ok(tga::decode(&bytes)?)
+}
+
+/// Writes an image as a TGA file.
+///
+/// # Arguments
+///
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `path` - Path where the file should be written
+/// * `compression` - Whether to run-length encode the pixel data
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+pub fn write_tga<I: WritableImage, P: AsRef<Path>>(
+    matrix: &I,
+    path: P,
+    compression: TgaCompression,
+) -> Result<(), ImageError> {
+    std::fs::write(path, tga::encode(matrix, compression)?)?;
+    Ok(())
+}
+
+/// How the area of an animation frame is treated before the next frame is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDisposal {
+    /// Leave the frame in place
+    Keep,
+    /// Clear the frame's area to transparent (black)
+    Background,
+    /// Restore the area to what it was before the frame was drawn
+    Previous,
+}
+
+/// A fully composited frame of an animated GIF or APNG.
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    /// The whole canvas as it is shown while this frame is displayed
+    pub image: Matrix3,
+    /// How long the frame is shown, in milliseconds
+    pub delay_ms: u32,
+    /// Disposal applied to the frame's area before the next frame is drawn
+    pub disposal: FrameDisposal,
+}
+
+impl AnimationFrame {
+    /// Creates a frame that is shown for `delay_ms` milliseconds and then kept.
+    pub fn new(image: Matrix3, delay_ms: u32) -> Self {
+        Self {
+            image,
+            delay_ms,
+            disposal: FrameDisposal::Keep,
+        }
+    }
+}
+
+/// RGBA canvas onto which animation sub-frames are composited.
+struct Canvas {
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            rgba: vec![0; width * height * 4],
+        }
+    }
+
+    /// Draws an RGBA sub-frame at (`left`, `top`), either alpha-blending it over the
+    /// canvas or replacing the covered area.
+    fn draw(&mut self, rgba: &[u8], left: usize, top: usize, width: usize, over: bool) {
+        for (y, row) in rgba.chunks_exact(width * 4).enumerate() {
+            let cy = top + y;
+            if cy >= self.height {
+                break;
+            }
+            for (x, src) in row.chunks_exact(4).enumerate() {
+                let cx = left + x;
+                if cx >= self.width {
+                    break;
+                }
+                let dst = &mut self.rgba[(cy * self.width + cx) * 4..][..4];
+                let sa = src[3] as u32;
+                if !over || sa == 255 {
+                    dst.copy_from_slice(src);
+                } else if sa > 0 {
+                    let da = dst[3] as u32 * (255 - sa);
+                    let out_a = sa * 255 + da;
+                    for c in 0..3 {
+                        dst[c] = ((src[c] as u32 * sa * 255 + dst[c] as u32 * da) / out_a) as u8;
+                    }
+                    dst[3] = (out_a / 255) as u8;
+                }
+            }
+        }
+    }
+
+    /// Clears a rectangle to transparent black.
+    fn clear(&mut self, left: usize, top: usize, width: usize, height: usize) {
+        for y in top..(top + height).min(self.height) {
+            for x in left..(left + width).min(self.width) {
+                self.rgba[(y * self.width + x) * 4..][..4].fill(0);
+            }
+        }
+    }
+
+    /// Draws a sub-frame, records the resulting canvas and applies the disposal.
+    fn push_frame(
+        &mut self,
+        frames: &mut Vec<AnimationFrame>,
+        rgba: &[u8],
+        (left, top, width, height): (usize, usize, usize, usize),
+        over: bool,
+        delay_ms: u32,
+        disposal: FrameDisposal,
+    ) {
+        let previous = (disposal == FrameDisposal::Previous).then(|| self.rgba.clone());
+        self.draw(rgba, left, top, width, over);
+
+        let rgb = self
+            .rgba
+            .chunks_exact(4)
+            .flat_map(|px| [px[0], px[1], px[2]])
+            .collect();
+        frames.push(AnimationFrame {
+            image: Matrix3::new(self.width, self.height, rgb),
+            delay_ms,
+            disposal,
+        });
+
+        match (disposal, previous) {
+            (FrameDisposal::Background, _) => self.clear(left, top, width, height),
+            (FrameDisposal::Previous, Some(previous)) => self.rgba = previous,
+            _ => {}
+        }
+    }
+}
+
+/// Checks that an animation is non-empty and that all frames share one size.
+fn animation_dimensions(frames: &[AnimationFrame]) -> Result<(usize, usize), ImageError> {
+    let first = frames.first().ok_or_else(|| {
+        ImageError::UnsupportedFormat("An animation needs at least one frame".to_string())
+    })?;
+    let dimensions = first.image.dimensions();
+    if frames.iter().any(|f| f.image.dimensions() != dimensions) {
+        return Err(ImageError::UnsupportedFormat(
+            "All animation frames must have the same dimensions".to_string(),
+        ));
+    }
+    Ok(dimensions)
+}
+
+/// Reads all frames of a GIF file, composited onto the full canvas.
+///
+/// Each returned frame is the complete image as displayed at that point of the
+/// animation, so sub-rectangle updates, transparency and disposal are already applied.
+/// Transparent areas are black.
+///
+/// # Arguments
+///
+/// * `path` - Path to the GIF file
+///
+/// # Returns
+///
+/// Returns a `Result` containing the frames in display order on success,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_gif_frames;
+///
+/// let frames = read_gif_frames("tracking.gif").expect("Failed to read GIF");
+/// let total_ms: u32 = frames.iter().map(|f| f.delay_ms).sum();
+/// ```
+pub fn read_gif_frames<P: AsRef<Path>>(path: P) -> Result<Vec<AnimationFrame>, ImageError> {
+    let file = File::open(path)?;
+    read_gif_frames_from(BufReader::new(file))
+}
+
+/// Decodes all frames of a GIF held in memory.
+///
+/// See [`read_gif_frames`] for details.
+pub fn decode_gif_frames(bytes: &[u8]) -> Result<Vec<AnimationFrame>, ImageError> {
+    read_gif_frames_from(bytes)
+}
+
+/// Reads all frames of a GIF from any reader.
+///
+/// See [`read_gif_frames`] for details.
+pub fn read_gif_frames_from<R: Read>(reader: R) -> Result<Vec<AnimationFrame>, ImageError> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options
+        .read_info(reader)
+        .map_err(|e| ImageError::GifDecode(format!("{}", e)))?;
+
+    let mut canvas = Canvas::new(decoder.width() as usize, decoder.height() as usize);
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder
+        .read_next_frame()
+        .map_err(|e| ImageError::GifDecode(format!("{}", e)))?
+    {
+        let disposal = match frame.dispose {
+            gif::DisposalMethod::Background => FrameDisposal::Background,
+            gif::DisposalMethod::Previous => FrameDisposal::Previous,
+            _ => FrameDisposal::Keep,
+        };
+        canvas.push_frame(
+            &mut frames,
+            &frame.buffer,
+            (
+                frame.left as usize,
+                frame.top as usize,
+                frame.width as usize,
+                frame.height as usize,
+            ),
+            true,
+            frame.delay as u32 * 10,
+            disposal,
+        );
+    }
+
+    Ok(frames)
+}
+
+/// Writes a sequence of frames as an animated GIF that loops forever.
+///
+/// Each frame is quantized to its own palette of at most 256 colors (see
+/// [`IndexedImage::quantize`]). GIF delays have a resolution of 10 ms.
+///
+/// # Arguments
+///
+/// * `frames` - The frames to write; all must have the same dimensions
+/// * `path` - Path where the GIF file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::{write_gif, AnimationFrame}};
+///
+/// let frames: Vec<AnimationFrame> = (0..10)
+///     .map(|_| AnimationFrame::new(Matrix3::zeros(320, 240), 100))
+///     .collect();
+/// write_gif(&frames, "tracking.gif").expect("Failed to write GIF");
+/// ```
+pub fn write_gif<P: AsRef<Path>>(frames: &[AnimationFrame], path: P) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_gif_to(frames, io::BufWriter::new(file))
+}
+
+/// Encodes a sequence of frames as an animated GIF into an in-memory buffer.
+///
+/// See [`write_gif`] for details.
+pub fn encode_gif(frames: &[AnimationFrame]) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+    write_gif_to(frames, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes a sequence of frames as an animated GIF to any writer.
+///
+/// See [`write_gif`] for details.
+pub fn write_gif_to<W: Write>(frames: &[AnimationFrame], writer: W) -> Result<(), ImageError> {
+    let (width, height) = animation_dimensions(frames)?;
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(ImageError::UnsupportedFormat(format!(
+            "GIF dimensions {}x{}",
+            width, height
+        )));
+    }
+
+    let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &[])
+        .map_err(|e| ImageError::GifEncode(format!("{}", e)))?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| ImageError::GifEncode(format!("{}", e)))?;
+
+    for frame in frames {
+        let indexed = IndexedImage::quantize(&frame.image, 256);
+        let gif_frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: frame.delay_ms.div_ceil(10).min(u16::MAX as u32) as u16,
+            dispose: match frame.disposal {
+                FrameDisposal::Keep => gif::DisposalMethod::Keep,
+                FrameDisposal::Background => gif::DisposalMethod::Background,
+                FrameDisposal::Previous => gif::DisposalMethod::Previous,
+            },
+            palette: Some(
+                indexed
+                    .palette
+                    .iter()
+                    .flat_map(|&(r, g, b)| [r, g, b])
+                    .collect(),
+            ),
+            buffer: indexed.indices.into_raw().into(),
+            ..gif::Frame::default()
+        };
+        encoder
+            .write_frame(&gif_frame)
+            .map_err(|e| ImageError::GifEncode(format!("{}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// Reads all frames of an animated PNG (APNG) file, composited onto the full canvas.
+///
+/// Frame blending and disposal are applied, so each returned frame is the complete
+/// image as displayed at that point of the animation. Transparent areas are black.
+/// A regular PNG yields a single frame with a delay of 0.
+///
+/// # Arguments
+///
+/// * `path` - Path to the PNG file
+///
+/// # Returns
+///
+/// Returns a `Result` containing the frames in display order on success,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_apng_frames;
+///
+/// let frames = read_apng_frames("tracking.png").expect("Failed to read APNG");
+/// ```
+pub fn read_apng_frames<P: AsRef<Path>>(path: P) -> Result<Vec<AnimationFrame>, ImageError> {
+    let file = File::open(path)?;
+    read_apng_frames_from(BufReader::new(file))
+}
+
+/// Decodes all frames of an APNG held in memory.
+///
+/// See [`read_apng_frames`] for details.
+pub fn decode_apng_frames(bytes: &[u8]) -> Result<Vec<AnimationFrame>, ImageError> {
+    read_apng_frames_from(bytes)
+}
+
+/// Reads all frames of an APNG from any reader.
+///
+/// See [`read_apng_frames`] for details.
+pub fn read_apng_frames_from<R: Read>(reader: R) -> Result<Vec<AnimationFrame>, ImageError> {
+    let mut decoder = PngDecoder::new(reader);
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+
+    let info = reader.info();
+    let (width, height) = (info.width as usize, info.height as usize);
+    // Without an fcTL before it, the default image is not part of the animation
+    let skip_default = info.animation_control.is_some() && info.frame_control.is_none();
+    let count = match info.animation_control {
+        Some(animation) => animation.num_frames as usize + skip_default as usize,
+        None => 1,
+    };
+
+    let mut canvas = Canvas::new(width, height);
+    let mut frames = Vec::with_capacity(count);
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    for index in 0..count {
+        let output = reader
+            .next_frame(&mut buf)
+            .map_err(|e| ImageError::PngDecode(format!("{}", e)))?;
+        if index == 0 && skip_default {
+            continue;
+        }
+
+        let control = reader.info().frame_control.unwrap_or(png::FrameControl {
+            width: width as u32,
+            height: height as u32,
+            ..Default::default()
+        });
+        let rgba = png_pixels_to_rgba(output.color_type, &buf[..output.buffer_size()])?;
+        let delay_ms = match control.delay_den {
+            0 => control.delay_num as u32 * 10,
+            den => control.delay_num as u32 * 1000 / den as u32,
+        };
+        let disposal = match control.dispose_op {
+            // Restoring before the first frame means clearing to the background
+            png::DisposeOp::Previous if frames.is_empty() => FrameDisposal::Background,
+            png::DisposeOp::Previous => FrameDisposal::Previous,
+            png::DisposeOp::Background => FrameDisposal::Background,
+            png::DisposeOp::None => FrameDisposal::Keep,
+        };
+
+        canvas.push_frame(
+            &mut frames,
+            &rgba,
+            (
+                control.x_offset as usize,
+                control.y_offset as usize,
+                control.width as usize,
+                control.height as usize,
+            ),
+            control.blend_op == png::BlendOp::Over,
+            delay_ms,
+            disposal,
+        );
+    }
+
+    Ok(frames)
+}
+
+/// Converts expanded 8-bit PNG pixels to RGBA.
+fn png_pixels_to_rgba(color_type: ColorType, buf: &[u8]) -> Result<Vec<u8>, ImageError> {
+    let rgba = match color_type {
+        ColorType::Rgba => buf.to_vec(),
+        ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        ColorType::Grayscale => buf.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        ColorType::Indexed => {
+            return Err(ImageError::UnsupportedFormat(
+                "Unexpanded indexed PNG".to_string(),
+            ))
+        }
+    };
+    Ok(rgba)
+}
+
+/// Writes a sequence of frames as an animated PNG (APNG) that loops forever.
+///
+/// Unlike GIF, APNG is lossless and keeps full 24-bit color. Viewers without APNG
+/// support show the first frame.
+///
+/// # Arguments
+///
+/// * `frames` - The frames to write; all must have the same dimensions
+/// * `path` - Path where the PNG file should be written
+///
+/// # Returns
+///
+/// Returns `Ok(())` on success, or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::{write_apng, AnimationFrame}};
+///
+/// let frames: Vec<AnimationFrame> = (0..10)
+///     .map(|_| AnimationFrame::new(Matrix3::zeros(320, 240), 40))
+///     .collect();
+/// write_apng(&frames, "tracking.png").expect("Failed to write APNG");
+/// ```
+pub fn write_apng<P: AsRef<Path>>(frames: &[AnimationFrame], path: P) -> Result<(), ImageError> {
+    let file = File::create(path)?;
+    write_apng_to(frames, io::BufWriter::new(file))
+}
+
+/// Encodes a sequence of frames as an APNG into an in-memory buffer.
+///
+/// See [`write_apng`] for details.
+pub fn encode_apng(frames: &[AnimationFrame]) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+    write_apng_to(frames, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Writes a sequence of frames as an APNG to any writer.
+///
+/// See [`write_apng`] for details.
+pub fn write_apng_to<W: Write>(frames: &[AnimationFrame], writer: W) -> Result<(), ImageError> {
+    use png::Encoder;
+
+    let (width, height) = animation_dimensions(frames)?;
+    let encode_error = |e: png::EncodingError| ImageError::PngEncode(format!("{}", e));
+
+    let mut encoder = Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(encode_error)?;
+
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+    for frame in frames {
+        writer
+            .set_frame_delay(frame.delay_ms.min(u16::MAX as u32) as u16, 1000)
+            .map_err(encode_error)?;
+        writer
+            .set_dispose_op(match frame.disposal {
+                FrameDisposal::Keep => png::DisposeOp::None,
+                FrameDisposal::Background => png::DisposeOp::Background,
+                FrameDisposal::Previous => png::DisposeOp::Previous,
+            })
+            .map_err(encode_error)?;
+        writer
+            .write_image_data(frame.image.data())
+            .map_err(encode_error)?;
+    }
+    writer.finish().map_err(encode_error)?;
+
+    Ok(())
+}
+
+/// Largest frame width or height accepted by [`Y4mReader`]. This is far beyond any
+/// real video resolution and keeps forged headers from requesting huge buffers.
+const MAX_Y4M_DIMENSION: usize = 16384;
+
+/// Reads frames from a YUV4MPEG2 (Y4M) video stream.
+///
+/// Frames are converted to RGB using BT.601 coefficients, in limited range unless
+/// the stream header declares `XCOLORRANGE=FULL`. 4:2:0, 4:2:2, 4:4:4 and monochrome
+/// streams with 8-bit samples are supported. The reader is an iterator over the frames.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::Y4mReader;
+///
+/// let reader = Y4mReader::open("clip.y4m").expect("Failed to open Y4M");
+/// println!("{}x{} at {:?} fps", reader.width(), reader.height(), reader.frame_rate());
+/// for frame in reader {
+///     let frame = frame.expect("Failed to read frame");
+///     println!("Frame of {}x{}", frame.width(), frame.height());
+/// }
+/// ```
+pub struct Y4mReader<R: Read> {
+    reader: R,
+    width: usize,
+    height: usize,
+    frame_rate: (u32, u32),
+    /// `None` for monochrome streams
+    subsampling: Option<ChromaSubsampling>,
+    range: YuvRange,
+    /// Size of one frame's YUV data in bytes
+    frame_len: usize,
+    buffer: Vec<u8>,
+    frames_read: u64,
+}
+
+impl Y4mReader<BufReader<File>> {
+    /// Opens a Y4M file and reads its stream header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the Y4M file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the reader on success, or an `ImageError` if the
+    /// file cannot be opened or the header is invalid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> Y4mReader<R> {
+    /// Creates a reader over any Y4M stream and reads its stream header.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Source of the stream. Wrap unbuffered sources in a `BufReader`
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the reader on success, or an `ImageError` if the
+    /// header is invalid.
+    pub fn new(mut reader: R) -> Result<Self, ImageError> {
+        let header = read_y4m_line(&mut reader)?
+            .ok_or_else(|| ImageError::Y4mDecode("Empty stream".to_string()))?;
+        let mut params = header.split(' ');
+        if params.next() != Some("YUV4MPEG2") {
+            return Err(ImageError::Y4mDecode(
+                "Missing YUV4MPEG2 signature".to_string(),
+            ));
+        }
+
+        let invalid = |param: &str| ImageError::Y4mDecode(format!("Invalid parameter {}", param));
+        let mut width = None;
+        let mut height = None;
+        let mut frame_rate = (25, 1);
+        let mut subsampling = Some(ChromaSubsampling::Yuv420);
+        let mut range = YuvRange::Limited;
+
+        for param in params.filter(|p| !p.is_empty()) {
+            // Every parameter is a single-letter tag followed by its value
+            let (tag, value) = param.split_at(param.chars().next().map_or(0, char::len_utf8));
+            match tag {
+                "W" => width = Some(value.parse().map_err(|_| invalid(param))?),
+                "H" => height = Some(value.parse().map_err(|_| invalid(param))?),
+                "F" => {
+                    let (num, den) = value.split_once(':').ok_or_else(|| invalid(param))?;
+                    frame_rate = (
+                        num.parse().map_err(|_| invalid(param))?,
+                        den.parse().map_err(|_| invalid(param))?,
+                    );
+                }
+                "C" => {
+                    subsampling = match value {
+                        "420" | "420jpeg" | "420paldv" | "420mpeg2" => {
+                            Some(ChromaSubsampling::Yuv420)
+                        }
+                        "422" => Some(ChromaSubsampling::Yuv422),
+                        "444" => Some(ChromaSubsampling::Yuv444),
+                        "mono" => None,
+                        _ => {
+                            return Err(ImageError::UnsupportedFormat(format!(
+                                "Y4M colorspace {}",
+                                value
+                            )))
+                        }
+                    }
+                }
+                "X" if value == "COLORRANGE=FULL" => range = YuvRange::Full,
+                // Interlacing, aspect ratio and other extensions do not affect decoding
+                _ => {}
+            }
+        }
+
+        let (width, height) = match (width, height) {
+            (Some(w), Some(h)) => (w, h),
+            _ => {
+                return Err(ImageError::Y4mDecode(
+                    "Missing frame dimensions".to_string(),
+                ))
+            }
+        };
+        if !(1..=MAX_Y4M_DIMENSION).contains(&width) || !(1..=MAX_Y4M_DIMENSION).contains(&height) {
+            return Err(ImageError::Y4mDecode(format!(
+                "Invalid frame dimensions {}x{}",
+                width, height
+            )));
+        }
+        // Even 4:4:4 frames take at most three bytes per pixel, which bounds the
+        // plane sizes computed below
+        width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(|| ImageError::Y4mDecode("Frame dimensions too large".to_string()))?;
+        let frame_len = match subsampling {
+            Some(subsampling) => planar_yuv_format(subsampling).buffer_len(width, height),
+            None => width * height,
+        };
+
+        Ok(Self {
+            reader,
+            width,
+            height,
+            frame_rate,
+            subsampling,
+            range,
+            frame_len,
+            buffer: Vec::new(),
+            frames_read: 0,
+        })
+    }
+
+    /// Returns the frame width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the frame height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the frame rate as a (numerator, denominator) fraction.
+    pub fn frame_rate(&self) -> (u32, u32) {
+        self.frame_rate
+    }
+
+    /// Returns the chroma subsampling of the stream, or `None` for monochrome streams.
+    pub fn subsampling(&self) -> Option<ChromaSubsampling> {
+        self.subsampling
+    }
+
+    /// Returns the number of frames read so far.
+    pub fn frames_read(&self) -> u64 {
+        self.frames_read
+    }
+
+    /// Reads the next frame.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(None)` at the end of the stream, the RGB frame on success, or an
+    /// `ImageError` if the frame is truncated or malformed.
+    pub fn read_frame(&mut self) -> Result<Option<Matrix3>, ImageError> {
+        let header = match read_y4m_line(&mut self.reader)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        if header != "FRAME" && !header.starts_with("FRAME ") {
+            return Err(ImageError::Y4mDecode("Missing FRAME marker".to_string()));
+        }
+
+        // Reading through `take` only grows the buffer as data actually arrives, so a
+        // truncated stream fails without committing a whole frame of memory
+        self.buffer.clear();
+        (&mut self.reader)
+            .take(self.frame_len as u64)
+            .read_to_end(&mut self.buffer)?;
+        if self.buffer.len() < self.frame_len {
+            return Err(ImageError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Truncated Y4M frame",
+            )));
+        }
+
+        let format = self.subsampling.map(planar_yuv_format);
+        let frame = match format {
+            Some(format) => Matrix3::from_yuv(
+                &self.buffer,
+                self.width,
+                self.height,
+                format,
+                YuvMatrix::Bt601,
+                self.range,
+            )
+            .map_err(|e| ImageError::Y4mDecode(e.to_string()))?,
+            None => {
+                // Monochrome frames hold only the luma plane
+                let data = self
+                    .buffer
+                    .iter()
+                    .flat_map(|&y| {
+                        let (r, g, b) = yuv_to_rgb(y, 128, 128, YuvMatrix::Bt601, self.range);
+                        [r, g, b]
+                    })
+                    .collect();
+                Matrix3::new(self.width, self.height, data)
+            }
+        };
+
+        self.frames_read += 1;
+        Ok(Some(frame))
+    }
+}
+
+impl<R: Read> Iterator for Y4mReader<R> {
+    type Item = Result<Matrix3, ImageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Writes frames to a YUV4MPEG2 (Y4M) video stream.
+///
+/// Frames are converted from RGB using BT.601 coefficients in limited range. The
+/// stream header is written on creation and each call to
+/// [`write_frame`](Y4mWriter::write_frame) appends one frame.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::{Matrix3, io::{ChromaSubsampling, Y4mWriter}};
+///
+/// let mut writer = Y4mWriter::create("out.y4m", 640, 480, (30, 1), ChromaSubsampling::Yuv420)
+///     .expect("Failed to create Y4M");
+/// for _ in 0..10 {
+///     writer.write_frame(&Matrix3::zeros(640, 480)).expect("Failed to write frame");
+/// }
+/// writer.finish().expect("Failed to finish Y4M");
+/// ```
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+    format: YuvFormat,
+}
+
+impl Y4mWriter<io::BufWriter<File>> {
+    /// Creates a Y4M file and writes its stream header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path where the Y4M file should be written
+    /// * `width` - Frame width in pixels
+    /// * `height` - Frame height in pixels
+    /// * `frame_rate` - Frame rate as a (numerator, denominator) fraction
+    /// * `subsampling` - Chroma subsampling of the stored frames
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the writer on success, or an `ImageError` on failure.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        width: usize,
+        height: usize,
+        frame_rate: (u32, u32),
+        subsampling: ChromaSubsampling,
+    ) -> Result<Self, ImageError> {
+        let file = File::create(path)?;
+        Self::new(
+            io::BufWriter::new(file),
+            width,
+            height,
+            frame_rate,
+            subsampling,
+        )
+    }
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Creates a writer over any output and writes the stream header.
+    ///
+    /// See [`Y4mWriter::create`] for the arguments.
+    pub fn new(
+        mut writer: W,
+        width: usize,
+        height: usize,
+        frame_rate: (u32, u32),
+        subsampling: ChromaSubsampling,
+    ) -> Result<Self, ImageError> {
+        if width == 0 || height == 0 || frame_rate.0 == 0 || frame_rate.1 == 0 {
+            return Err(ImageError::Y4mEncode(
+                "Dimensions and frame rate must be non-zero".to_string(),
+            ));
+        }
+
+        let colorspace = match subsampling {
+            ChromaSubsampling::Yuv420 => "420jpeg",
+            ChromaSubsampling::Yuv422 => "422",
+            ChromaSubsampling::Yuv444 => "444",
+        };
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C{}",
+            width, height, frame_rate.0, frame_rate.1, colorspace
+        )?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            format: planar_yuv_format(subsampling),
+        })
+    }
+
+    /// Appends a frame to the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to write, which must match the stream dimensions
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an `ImageError` on failure.
+    pub fn write_frame(&mut self, frame: &Matrix3) -> Result<(), ImageError> {
+        if frame.dimensions() != (self.width, self.height) {
+            return Err(ImageError::Y4mEncode(format!(
+                "Frame is {}x{} but the stream is {}x{}",
+                frame.width(),
+                frame.height(),
+                self.width,
+                self.height
+            )));
+        }
+
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer
+            .write_all(&frame.to_yuv(self.format, YuvMatrix::Bt601, YuvRange::Limited))?;
+        Ok(())
+    }
+
+    /// Flushes the stream and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, ImageError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Planar YUV layout used by Y4M frames with the given subsampling.
+fn planar_yuv_format(subsampling: ChromaSubsampling) -> YuvFormat {
+    match subsampling {
+        ChromaSubsampling::Yuv420 => YuvFormat::I420,
+        ChromaSubsampling::Yuv422 => YuvFormat::I422,
+        ChromaSubsampling::Yuv444 => YuvFormat::I444,
+    }
+}
+
+/// Reads a newline-terminated Y4M header line.
+///
+/// Returns `None` at the end of the stream.
+fn read_y4m_line<R: Read>(reader: &mut R) -> Result<Option<String>, ImageError> {
+    const MAX_LINE: usize = 4096;
+
+    let mut line = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        match reader.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && line.is_empty() => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+        if line.len() > MAX_LINE {
+            return Err(ImageError::Y4mDecode("Header line too long".to_string()));
+        }
+    }
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| ImageError::Y4mDecode("Header is not valid text".to_string()))
+}
+
+/// Reads an image file of any supported format as a three-channel RGB matrix.
+///
+/// The format is detected from the file contents (magic bytes), not the extension,
+/// so mislabeled files are still decoded correctly. TGA files, which have no
+/// signature, are recognized by their `.tga` extension instead.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure. Unrecognized contents produce
+/// `ImageError::UnsupportedFormat`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::read_image;
+///
+/// let image = read_image("photo.jpg").expect("Failed to read image");
+/// println!("Image dimensions: {}x{}", image.width(), image.height());
+/// ```
+pub fn read_image<P: AsRef<Path>>(path: P) -> Result<Matrix3, ImageError> {
+    read_image_with_options(path, &ReadOptions::default())
+}
+
+/// Decodes an in-memory image of any supported format as a three-channel RGB matrix.
+///
+/// The format is detected from the magic bytes at the start of `bytes`. TGA files
+/// have no signature and cannot be detected this way; use [`read_image`] with a
+/// `.tga` path or [`tga::decode`] for those.
+///
+/// # Arguments
+///
+/// * `bytes` - The encoded image data
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+pub fn decode_image(bytes: &[u8]) -> Result<Matrix3, ImageError> {
+    match ImageFormat::from_magic(bytes) {
+        Some(format) => decode_format(bytes, format),
+        None => Err(ImageError::UnsupportedFormat(
+            "Unrecognized image signature".to_string(),
+        )),
+    }
+}
+
+/// Decodes an in-memory image of a known format as a three-channel RGB matrix.
+fn decode_format(bytes: &[u8], format: ImageFormat) -> Result<Matrix3, ImageError> {
+    match format {
+        ImageFormat::Jpeg => decode_jpeg(bytes),
+        ImageFormat::Png => decode_png(bytes),
+        ImageFormat::Netpbm => Ok(netpbm::decode(bytes)?.into_rgb8()),
+        ImageFormat::Bmp => Ok(bmp::decode(bytes)?.into_rgb8()),
+        ImageFormat::Tiff => Ok(decode_tiff(bytes)?.into_rgb8()),
+        ImageFormat::Qoi => Ok(qoi::decode(bytes)?),
+        ImageFormat::Tga => Ok(tga::decode(bytes)?.into_rgb8()),
+        ImageFormat::Gif => decode_gif_frames(bytes)?
+            .into_iter()
+            .next()
+            .map(|frame| frame.image)
+            .ok_or_else(|| ImageError::GifDecode("GIF has no frames".to_string())),
+    }
+}
+
+/// Reads an image file of any supported format with explicit read options.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+/// * `options` - Decoder settings
+///
+/// # Returns
+///
+/// Returns a `Result` containing a `Matrix3` with RGB data on success,
+/// or an `ImageError` on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cv_rusty::io::{read_image_with_options, ReadOptions};
+///
+/// let options = ReadOptions::default().with_apply_orientation(true);
+/// let image = read_image_with_options("phone.jpg", &options).expect("Failed to read image");
+/// ```
+pub fn read_image_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ReadOptions,
+) -> Result<Matrix3, ImageError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    // TGA has no signature, so it can only be recognized by its extension
+    if ImageFormat::from_magic(&bytes).is_none()
+        && ImageFormat::from_path(path) == Some(ImageFormat::Tga)
+    {
+        return decode_format(&bytes, ImageFormat::Tga);
+    }
+    decode_image_with_options(&bytes, options)
+}
+
+/// Decodes an in-memory image of any supported format with explicit read options.
+///
+/// See [`read_image_with_options`] for details.
+pub fn decode_image_with_options(
+    bytes: &[u8],
+    options: &ReadOptions,
+) -> Result<Matrix3, ImageError> {
+    let image = decode_image(bytes)?;
+    if !options.apply_orientation || ImageFormat::from_magic(bytes) != Some(ImageFormat::Jpeg) {
+        return Ok(image);
+    }
+
+    // A damaged EXIF block should not prevent reading the pixels
+    match decode_jpeg_metadata(bytes).ok().and_then(|m| m.orientation) {
+        Some(orientation) => Ok(image.apply_orientation(orientation)),
+        None => Ok(image),
+    }
 }
 
-/// Writes a Matrix3 as a JPEG image file.
+/// Writes an image to a file, choosing the encoder from the file extension.
+///
+/// Uses [`WriteOptions::default`] for format-specific settings.
 ///
 /// # Arguments
 ///
-/// * `matrix` - The Matrix3 containing RGB data to write
-/// * `path` - Path where the JPEG file should be written
-/// * `quality` - JPEG quality (1-100, where 100 is best quality)
+/// * `path` - Path where the image should be written (e.g. `out.png`, `out.jpg`)
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an `ImageError` on failure.
+/// Returns `Ok(())` on success, or an `ImageError` on failure. Unknown extensions
+/// produce `ImageError::UnsupportedFormat`.
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use cv_rusty::{Matrix3, io::write_jpeg};
+/// use cv_rusty::{Matrix3, io::write_image};
 ///
 /// let image = Matrix3::zeros(640, 480);
-/// write_jpeg(&image, "output.jpg", 90).expect("Failed to write JPEG");
+/// write_image("output.png", &image).expect("Failed to write image");
 /// ```
-pub fn write_jpeg<P: AsRef<Path>>(
-    matrix: &Matrix3,
+pub fn write_image<I: WritableImage, P: AsRef<Path>>(
     path: P,
-    quality: u8,
+    matrix: &I,
 ) -> Result<(), ImageError> {
-    use jpeg_encoder::{ColorType as JpegColorType, Encoder};
-
-    let quality = quality.clamp(1, 100);
-
-    // Create the output file
-    let file = File::create(path)?;
-    let mut writer = io::BufWriter::new(file);
-
-    // Create encoder
-    let encoder = Encoder::new(&mut writer, quality);
-
-    // Encode the image
-    encoder
-        .encode(
-            matrix.data(),
-            matrix.width() as u16,
-            matrix.height() as u16,
-            JpegColorType::Rgb,
-        )
-        .map_err(|e| ImageError::JpegEncode(format!("{}", e)))?;
-
-    Ok(())
+    write_image_with_options(path, matrix, &WriteOptions::default())
 }
 
-/// Writes a Matrix3 as a PNG image file.
+/// Writes an image to a file with explicit per-format options.
 ///
 /// # Arguments
 ///
-/// * `matrix` - The Matrix3 containing RGB data to write
-/// * `path` - Path where the PNG file should be written
+/// * `path` - Path where the image should be written
+/// * `matrix` - The image to write (`Matrix1` or `Matrix3`)
+/// * `options` - Format-specific encoder settings
 ///
 /// # Returns
 ///
@@ -290,39 +3700,56 @@ pub fn write_jpeg<P: AsRef<Path>>(
 /// # Examples
 ///
 /// ```no_run
-/// use cv_rusty::{Matrix3, io::write_png};
+/// use cv_rusty::{Matrix3, io::{write_image_with_options, WriteOptions}};
 ///
 /// let image = Matrix3::zeros(640, 480);
-/// write_png(&image, "output.png").expect("Failed to write PNG");
+/// let options = WriteOptions::default().with_jpeg_quality(75);
+/// write_image_with_options("output.jpg", &image, &options).expect("Failed to write image");
 /// ```
-pub fn write_png<P: AsRef<Path>>(matrix: &Matrix3, path: P) -> Result<(), ImageError> {
-    use png::{BitDepth, Encoder};
-
-    // Create the output file
-    let file = File::create(path)?;
-    let writer = io::BufWriter::new(file);
-
-    // Create encoder
-    let mut encoder = Encoder::new(writer, matrix.width() as u32, matrix.height() as u32);
-    encoder.set_color(ColorType::Rgb);
-    encoder.set_depth(BitDepth::Eight);
-
-    // Write the PNG header
-    let mut writer = encoder
-        .write_header()
-        .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
-
-    // Write the image data
-    writer
-        .write_image_data(matrix.data())
-        .map_err(|e| ImageError::PngEncode(format!("{}", e)))?;
-
-    Ok(())
+pub fn write_image_with_options<I: WritableImage, P: AsRef<Path>>(
+    path: P,
+    matrix: &I,
+    options: &WriteOptions,
+) -> Result<(), ImageError> {
+    let path = path.as_ref();
+    match ImageFormat::from_path(path) {
+        Some(ImageFormat::Jpeg) => write_jpeg_with_options(matrix, path, &options.jpeg),
+        Some(ImageFormat::Png) => write_png(matrix, path),
+        Some(ImageFormat::Netpbm) => {
+            let is_ext = |ext: &str| {
+                path.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+            };
+            if is_ext("pam") {
+                write_pam(matrix, path)
+            } else if is_ext("pbm") {
+                Err(ImageError::UnsupportedFormat(
+                    "Use write_pbm to write bilevel PBM files".to_string(),
+                ))
+            } else {
+                write_pnm(matrix, path, PnmEncoding::Binary)
+            }
+        }
+        Some(ImageFormat::Bmp) => write_bmp(matrix, path),
+        Some(ImageFormat::Tiff) => write_tiff(matrix, path, options.tiff_compression),
+        Some(ImageFormat::Qoi) => write_qoi(matrix, path),
+        Some(ImageFormat::Tga) => write_tga(matrix, path, TgaCompression::Rle),
+        Some(ImageFormat::Gif) => {
+            let frame = AnimationFrame::new(writable_to_rgb8(matrix)?, 0);
+            write_gif(std::slice::from_ref(&frame), path)
+        }
+        None => Err(ImageError::UnsupportedFormat(format!(
+            "Cannot determine image format from path: {}",
+            path.display()
+        ))),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transform::Orientation;
     use std::fs;
 
     #[test]
@@ -425,4 +3852,784 @@ mod tests {
         // Clean up
         fs::remove_file(temp_path).ok();
     }
+
+    #[test]
+    fn test_encode_decode_png_in_memory() {
+        let mut original = Matrix3::zeros(16, 8);
+        original.set_pixel(3, 4, 10, 20, 30);
+
+        let bytes = encode_png(&original).expect("Failed to encode PNG");
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoded = decode_png(&bytes).expect("Failed to decode PNG");
+        assert_eq!(decoded.data(), original.data());
+    }
+
+    #[test]
+    fn test_encode_decode_jpeg_in_memory() {
+        let original = Matrix3::new(32, 16, vec![90u8; 32 * 16 * 3]);
+
+        let bytes = encode_jpeg(&original, 95).expect("Failed to encode JPEG");
+        let decoded = decode_jpeg(&bytes).expect("Failed to decode JPEG");
+
+        assert_eq!(decoded.dimensions(), (32, 16));
+        assert!(decoded.data().iter().all(|&v| (v as i16 - 90).abs() <= 2));
+    }
+
+    /// Returns the payload of the first segment with the given marker.
+    fn jpeg_segment(bytes: &[u8], marker: u8) -> Option<&[u8]> {
+        let mut pos = 2;
+        while pos + 4 <= bytes.len() && bytes[pos] == 0xFF && bytes[pos + 1] != 0xDA {
+            let length = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+            if bytes[pos + 1] == marker {
+                return Some(&bytes[pos + 4..pos + 2 + length]);
+            }
+            pos += 2 + length;
+        }
+        None
+    }
+
+    #[test]
+    fn test_jpeg_options() {
+        let original = Matrix3::new(32, 16, vec![90u8; 32 * 16 * 3]);
+        // Luma sampling factors are stored in the first component of the SOF segment
+        let luma_sampling = |options: &JpegOptions| {
+            let bytes = encode_jpeg_with_options(&original, options).unwrap();
+            let sof = jpeg_segment(&bytes, 0xC0)
+                .or_else(|| jpeg_segment(&bytes, 0xC2))
+                .unwrap();
+            sof[7]
+        };
+
+        let options = JpegOptions::default().with_quality(50);
+        assert_eq!(luma_sampling(&options), 0x22);
+        let options = options.with_subsampling(ChromaSubsampling::Yuv444);
+        assert_eq!(luma_sampling(&options), 0x11);
+        let options = options.with_subsampling(ChromaSubsampling::Yuv422);
+        assert_eq!(luma_sampling(&options), 0x21);
+
+        let options = JpegOptions::default()
+            .with_progressive(true)
+            .with_optimized_huffman(true)
+            .with_restart_interval(4);
+        let bytes = encode_jpeg_with_options(&original, &options).unwrap();
+        assert!(jpeg_segment(&bytes, 0xC2).is_some());
+        assert_eq!(jpeg_segment(&bytes, 0xDD), Some(&[0u8, 4][..]));
+        let decoded = decode_jpeg(&bytes).unwrap();
+        assert!(decoded.data().iter().all(|&v| (v as i16 - 90).abs() <= 2));
+
+        let options = JpegOptions::default().with_grayscale(true);
+        let bytes = encode_jpeg_with_options(&original, &options).unwrap();
+        assert_eq!(jpeg_segment(&bytes, 0xC0).unwrap()[5], 1);
+        assert_eq!(decode_jpeg(&bytes).unwrap().dimensions(), (32, 16));
+    }
+
+    #[test]
+    fn test_jpeg_embedded_segments() {
+        let exif = b"MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x03\0\0\0\0\0\0".to_vec();
+        let options = JpegOptions::default()
+            .with_exif(exif)
+            .with_icc_profile(vec![1, 2, 3])
+            .with_comment("hello");
+        let bytes = encode_jpeg_with_options(&Matrix3::zeros(8, 8), &options).unwrap();
+
+        let metadata = decode_jpeg_metadata(&bytes).unwrap();
+        assert_eq!(metadata.orientation, Some(Orientation::Rotate180));
+        assert_eq!(
+            jpeg_segment(&bytes, 0xE2),
+            Some(&b"ICC_PROFILE\0\x01\x01\x01\x02\x03"[..])
+        );
+        assert_eq!(jpeg_segment(&bytes, 0xFE), Some(&b"hello"[..]));
+        assert_eq!(decode_jpeg(&bytes).unwrap().dimensions(), (8, 8));
+    }
+
+    #[test]
+    fn test_stream_variants() {
+        let original = Matrix3::new(4, 4, (0..48).collect());
+
+        let mut stream = Vec::new();
+        write_png_to(&original, &mut stream).expect("Failed to write PNG to stream");
+        let decoded = read_png_from(stream.as_slice()).expect("Failed to read PNG from stream");
+        assert_eq!(decoded.data(), original.data());
+
+        assert!(matches!(
+            decode_png(b"not a png"),
+            Err(ImageError::PngDecode(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(
+            ImageFormat::from_magic(b"\x89PNG\r\n\x1a\n\0\0"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(ImageFormat::from_extension("JPEG"), Some(ImageFormat::Jpeg));
+        assert_eq!(
+            ImageFormat::from_path("dir/image.Png"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(ImageFormat::from_path("no_extension"), None);
+    }
+
+    #[test]
+    fn test_read_image_sniffs_contents() {
+        let original = Matrix3::new(8, 8, vec![77u8; 8 * 8 * 3]);
+
+        // A PNG stored with a misleading extension is still decoded as PNG
+        let temp_path = "test_sniff.jpg";
+        fs::write(temp_path, encode_png(&original).unwrap()).unwrap();
+        let loaded = read_image(temp_path).expect("Failed to read image");
+        assert_eq!(loaded.data(), original.data());
+        fs::remove_file(temp_path).ok();
+
+        assert!(matches!(
+            decode_image(b"RIFF\0\0\0\0WEBP"),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_jpeg_exif_orientation() {
+        // Little-endian EXIF block with Model = "Cam" and Orientation = 6
+        let mut block = b"Exif\0\0II\x2a\0\x08\0\0\0\x02\0".to_vec();
+        block.extend_from_slice(&[0x10, 0x01, 2, 0, 4, 0, 0, 0, b'C', b'a', b'm', 0]);
+        block.extend_from_slice(&[0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0]);
+        block.extend_from_slice(&[0, 0, 0, 0]);
+
+        let image = Matrix3::zeros(16, 8);
+        let encoded = encode_jpeg(&image, 90).unwrap();
+        let mut bytes = encoded[..2].to_vec();
+        bytes.extend_from_slice(&[0xFF, 0xE1]);
+        bytes.extend_from_slice(&(block.len() as u16 + 2).to_be_bytes());
+        bytes.extend_from_slice(&block);
+        bytes.extend_from_slice(&encoded[2..]);
+
+        let metadata = decode_jpeg_metadata(&bytes).unwrap();
+        assert_eq!(metadata.orientation, Some(Orientation::Rotate90));
+        assert_eq!(metadata.model.as_deref(), Some("Cam"));
+        assert_eq!(metadata.date_time, None);
+        assert_eq!(
+            decode_jpeg_metadata(&encoded).unwrap(),
+            ExifMetadata::default()
+        );
+
+        let plain = decode_image(&bytes).unwrap();
+        assert_eq!((plain.width(), plain.height()), (16, 8));
+
+        let options = ReadOptions::default().with_apply_orientation(true);
+        let upright = decode_image_with_options(&bytes, &options).unwrap();
+        assert_eq!((upright.width(), upright.height()), (8, 16));
+    }
+
+    #[test]
+    fn test_write_image_by_extension() {
+        let image = Matrix3::zeros(12, 10);
+
+        let temp_path = "test_write_image.jpeg";
+        let options = WriteOptions::default().with_jpeg_quality(50);
+        write_image_with_options(temp_path, &image, &options).expect("Failed to write image");
+        let bytes = fs::read(temp_path).unwrap();
+        assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Jpeg));
+
+        // The full set of JPEG settings is reachable by extension
+        let jpeg = JpegOptions::default()
+            .with_grayscale(true)
+            .with_comment("by extension");
+        let options = WriteOptions::default().with_jpeg(jpeg.clone());
+        write_image_with_options(temp_path, &image, &options).expect("Failed to write image");
+        assert_eq!(
+            fs::read(temp_path).unwrap(),
+            encode_jpeg_with_options(&image, &jpeg).unwrap()
+        );
+        fs::remove_file(temp_path).ok();
+
+        assert!(matches!(
+            write_image("test_write_image.xyz", &image),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_grayscale_png_roundtrip() {
+        let original = Matrix1::new(6, 4, (0..24).map(|v| v * 10).collect());
+
+        let bytes = encode_png(&original).expect("Failed to encode PNG");
+        // IHDR color type byte (offset 25) is 0 for grayscale
+        assert_eq!(bytes[25], 0);
+
+        let decoded =
+            decode_png_gray(&bytes, GrayscaleMethod::Luminosity).expect("Failed to decode PNG");
+        assert_eq!(decoded.data(), original.data());
+
+        // Reading the same file as color expands it to three equal channels
+        let rgb = decode_png(&bytes).expect("Failed to decode PNG");
+        assert_eq!(rgb.get_pixel(5, 3), Some((230, 230, 230)));
+    }
+
+    #[test]
+    fn test_grayscale_jpeg_roundtrip() {
+        let original = Matrix1::new(16, 16, vec![140u8; 256]);
+
+        let bytes = encode_jpeg(&original, 95).expect("Failed to encode JPEG");
+        let decoded =
+            decode_jpeg_gray(&bytes, GrayscaleMethod::Luminosity).expect("Failed to decode JPEG");
+
+        assert_eq!(decoded.dimensions(), (16, 16));
+        assert!(decoded.data().iter().all(|&v| (v as i16 - 140).abs() <= 2));
+        // Single-channel JPEGs are much smaller than the RGB equivalent
+        let rgb = Matrix3::new(16, 16, vec![140u8; 256 * 3]);
+        assert!(bytes.len() < encode_jpeg(&rgb, 95).unwrap().len());
+    }
+
+    #[test]
+    fn test_read_gray_converts_color_sources() {
+        let mut color = Matrix3::zeros(2, 1);
+        color.set_pixel(0, 0, 255, 0, 0);
+        color.set_pixel(1, 0, 30, 60, 90);
+
+        let bytes = encode_png(&color).unwrap();
+        let gray = decode_png_gray(&bytes, GrayscaleMethod::Average).unwrap();
+        assert_eq!(gray.data(), &[85, 60]);
+    }
+
+    #[test]
+    fn test_16_bit_png_roundtrip() {
+        let depth = Matrix1U16::new(3, 2, vec![0, 1, 256, 4095, 40000, 65535]);
+
+        let bytes = encode_png(&depth).expect("Failed to encode PNG");
+        // IHDR bit depth byte (offset 24) is 16
+        assert_eq!(bytes[24], 16);
+
+        let decoded = decode_png_gray_16(&bytes, GrayscaleMethod::Luminosity)
+            .expect("Failed to decode 16-bit PNG");
+        assert_eq!(decoded.data(), depth.data());
+
+        let rgb = Matrix3U16::new(1, 2, vec![1, 2, 3, 60000, 50000, 40000]);
+        let decoded = decode_png_16(&encode_png(&rgb).unwrap()).unwrap();
+        assert_eq!(decoded.data(), rgb.data());
+    }
+
+    #[test]
+    fn test_16_bit_png_read_as_8_bit() {
+        // Previously this panicked on the Matrix3 length assertion
+        let rgb = Matrix3U16::new(2, 1, vec![0, 32896, 65535, 257, 514, 771]);
+        let bytes = encode_png(&rgb).unwrap();
+
+        let decoded = decode_png(&bytes).expect("Failed to decode 16-bit PNG as 8-bit");
+        assert_eq!(decoded.data(), &[0, 128, 255, 1, 2, 3]);
+
+        // JPEG cannot hold 16-bit samples
+        assert!(matches!(
+            encode_jpeg(&rgb, 90),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_indexed_png_roundtrip() {
+        let labels = Matrix1::new(5, 2, vec![0, 1, 2, 1, 0, 2, 2, 1, 0, 0]);
+        let mut mask = IndexedImage::new(labels, vec![(0, 0, 0), (255, 0, 0), (0, 0, 255)]);
+        mask.alpha = vec![0];
+
+        let bytes = encode_png_indexed(&mask).expect("Failed to encode indexed PNG");
+        // IHDR: 2-bit depth, indexed color type
+        assert_eq!((bytes[24], bytes[25]), (2, 3));
+
+        let decoded = read_png_indexed_from(bytes.as_slice()).expect("Failed to read indices");
+        assert_eq!(decoded.indices.data(), mask.indices.data());
+        assert_eq!(decoded.palette, mask.palette);
+        assert_eq!(decoded.alpha, vec![0]);
+
+        // The regular reader expands the palette (tRNS alpha is dropped)
+        let rgb = decode_png(&bytes).expect("Failed to decode indexed PNG");
+        assert_eq!(rgb.get_pixel(1, 0), Some((255, 0, 0)));
+        assert_eq!(rgb.get_pixel(0, 1), Some((0, 0, 255)));
+        assert_eq!(rgb.get_pixel(0, 0), Some((0, 0, 0)));
+
+        let gray16 = decode_png_gray_16(&bytes, GrayscaleMethod::Average).unwrap();
+        assert_eq!(gray16.get_pixel(1, 0), Some(21845));
+    }
+
+    #[test]
+    fn test_indexed_quantize() {
+        let mut image = Matrix3::zeros(4, 4);
+        image.set_pixel(1, 1, 10, 200, 30);
+        image.set_pixel(2, 3, 250, 250, 250);
+
+        // Few colors: lossless palette
+        let indexed = IndexedImage::quantize(&image, 256);
+        assert_eq!(indexed.palette.len(), 3);
+        assert_eq!(indexed.to_rgb().data(), image.data());
+
+        // Too many colors: median-cut palette
+        let gradient = Matrix3::new(256, 1, (0..256 * 3).map(|v| (v / 3) as u8).collect());
+        let indexed = IndexedImage::quantize(&gradient, 8);
+        assert_eq!(indexed.palette.len(), 8);
+        let restored = indexed.to_rgb();
+        for (&restored, &original) in restored.data().iter().zip(gradient.data()) {
+            assert!(restored.abs_diff(original) <= 20);
+        }
+
+        let bad = IndexedImage::new(Matrix1::new(1, 1, vec![3]), vec![(0, 0, 0)]);
+        assert!(encode_png_indexed(&bad).is_err());
+    }
+
+    #[test]
+    fn test_netpbm_files() {
+        let depth = Matrix1U16::new(3, 1, vec![0, 4096, 65535]);
+        let path = "test_netpbm_depth.pgm";
+        write_pnm(&depth, path, PnmEncoding::Ascii).expect("Failed to write PGM");
+        let read = read_pnm(path).expect("Failed to read PGM");
+        fs::remove_file(path).ok();
+        match read {
+            DynamicImage::Gray16(m) => assert_eq!(m.data(), depth.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+
+        let image = Matrix3::new(2, 1, vec![10, 20, 30, 40, 50, 60]);
+        let path = "test_netpbm_image.pam";
+        write_image(path, &image).expect("Failed to write PAM");
+        let read = read_image(path).expect("Failed to read PAM");
+        fs::remove_file(path).ok();
+        assert_eq!(read.data(), image.data());
+
+        assert_eq!(ImageFormat::from_magic(b"P6\n"), Some(ImageFormat::Netpbm));
+        assert!(matches!(
+            write_image("test_netpbm.pbm", &image),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            decode_image(b"P5 2 2 255\n"),
+            Err(ImageError::Codec(CodecError::UnexpectedEof))
+        ));
+    }
+
+    #[test]
+    fn test_bmp_files() {
+        let gray = Matrix1::new(5, 3, (0..15).map(|v| v * 10).collect());
+        let path = "test_bmp_gray.bmp";
+        write_image(path, &gray).expect("Failed to write BMP");
+        let read = read_bmp(path).expect("Failed to read BMP");
+        let rgb = read_image(path).expect("Failed to read BMP as RGB");
+        fs::remove_file(path).ok();
+        match read {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), gray.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+        assert_eq!(rgb.get_pixel(4, 2), Some((140, 140, 140)));
+        assert_eq!(ImageFormat::from_path("frame.DIB"), Some(ImageFormat::Bmp));
+    }
+
+    #[test]
+    fn test_tiff_roundtrip() {
+        let gray = Matrix1::new(4, 3, (0..12).map(|v| v * 20).collect());
+        let rgb16 = Matrix3U16::new(2, 2, (0..12).map(|v| v * 5000).collect());
+
+        for compression in [
+            TiffCompression::None,
+            TiffCompression::Lzw,
+            TiffCompression::Deflate,
+        ] {
+            let bytes = encode_tiff(&gray, compression).expect("Failed to encode TIFF");
+            assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Tiff));
+            match decode_tiff(&bytes).expect("Failed to decode TIFF") {
+                DynamicImage::Gray8(m) => assert_eq!(m.data(), gray.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+
+            let bytes = encode_tiff(&rgb16, compression).expect("Failed to encode TIFF");
+            match decode_tiff(&bytes).expect("Failed to decode TIFF") {
+                DynamicImage::Rgb16(m) => assert_eq!(m.data(), rgb16.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tiff_pages() {
+        let pages: Vec<Matrix1> = (0..3)
+            .map(|i| Matrix1::new(2, 2, vec![i * 50; 4]))
+            .collect();
+        let path = "test_tiff_stack.tif";
+        write_tiff_pages(&pages, path, TiffCompression::Lzw).expect("Failed to write TIFF");
+        let read = read_tiff_pages(path).expect("Failed to read TIFF");
+        let first = read_image(path).expect("Failed to read first page");
+        fs::remove_file(path).ok();
+
+        assert_eq!(read.len(), 3);
+        for (page, expected) in read.into_iter().zip(&pages) {
+            match page {
+                DynamicImage::Gray8(m) => assert_eq!(m.data(), expected.data()),
+                other => panic!("Unexpected image: {:?}", other),
+            }
+        }
+        assert_eq!(first.get_pixel(0, 0), Some((0, 0, 0)));
+
+        let empty: [Matrix1; 0] = [];
+        assert!(encode_tiff_pages(&empty, TiffCompression::None).is_err());
+
+        // Mixed channel counts, bit depths and sizes through DynamicImage
+        let gray = Matrix1::new(2, 1, vec![10, 20]);
+        let rgb = Matrix3U16::new(1, 2, vec![1, 2, 3, 60000, 50000, 40000]);
+        let mixed = [
+            DynamicImage::Gray8(gray.clone()),
+            DynamicImage::Rgb16(rgb.clone()),
+        ];
+        let bytes = encode_tiff_pages(&mixed, TiffCompression::Deflate).unwrap();
+        let pages = decode_tiff_pages(&bytes).unwrap();
+        assert_eq!(pages.len(), 2);
+        match &pages[0] {
+            DynamicImage::Gray8(m) => assert_eq!(m.data(), gray.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+        match &pages[1] {
+            DynamicImage::Rgb16(m) => assert_eq!(m.data(), rgb.data()),
+            other => panic!("Unexpected image: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_qoi_and_tga_files() {
+        let image = Matrix3::new(3, 2, (0..18).map(|v| v * 14).collect());
+
+        let path = "test_qoi_image.qoi";
+        write_image(path, &image).expect("Failed to write QOI");
+        assert_eq!(read_qoi(path).unwrap().data(), image.data());
+        assert_eq!(read_image(path).unwrap().data(), image.data());
+        fs::remove_file(path).ok();
+
+        let path = "test_tga_image.tga";
+        write_image(path, &image).expect("Failed to write TGA");
+        assert_eq!(read_tga(path).unwrap().into_rgb8().data(), image.data());
+        // TGA is recognized by extension since it has no signature
+        assert_eq!(read_image(path).unwrap().data(), image.data());
+        fs::remove_file(path).ok();
+    }
+
+    fn animation() -> Vec<AnimationFrame> {
+        (0..3u8)
+            .map(|i| {
+                let mut image = Matrix3::zeros(4, 3);
+                image.set_pixel(i as usize, 1, 200, 50 * i, 10);
+                AnimationFrame::new(image, 100 + i as u32 * 20)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gif_animation_roundtrip() {
+        let frames = animation();
+        let bytes = encode_gif(&frames).expect("Failed to encode GIF");
+        assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Gif));
+
+        let decoded = decode_gif_frames(&bytes).expect("Failed to decode GIF");
+        assert_eq!(decoded.len(), 3);
+        for (frame, expected) in decoded.iter().zip(&frames) {
+            assert_eq!(frame.image.data(), expected.image.data());
+            assert_eq!(frame.delay_ms, expected.delay_ms);
+            assert_eq!(frame.disposal, FrameDisposal::Keep);
+        }
+
+        assert_eq!(decode_image(&bytes).unwrap().data(), frames[0].image.data());
+        let mismatched = [
+            frames[0].clone(),
+            AnimationFrame::new(Matrix3::zeros(1, 1), 0),
+        ];
+        assert!(encode_gif(&mismatched).is_err());
+        assert!(encode_gif(&[]).is_err());
+    }
+
+    #[test]
+    fn test_apng_animation_roundtrip() {
+        let mut frames = animation();
+        frames[1].disposal = FrameDisposal::Background;
+        let bytes = encode_apng(&frames).expect("Failed to encode APNG");
+
+        let decoded = decode_apng_frames(&bytes).expect("Failed to decode APNG");
+        assert_eq!(decoded.len(), 3);
+        for (frame, expected) in decoded.iter().zip(&frames) {
+            assert_eq!(frame.image.data(), expected.image.data());
+            assert_eq!(frame.delay_ms, expected.delay_ms);
+            assert_eq!(frame.disposal, expected.disposal);
+        }
+
+        // A plain PNG reads as a single frame, and the first frame is the default image
+        assert_eq!(decode_png(&bytes).unwrap().data(), frames[0].image.data());
+        let still = encode_png(&frames[2].image).unwrap();
+        assert_eq!(decode_apng_frames(&still).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_canvas_compositing() {
+        let mut canvas = Canvas::new(2, 1);
+        let mut frames = Vec::new();
+        // Opaque red on the left, then half-transparent white on the right that is
+        // blended over and restored afterwards
+        canvas.push_frame(
+            &mut frames,
+            &[255, 0, 0, 255],
+            (0, 0, 1, 1),
+            true,
+            0,
+            FrameDisposal::Keep,
+        );
+        canvas.push_frame(
+            &mut frames,
+            &[255, 255, 255, 128],
+            (1, 0, 1, 1),
+            true,
+            0,
+            FrameDisposal::Previous,
+        );
+        canvas.push_frame(
+            &mut frames,
+            &[0, 0, 0, 0],
+            (0, 0, 1, 1),
+            true,
+            0,
+            FrameDisposal::Background,
+        );
+        canvas.push_frame(
+            &mut frames,
+            &[0, 0, 0, 0],
+            (0, 0, 1, 1),
+            true,
+            0,
+            FrameDisposal::Keep,
+        );
+
+        assert_eq!(frames[0].image.data(), &[255, 0, 0, 0, 0, 0]);
+        assert_eq!(frames[1].image.data(), &[255, 0, 0, 255, 255, 255]);
+        assert_eq!(frames[2].image.data(), &[255, 0, 0, 0, 0, 0]);
+        assert_eq!(frames[3].image.data(), &[0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_y4m_roundtrip() {
+        let mut first = Matrix3::zeros(6, 4);
+        for y in 0..4 {
+            for x in 0..6 {
+                first.set_pixel(x, y, 200, 60, 30);
+            }
+        }
+        let second = Matrix3::new(6, 4, vec![128; 72]);
+
+        for subsampling in [
+            ChromaSubsampling::Yuv420,
+            ChromaSubsampling::Yuv422,
+            ChromaSubsampling::Yuv444,
+        ] {
+            let mut writer = Y4mWriter::new(Vec::new(), 6, 4, (30000, 1001), subsampling).unwrap();
+            writer.write_frame(&first).unwrap();
+            writer.write_frame(&second).unwrap();
+            assert!(matches!(
+                writer.write_frame(&Matrix3::zeros(4, 4)),
+                Err(ImageError::Y4mEncode(_))
+            ));
+            let bytes = writer.finish().unwrap();
+
+            let reader = Y4mReader::new(bytes.as_slice()).unwrap();
+            assert_eq!((reader.width(), reader.height()), (6, 4));
+            assert_eq!(reader.frame_rate(), (30000, 1001));
+            assert_eq!(reader.subsampling(), Some(subsampling));
+
+            let frames: Vec<Matrix3> = reader.collect::<Result<_, _>>().unwrap();
+            assert_eq!(frames.len(), 2);
+            for (frame, original) in frames.iter().zip([&first, &second]) {
+                for (a, b) in frame.data().iter().zip(original.data()) {
+                    assert!((*a as i16 - *b as i16).abs() <= 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_y4m_mono_and_full_range() {
+        let mut stream = b"YUV4MPEG2 W2 H1 F25:1 Cmono XCOLORRANGE=FULL\nFRAME\n".to_vec();
+        stream.extend_from_slice(&[0, 200]);
+        stream.extend_from_slice(b"FRAME Ixyz\n\x10\xeb");
+
+        let mut reader = Y4mReader::new(stream.as_slice()).unwrap();
+        assert_eq!(reader.subsampling(), None);
+        let frame = reader.read_frame().unwrap().unwrap();
+        assert_eq!(frame.data(), &[0, 0, 0, 200, 200, 200]);
+        let frame = reader.read_frame().unwrap().unwrap();
+        assert_eq!(frame.get_pixel(1, 0), Some((235, 235, 235)));
+        assert!(reader.read_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_y4m_invalid_streams() {
+        assert!(matches!(
+            Y4mReader::new(&b"YUV4MPEG W2 H2\n"[..]),
+            Err(ImageError::Y4mDecode(_))
+        ));
+        assert!(matches!(
+            Y4mReader::new(&b"YUV4MPEG2 W2\n"[..]),
+            Err(ImageError::Y4mDecode(_))
+        ));
+        assert!(matches!(
+            Y4mReader::new(&b"YUV4MPEG2 W2 H2 C420p10\n"[..]),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+
+        // Truncated frame data
+        let mut reader = Y4mReader::new(&b"YUV4MPEG2 W2 H2\nFRAME\n\0\0"[..]).unwrap();
+        assert!(matches!(reader.next(), Some(Err(ImageError::Io(_)))));
+
+        // Zero and absurd dimensions are rejected before any frame is read
+        for header in [
+            &b"YUV4MPEG2 W0 H2\n"[..],
+            b"YUV4MPEG2 W99999999 H99999999\nFRAME\n",
+            b"YUV4MPEG2 W16385 H1 Cmono\n",
+        ] {
+            assert!(matches!(
+                Y4mReader::new(header),
+                Err(ImageError::Y4mDecode(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_y4m_file() {
+        let temp_path = "test_clip.y4m";
+        let frame = Matrix3::new(4, 2, vec![90; 24]);
+        let mut writer =
+            Y4mWriter::create(temp_path, 4, 2, (25, 1), ChromaSubsampling::Yuv420).unwrap();
+        for _ in 0..3 {
+            writer.write_frame(&frame).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let frames: Vec<_> = Y4mReader::open(temp_path).unwrap().collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].as_ref().unwrap().dimensions(), (4, 2));
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_png_row_reader() {
+        let mut image = Matrix3::zeros(5, 3);
+        for y in 0..3 {
+            for x in 0..5 {
+                image.set_pixel(x, y, (x * 50) as u8, (y * 80) as u8, 7);
+            }
+        }
+        let bytes = encode_png(&image).unwrap();
+        let mut reader = PngRowReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(
+            (reader.width(), reader.height(), reader.channels()),
+            (5, 3, 3)
+        );
+        let mut streamed = Vec::new();
+        while let Some(row) = reader.next_row().unwrap() {
+            assert_eq!(row.len(), 15);
+            streamed.extend_from_slice(row);
+        }
+        assert_eq!(streamed, image.data());
+
+        // RGBA rows lose their alpha
+        let mut rgba = Vec::new();
+        let mut encoder = png::Encoder::new(&mut rgba, 2, 1);
+        encoder.set_color(ColorType::Rgba);
+        encoder.set_depth(BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        writer.finish().unwrap();
+        let mut reader = PngRowReader::new(rgba.as_slice()).unwrap();
+        assert_eq!(reader.next_row().unwrap(), Some(&[1, 2, 3, 5, 6, 7][..]));
+        assert_eq!(reader.next_row().unwrap(), None);
+
+        // Grayscale stays single-channel
+        let gray = encode_png(&Matrix1::new(3, 1, vec![10, 20, 30])).unwrap();
+        let mut reader = PngRowReader::new(gray.as_slice()).unwrap();
+        assert_eq!(reader.channels(), 1);
+        assert_eq!(reader.next_row().unwrap(), Some(&[10, 20, 30][..]));
+    }
+
+    #[test]
+    fn test_png_row_writer() {
+        let temp_path = "test_rows.png";
+        let mut writer = PngRowWriter::create(temp_path, 2, 2, 3).unwrap();
+        writer.write_row(&[1, 2, 3, 4, 5, 6]).unwrap();
+        assert!(matches!(
+            writer.write_row(&[1, 2, 3]),
+            Err(ImageError::PngEncode(_))
+        ));
+        writer.write_row(&[7, 8, 9, 10, 11, 12]).unwrap();
+        assert!(writer.write_row(&[0; 6]).is_err());
+        writer.finish().unwrap();
+
+        let image = read_png(temp_path).unwrap();
+        assert_eq!(image.data(), (1..=12).collect::<Vec<u8>>().as_slice());
+        fs::remove_file(temp_path).ok();
+
+        let writer = PngRowWriter::new(Vec::new(), 2, 2, 1).unwrap();
+        assert!(matches!(writer.finish(), Err(ImageError::PngEncode(_))));
+        assert!(PngRowWriter::new(Vec::new(), 2, 2, 4).is_err());
+    }
+
+    #[test]
+    fn test_pnm_rows() {
+        let mut writer = PnmRowWriter::new(Vec::new(), 2, 2, 3).unwrap();
+        writer.write_row(&[1, 2, 3, 4, 5, 6]).unwrap();
+        writer.write_row(&[7, 8, 9, 10, 11, 12]).unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(
+            netpbm::decode(&bytes).unwrap().into_rgb8().data(),
+            (1..=12).collect::<Vec<u8>>().as_slice()
+        );
+
+        let mut reader = PnmRowReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(
+            (reader.width(), reader.height(), reader.channels()),
+            (2, 2, 3)
+        );
+        assert_eq!(reader.next_row().unwrap(), Some(&[1, 2, 3, 4, 5, 6][..]));
+        assert_eq!(reader.next_row().unwrap(), Some(&[7, 8, 9, 10, 11, 12][..]));
+        assert_eq!(reader.next_row().unwrap(), None);
+
+        // Comments, a small maxval and 16-bit samples are rescaled to 8 bits
+        let mut reader = PnmRowReader::new(&b"P5 # depth\n2 1 15\n\x00\x0f"[..]).unwrap();
+        assert_eq!(reader.next_row().unwrap(), Some(&[0, 255][..]));
+        let mut reader = PnmRowReader::new(&b"P5\n2 1\n65535\n\x00\x00\xff\xff"[..]).unwrap();
+        assert_eq!(reader.next_row().unwrap(), Some(&[0, 255][..]));
+
+        // PBM bits are unpacked with 1 for black
+        let mut reader = PnmRowReader::new(&b"P4\n10 1\n\xa0\x40"[..]).unwrap();
+        assert_eq!(
+            reader.next_row().unwrap(),
+            Some(&[0, 255, 0, 255, 255, 255, 255, 255, 255, 0][..])
+        );
+
+        assert!(matches!(
+            PnmRowReader::new(&b"P3\n1 1\n255\n0 0 0\n"[..]),
+            Err(ImageError::Codec(CodecError::Unsupported(_)))
+        ));
+        assert!(matches!(
+            PnmRowReader::new(&b"P5\n2 x\n"[..]),
+            Err(ImageError::Codec(CodecError::InvalidData(_)))
+        ));
+        let mut reader = PnmRowReader::new(&b"P5\n2 2\n255\n\x01\x02\x03"[..]).unwrap();
+        reader.next_row().unwrap();
+        assert!(matches!(
+            reader.next_row(),
+            Err(ImageError::Codec(CodecError::UnexpectedEof))
+        ));
+
+        // Oversized widths and samples above maxval fail instead of allocating or wrapping
+        assert!(matches!(
+            PnmRowReader::new(&b"P5 999999999999 1 255\n"[..]),
+            Err(ImageError::Codec(CodecError::InvalidData(_)))
+        ));
+        let mut reader = PnmRowReader::new(&b"P5 1 1 15\n\xc8"[..]).unwrap();
+        assert!(matches!(
+            reader.next_row(),
+            Err(ImageError::Codec(CodecError::InvalidData(_)))
+        ));
+
+        let writer = PnmRowWriter::new(Vec::new(), 1, 1, 1).unwrap();
+        assert!(writer.finish().is_err());
+    }
 }
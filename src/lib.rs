@@ -43,26 +43,58 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+pub mod codec;
 pub mod color;
 pub mod convolution;
+pub mod demosaic;
 pub mod drawing;
+pub mod histogram;
+pub mod lut;
 pub mod matrix;
+pub mod quantize;
+pub mod threshold;
 pub mod transform;
+pub mod white_balance;
 
 #[cfg(feature = "std")]
 pub mod io;
+#[cfg(feature = "std")]
+pub mod video;
 
 #[cfg(feature = "window")]
 pub mod window;
 
-pub use color::{hsl_to_rgb, hsv_to_rgb, rgb_to_hsl, rgb_to_hsv, GrayscaleMethod};
+pub use color::{
+    hsl_to_rgb, hsv_to_rgb, linear_to_srgb, rgb_to_hsl, rgb_to_hsv, rgb_to_yuv, srgb_to_linear,
+    yuv_to_rgb, ColorSpace, GrayscaleMethod, SrgbLut, YuvBufferSizeError, YuvFormat, YuvMatrix,
+    YuvRange,
+};
 pub use convolution::{BorderMode, Kernel};
-pub use drawing::{draw_circle, draw_rectangle, Color, DrawTarget, HexParseError, Stroke};
-pub use matrix::{Matrix1, Matrix3};
-pub use transform::{InterpolationMethod, Rotation, RotationAngle};
+pub use demosaic::{BayerPattern, DemosaicMethod};
+pub use drawing::{
+    draw_circle, draw_rectangle, Color, DrawTarget, HexParseError, LinearLight, Stroke,
+};
+pub use matrix::{DynamicImage, Matrix1, Matrix1U16, Matrix3, Matrix3U16, WritableImage};
+pub use quantize::{DitherMethod, QuantizeMethod};
+pub use transform::{
+    FlipDirection, InterpolationMethod, Orientation, PolarMapping, Rotation, RotationAngle,
+};
+pub use white_balance::WhiteBalanceMethod;
+
+#[cfg(feature = "std")]
+pub use io::{
+    decode_apng_frames, decode_gif_frames, decode_image, decode_jpeg, decode_png, encode_jpeg,
+    encode_png, read_apng_frames, read_bmp, read_gif_frames, read_image, read_jpeg, read_jpeg_gray,
+    read_jpeg_metadata, read_png, read_png_16, read_png_gray, read_png_gray_16, read_png_indexed,
+    read_pnm, read_qoi, read_tga, read_tiff, read_tiff_pages, write_apng, write_bmp, write_gif,
+    write_image, write_jpeg, write_jpeg_with_options, write_pam, write_pbm, write_png,
+    write_png_indexed, write_pnm, write_qoi, write_tga, write_tiff, write_tiff_pages,
+    AnimationFrame, ChromaSubsampling, FrameDisposal, ImageFormat, IndexedImage, JpegOptions,
+    PngRowReader, PngRowWriter, PnmRowReader, PnmRowWriter, TiffCompression, Y4mReader, Y4mWriter,
+};
 
 #[cfg(feature = "std")]
-pub use io::{read_jpeg, read_png, write_jpeg, write_png};
+pub use video::{Frame, FrameSink, FrameSource};
 
 #[cfg(feature = "window")]
 pub use window::{show_and_wait, show_image, wait_key, Displayable, WindowError};
@@ -0,0 +1,366 @@
+//! Lookup table module.
+//!
+//! This module applies 256-entry lookup tables (LUTs) to images and builds tables
+//! for common tone adjustments: gamma, brightness and contrast, levels, and
+//! piecewise-linear curves. A LUT replaces every sample with a table entry, so any
+//! combination of adjustments costs one memory access per sample once the table is
+//! built. Tables can be chained with [`compose`].
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::{lut, Matrix3};
+//!
+//! let image = Matrix3::zeros(320, 240);
+//! let table = lut::compose(&lut::levels(16, 235), &lut::gamma(0.8));
+//! let adjusted = image.apply_lut(&table);
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::matrix::{Matrix1, Matrix3};
+
+/// Rounds and saturates a value to the 0-255 range.
+fn saturate(value: f32) -> u8 {
+    (value.clamp(0.0, 255.0) + 0.5) as u8
+}
+
+/// Builds a table by evaluating `f` for every input value.
+fn build<F: Fn(f32) -> f32>(f: F) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (value, out) in table.iter_mut().enumerate() {
+        *out = saturate(f(value as f32));
+    }
+    table
+}
+
+/// Returns the table that leaves every value unchanged.
+pub fn identity() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (value, out) in table.iter_mut().enumerate() {
+        *out = value as u8;
+    }
+    table
+}
+
+/// Builds a power-law (gamma) table.
+///
+/// Values are normalized to 0.0-1.0 and raised to the power `gamma`: exponents below
+/// 1.0 brighten the midtones and exponents above 1.0 darken them. Black and white are
+/// unchanged.
+///
+/// # Arguments
+///
+/// * `gamma` - The exponent
+///
+/// # Panics
+///
+/// Panics if `gamma` is not a positive finite number.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::lut;
+///
+/// let brighten = lut::gamma(0.5);
+/// assert_eq!((brighten[0], brighten[64], brighten[255]), (0, 128, 255));
+/// ```
+pub fn gamma(gamma: f32) -> [u8; 256] {
+    assert!(
+        gamma.is_finite() && gamma > 0.0,
+        "Gamma must be a positive finite number"
+    );
+    build(|v| 255.0 * libm::powf(v / 255.0, gamma))
+}
+
+/// Builds a brightness and contrast table.
+///
+/// Contrast scales the distance of each value from mid-gray (127.5), then brightness
+/// is added. Results are saturated to 0-255.
+///
+/// # Arguments
+///
+/// * `brightness` - Offset added to every value, typically -255.0 to 255.0
+/// * `contrast` - Contrast factor; 1.0 keeps the contrast, 0.0 turns everything gray
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::lut;
+///
+/// let punchy = lut::brightness_contrast(10.0, 1.5);
+/// assert_eq!((punchy[0], punchy[128], punchy[240]), (0, 138, 255));
+/// ```
+pub fn brightness_contrast(brightness: f32, contrast: f32) -> [u8; 256] {
+    build(|v| (v - 127.5) * contrast + 127.5 + brightness)
+}
+
+/// Builds a levels table that stretches a black point to 0 and a white point to 255.
+///
+/// Values at or below `black` become 0, values at or above `white` become 255, and
+/// values in between are stretched linearly.
+///
+/// # Arguments
+///
+/// * `black` - Input value mapped to black
+/// * `white` - Input value mapped to white
+///
+/// # Panics
+///
+/// Panics if `black` is not below `white`.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::lut;
+///
+/// // Expand limited-range video levels to full range
+/// let full = lut::levels(16, 235);
+/// assert_eq!((full[16], full[126], full[235]), (0, 128, 255));
+/// ```
+pub fn levels(black: u8, white: u8) -> [u8; 256] {
+    assert!(black < white, "Black point must be below the white point");
+    let (black, white) = (black as f32, white as f32);
+    build(|v| (v - black) * 255.0 / (white - black))
+}
+
+/// Builds a piecewise-linear tone curve through control points.
+///
+/// The curve interpolates linearly between the points, which are sorted by input
+/// value. Inputs before the first point take its output and inputs after the last
+/// point take the last output. If several points share an input value, the last one
+/// given wins.
+///
+/// # Arguments
+///
+/// * `points` - `(input, output)` control points
+///
+/// # Panics
+///
+/// Panics if `points` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::lut;
+///
+/// // A gentle S-curve that deepens shadows and lifts highlights
+/// let s_curve = lut::curve(&[(0, 0), (64, 48), (192, 208), (255, 255)]);
+/// assert_eq!((s_curve[64], s_curve[128], s_curve[192]), (48, 128, 208));
+/// ```
+pub fn curve(points: &[(u8, u8)]) -> [u8; 256] {
+    assert!(!points.is_empty(), "A curve needs at least one point");
+
+    let mut points: Vec<(u8, u8)> = points.to_vec();
+    // A stable sort keeps duplicate inputs in their given order
+    points.sort_by_key(|&(input, _)| input);
+    points.reverse();
+    points.dedup_by_key(|&mut (input, _)| input);
+    points.reverse();
+
+    let mut table = [0u8; 256];
+    let mut segment = 0;
+    for (value, out) in table.iter_mut().enumerate() {
+        while segment + 1 < points.len() && value >= points[segment + 1].0 as usize {
+            segment += 1;
+        }
+        let (x0, y0) = points[segment];
+        *out = match points.get(segment + 1) {
+            Some(&(x1, y1)) if value >= x0 as usize => {
+                let t = (value as f32 - x0 as f32) / (x1 as f32 - x0 as f32);
+                saturate(y0 as f32 + t * (y1 as f32 - y0 as f32))
+            }
+            _ => y0,
+        };
+    }
+    table
+}
+
+/// Combines two tables into one that applies `first` and then `second`.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::lut;
+///
+/// let invert = lut::curve(&[(0, 255), (255, 0)]);
+/// assert_eq!(lut::compose(&invert, &invert), lut::identity());
+/// ```
+pub fn compose(first: &[u8; 256], second: &[u8; 256]) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (out, &value) in table.iter_mut().zip(first) {
+        *out = second[value as usize];
+    }
+    table
+}
+
+/// Replaces every sample of interleaved data through the table of its channel.
+fn map_samples(data: &mut [u8], luts: &[&[u8; 256]]) {
+    let channels = luts.len();
+
+    #[cfg(feature = "parallel")]
+    data.par_chunks_mut(channels).for_each(|pixel| {
+        for (sample, lut) in pixel.iter_mut().zip(luts) {
+            *sample = lut[*sample as usize];
+        }
+    });
+
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_mut(channels).for_each(|pixel| {
+        for (sample, lut) in pixel.iter_mut().zip(luts) {
+            *sample = lut[*sample as usize];
+        }
+    });
+}
+
+impl Matrix1 {
+    /// Replaces every pixel value through a lookup table.
+    ///
+    /// Pixels are processed in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `lut` - The output value for each input value, for example from [`gamma`]
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 with the table applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{lut, Matrix1};
+    ///
+    /// let image = Matrix1::new(2, 1, vec![0, 200]);
+    /// let inverted = image.apply_lut(&lut::curve(&[(0, 255), (255, 0)]));
+    /// assert_eq!(inverted.data(), &[255, 55]);
+    /// ```
+    pub fn apply_lut(&self, lut: &[u8; 256]) -> Matrix1 {
+        let mut result = self.clone();
+        map_samples(result.data_mut(), &[lut]);
+        result
+    }
+}
+
+impl Matrix3 {
+    /// Replaces every sample of all three channels through one lookup table.
+    ///
+    /// Pixels are processed in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `lut` - The output value for each input value
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the table applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{lut, Matrix3};
+    ///
+    /// let image = Matrix3::new(1, 1, vec![16, 126, 235]);
+    /// let full_range = image.apply_lut(&lut::levels(16, 235));
+    /// assert_eq!(full_range.data(), &[0, 128, 255]);
+    /// ```
+    pub fn apply_lut(&self, lut: &[u8; 256]) -> Matrix3 {
+        self.apply_channel_luts([lut, lut, lut])
+    }
+
+    /// Replaces the samples of each channel through its own lookup table.
+    ///
+    /// Separate tables allow color grading and white balancing, for example lifting
+    /// only the blue channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `luts` - The red, green and blue tables
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the tables applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{lut, Matrix3};
+    ///
+    /// let image = Matrix3::new(1, 1, vec![100, 100, 100]);
+    /// let identity = lut::identity();
+    /// let warmer = image.apply_channel_luts([
+    ///     &lut::brightness_contrast(20.0, 1.0),
+    ///     &identity,
+    ///     &lut::brightness_contrast(-20.0, 1.0),
+    /// ]);
+    /// assert_eq!(warmer.data(), &[120, 100, 80]);
+    /// ```
+    pub fn apply_channel_luts(&self, luts: [&[u8; 256]; 3]) -> Matrix3 {
+        let mut result = self.clone();
+        map_samples(result.data_mut(), &luts);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders() {
+        assert_eq!(gamma(1.0), identity());
+        assert_eq!(brightness_contrast(0.0, 1.0), identity());
+        assert_eq!(levels(0, 255), identity());
+        assert_eq!(curve(&[(0, 0), (255, 255)]), identity());
+
+        let darken = gamma(2.0);
+        assert_eq!((darken[0], darken[128], darken[255]), (0, 64, 255));
+
+        let flat = brightness_contrast(0.0, 0.0);
+        assert!(flat.iter().all(|&v| v == 128));
+
+        let stretched = levels(100, 150);
+        assert_eq!(
+            (stretched[50], stretched[125], stretched[200]),
+            (0, 128, 255)
+        );
+    }
+
+    #[test]
+    fn test_curve_edges_and_duplicates() {
+        // Outputs are held flat outside the control points
+        let table = curve(&[(200, 50), (100, 150)]);
+        assert_eq!(
+            (table[0], table[100], table[150], table[255]),
+            (150, 150, 100, 50)
+        );
+
+        // A step made of two points at the same input keeps the last one
+        let table = curve(&[(0, 0), (128, 0), (128, 255), (127, 0)]);
+        assert_eq!(table[127], 0);
+        assert_eq!(table[128], 255);
+        assert_eq!(table[255], 255);
+
+        assert!(curve(&[(10, 77)]).iter().all(|&v| v == 77));
+    }
+
+    #[test]
+    fn test_apply_luts() {
+        let gray = Matrix1::new(3, 1, vec![0, 128, 255]);
+        assert_eq!(gray.apply_lut(&gamma(2.0)).data(), &[0, 64, 255]);
+
+        let rgb = Matrix3::new(2, 1, vec![10, 20, 30, 40, 50, 60]);
+        let zero = [0u8; 256];
+        let full = [255u8; 256];
+        let id = identity();
+        let result = rgb.apply_channel_luts([&zero, &id, &full]);
+        assert_eq!(result.data(), &[0, 20, 255, 0, 50, 255]);
+        assert_eq!(rgb.apply_lut(&id).data(), rgb.data());
+    }
+}
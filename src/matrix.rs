@@ -1,12 +1,20 @@
 //! Matrix module for representing multi-channel image data.
 //!
+//! `Matrix1` and `Matrix3` hold 8-bit samples; `Matrix1U16` and `Matrix3U16`
+//! hold 16-bit samples for high bit-depth sources.
+//!
 //! This module is `no_std` compatible and only requires the `alloc` crate.
 
+#[cfg(not(feature = "std"))]
+use alloc::borrow::Cow;
 #[cfg(not(feature = "std"))]
 use alloc::vec;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
 
+use crate::color::GrayscaleMethod;
 use core::fmt;
 
 /// A single-channel matrix for representing grayscale image data.
@@ -36,6 +44,34 @@ pub struct Matrix3 {
     data: Vec<u8>,
 }
 
+/// A single-channel matrix with 16-bit samples, for high bit-depth grayscale data
+/// such as depth maps or scientific images.
+///
+/// The data is stored in a contiguous `Vec<u16>` in row-major order.
+#[derive(Debug, Clone)]
+pub struct Matrix1U16 {
+    /// Width of the matrix (number of columns)
+    width: usize,
+    /// Height of the matrix (number of rows)
+    height: usize,
+    /// Raw pixel data stored as [pixel, pixel, pixel, ...]
+    data: Vec<u16>,
+}
+
+/// A three-channel matrix with 16-bit samples, for high bit-depth RGB data.
+///
+/// The data is stored in a contiguous `Vec<u16>` in row-major order,
+/// with channels interleaved (RGBRGBRGB...).
+#[derive(Debug, Clone)]
+pub struct Matrix3U16 {
+    /// Width of the matrix (number of columns)
+    width: usize,
+    /// Height of the matrix (number of rows)
+    height: usize,
+    /// Raw pixel data stored as [R, G, B, R, G, B, ...]
+    data: Vec<u16>,
+}
+
 impl Matrix1 {
     /// Creates a new Matrix1 with the specified dimensions.
     ///
@@ -143,6 +179,15 @@ impl Matrix1 {
     pub fn into_raw(self) -> Vec<u8> {
         self.data
     }
+
+    /// Converts the matrix to 16 bits per sample, mapping 255 to 65535.
+    pub fn to_u16(&self) -> Matrix1U16 {
+        Matrix1U16::new(
+            self.width,
+            self.height,
+            self.data.iter().map(|&v| u8_to_u16(v)).collect(),
+        )
+    }
 }
 
 impl fmt::Display for Matrix1 {
@@ -266,6 +311,15 @@ impl Matrix3 {
     pub fn into_raw(self) -> Vec<u8> {
         self.data
     }
+
+    /// Converts the matrix to 16 bits per sample, mapping 255 to 65535.
+    pub fn to_u16(&self) -> Matrix3U16 {
+        Matrix3U16::new(
+            self.width,
+            self.height,
+            self.data.iter().map(|&v| u8_to_u16(v)).collect(),
+        )
+    }
 }
 
 impl fmt::Display for Matrix3 {
@@ -278,6 +332,448 @@ impl fmt::Display for Matrix3 {
     }
 }
 
+impl Matrix1U16 {
+    /// Creates a new Matrix1U16 with the specified dimensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width (number of columns) of the matrix
+    /// * `height` - The height (number of rows) of the matrix
+    /// * `data` - The raw pixel data (must be width * height samples)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data length doesn't match width * height.
+    pub fn new(width: usize, height: usize, data: Vec<u16>) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height,
+            "Data length must be width * height"
+        );
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Creates a new Matrix1U16 filled with zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width (number of columns) of the matrix
+    /// * `height` - The height (number of rows) of the matrix
+    pub fn zeros(width: usize, height: usize) -> Self {
+        let data = vec![0u16; width * height];
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Returns the width of the matrix.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the matrix.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the dimensions as (width, height).
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns a reference to the raw pixel data.
+    pub fn data(&self) -> &[u16] {
+        &self.data
+    }
+
+    /// Returns a mutable reference to the raw pixel data.
+    pub fn data_mut(&mut self) -> &mut [u16] {
+        &mut self.data
+    }
+
+    /// Gets the pixel value at the specified location.
+    ///
+    /// Returns Some(value) if the coordinates are valid, None otherwise.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<u16> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.data[y * self.width + x])
+    }
+
+    /// Sets the pixel value at the specified location.
+    ///
+    /// Returns true if the pixel was set successfully, false if coordinates are out of bounds.
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: u16) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        self.data[y * self.width + x] = value;
+        true
+    }
+
+    /// Consumes the matrix and returns the raw data.
+    pub fn into_raw(self) -> Vec<u16> {
+        self.data
+    }
+
+    /// Converts the matrix to 8 bits per sample, rounding to the nearest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix1U16;
+    ///
+    /// let depth = Matrix1U16::new(2, 1, vec![0, 65535]);
+    /// assert_eq!(depth.to_u8().data(), &[0, 255]);
+    /// ```
+    pub fn to_u8(&self) -> Matrix1 {
+        Matrix1::new(
+            self.width,
+            self.height,
+            self.data.iter().map(|&v| u16_to_u8(v)).collect(),
+        )
+    }
+}
+
+impl fmt::Display for Matrix1U16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Matrix1U16 {{ width: {}, height: {}, channels: 1 }}",
+            self.width, self.height
+        )
+    }
+}
+
+impl Matrix3U16 {
+    /// Creates a new Matrix3U16 with the specified dimensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width (number of columns) of the matrix
+    /// * `height` - The height (number of rows) of the matrix
+    /// * `data` - The raw pixel data in RGB format (must be width * height * 3 samples)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data length doesn't match width * height * 3.
+    pub fn new(width: usize, height: usize, data: Vec<u16>) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height * 3,
+            "Data length must be width * height * 3"
+        );
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Creates a new Matrix3U16 filled with zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width (number of columns) of the matrix
+    /// * `height` - The height (number of rows) of the matrix
+    pub fn zeros(width: usize, height: usize) -> Self {
+        let data = vec![0u16; width * height * 3];
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Returns the width of the matrix.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the matrix.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the dimensions as (width, height).
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns a reference to the raw pixel data.
+    pub fn data(&self) -> &[u16] {
+        &self.data
+    }
+
+    /// Returns a mutable reference to the raw pixel data.
+    pub fn data_mut(&mut self) -> &mut [u16] {
+        &mut self.data
+    }
+
+    /// Gets the RGB values at the specified pixel location.
+    ///
+    /// Returns Some((r, g, b)) if the coordinates are valid, None otherwise.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<(u16, u16, u16)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let idx = (y * self.width + x) * 3;
+        Some((self.data[idx], self.data[idx + 1], self.data[idx + 2]))
+    }
+
+    /// Sets the RGB values at the specified pixel location.
+    ///
+    /// Returns true if the pixel was set successfully, false if coordinates are out of bounds.
+    pub fn set_pixel(&mut self, x: usize, y: usize, r: u16, g: u16, b: u16) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let idx = (y * self.width + x) * 3;
+        self.data[idx] = r;
+        self.data[idx + 1] = g;
+        self.data[idx + 2] = b;
+        true
+    }
+
+    /// Consumes the matrix and returns the raw data.
+    pub fn into_raw(self) -> Vec<u16> {
+        self.data
+    }
+
+    /// Converts the matrix to 8 bits per sample, rounding to the nearest value.
+    pub fn to_u8(&self) -> Matrix3 {
+        Matrix3::new(
+            self.width,
+            self.height,
+            self.data.iter().map(|&v| u16_to_u8(v)).collect(),
+        )
+    }
+}
+
+impl fmt::Display for Matrix3U16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Matrix3U16 {{ width: {}, height: {}, channels: 3 }}",
+            self.width, self.height
+        )
+    }
+}
+
+/// An image whose channel count and bit depth are only known at runtime.
+///
+/// Decoders for formats that can store several layouts (such as Netpbm) return this
+/// type so no precision or channels are lost; use [`DynamicImage::into_rgb8`] or
+/// [`DynamicImage::into_gray8`] to normalize it.
+#[derive(Debug, Clone)]
+pub enum DynamicImage {
+    /// 8-bit grayscale
+    Gray8(Matrix1),
+    /// 16-bit grayscale
+    Gray16(Matrix1U16),
+    /// 8-bit RGB
+    Rgb8(Matrix3),
+    /// 16-bit RGB
+    Rgb16(Matrix3U16),
+}
+
+impl DynamicImage {
+    /// Returns the dimensions as (width, height).
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            DynamicImage::Gray8(m) => m.dimensions(),
+            DynamicImage::Gray16(m) => m.dimensions(),
+            DynamicImage::Rgb8(m) => m.dimensions(),
+            DynamicImage::Rgb16(m) => m.dimensions(),
+        }
+    }
+
+    /// Converts the image to 8-bit RGB, expanding grayscale to three equal channels.
+    pub fn into_rgb8(self) -> Matrix3 {
+        match self {
+            DynamicImage::Gray8(m) => gray_to_rgb(&m),
+            DynamicImage::Gray16(m) => gray_to_rgb(&m.to_u8()),
+            DynamicImage::Rgb8(m) => m,
+            DynamicImage::Rgb16(m) => m.to_u8(),
+        }
+    }
+
+    /// Converts the image to 8-bit grayscale, using `method` for color images.
+    pub fn into_gray8(self, method: GrayscaleMethod) -> Matrix1 {
+        match self {
+            DynamicImage::Gray8(m) => m,
+            DynamicImage::Gray16(m) => m.to_u8(),
+            DynamicImage::Rgb8(m) => m.to_grayscale_with_method(method),
+            DynamicImage::Rgb16(m) => m.to_u8().to_grayscale_with_method(method),
+        }
+    }
+}
+
+/// Expands a grayscale image to three equal RGB channels.
+fn gray_to_rgb(gray: &Matrix1) -> Matrix3 {
+    let data = gray.data().iter().flat_map(|&v| [v, v, v]).collect();
+    Matrix3::new(gray.width(), gray.height(), data)
+}
+
+/// Trait for image types that can be written by image encoders.
+///
+/// This trait is implemented by `Matrix1` (grayscale) and `Matrix3` (RGB) as well as
+/// their 16-bit counterparts, so writers such as `write_png` produce true
+/// single-channel or 16-bit files when given such images. `DynamicImage` implements it
+/// by delegating to the matrix it holds, which lets a single slice of pages mix
+/// channel counts and bit depths.
+pub trait WritableImage {
+    /// Returns the width of the image.
+    fn width(&self) -> usize;
+
+    /// Returns the height of the image.
+    fn height(&self) -> usize;
+
+    /// Returns the number of interleaved channels (1 for grayscale, 3 for RGB).
+    fn channels(&self) -> usize;
+
+    /// Returns the number of bits per channel sample (8 or 16).
+    fn bit_depth(&self) -> u8 {
+        8
+    }
+
+    /// Returns the interleaved samples as bytes, with 16-bit samples in big-endian order.
+    fn sample_bytes(&self) -> Cow<'_, [u8]>;
+}
+
+impl WritableImage for Matrix1 {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn channels(&self) -> usize {
+        1
+    }
+
+    fn sample_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.data())
+    }
+}
+
+impl WritableImage for Matrix3 {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn channels(&self) -> usize {
+        3
+    }
+
+    fn sample_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.data())
+    }
+}
+
+impl WritableImage for Matrix1U16 {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn channels(&self) -> usize {
+        1
+    }
+
+    fn bit_depth(&self) -> u8 {
+        16
+    }
+
+    fn sample_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.data().iter().flat_map(|v| v.to_be_bytes()).collect())
+    }
+}
+
+impl WritableImage for Matrix3U16 {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn channels(&self) -> usize {
+        3
+    }
+
+    fn bit_depth(&self) -> u8 {
+        16
+    }
+
+    fn sample_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.data().iter().flat_map(|v| v.to_be_bytes()).collect())
+    }
+}
+
+impl DynamicImage {
+    /// Returns the held matrix as a writable image.
+    fn as_writable(&self) -> &dyn WritableImage {
+        match self {
+            DynamicImage::Gray8(m) => m,
+            DynamicImage::Gray16(m) => m,
+            DynamicImage::Rgb8(m) => m,
+            DynamicImage::Rgb16(m) => m,
+        }
+    }
+}
+
+impl WritableImage for DynamicImage {
+    fn width(&self) -> usize {
+        self.as_writable().width()
+    }
+
+    fn height(&self) -> usize {
+        self.as_writable().height()
+    }
+
+    fn channels(&self) -> usize {
+        self.as_writable().channels()
+    }
+
+    fn bit_depth(&self) -> u8 {
+        self.as_writable().bit_depth()
+    }
+
+    fn sample_bytes(&self) -> Cow<'_, [u8]> {
+        self.as_writable().sample_bytes()
+    }
+}
+
+/// Scales a 16-bit sample to 8 bits with rounding.
+#[inline]
+fn u16_to_u8(value: u16) -> u8 {
+    ((value as u32 * 255 + 32767) / 65535) as u8
+}
+
+/// Scales an 8-bit sample to the full 16-bit range.
+#[inline]
+fn u8_to_u16(value: u8) -> u16 {
+    value as u16 * 257
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,4 +841,37 @@ mod tests {
         let data = vec![0u8; 100];
         Matrix3::new(10, 10, data); // Should panic: 100 != 10 * 10 * 3
     }
+
+    #[test]
+    fn test_matrix_u16_get_set_pixel() {
+        let mut mat = Matrix3U16::zeros(4, 4);
+        assert!(mat.set_pixel(1, 2, 1000, 40000, 65535));
+        assert_eq!(mat.get_pixel(1, 2), Some((1000, 40000, 65535)));
+        assert_eq!(mat.get_pixel(4, 0), None);
+
+        let mut gray = Matrix1U16::zeros(3, 3);
+        assert!(gray.set_pixel(2, 2, 4095));
+        assert_eq!(gray.get_pixel(2, 2), Some(4095));
+    }
+
+    #[test]
+    fn test_bit_depth_conversion_roundtrip() {
+        let mat = Matrix1::new(4, 1, vec![0, 1, 128, 255]);
+        let wide = mat.to_u16();
+        assert_eq!(wide.data(), &[0, 257, 32896, 65535]);
+        assert_eq!(wide.to_u8().data(), mat.data());
+
+        let rgb = Matrix3::new(1, 1, vec![10, 20, 30]);
+        assert_eq!(rgb.to_u16().to_u8().data(), rgb.data());
+    }
+
+    #[test]
+    fn test_dynamic_image_conversions() {
+        let gray = DynamicImage::Gray16(Matrix1U16::new(2, 1, vec![0, 65535]));
+        assert_eq!(gray.dimensions(), (2, 1));
+        assert_eq!(gray.into_rgb8().data(), &[0, 0, 0, 255, 255, 255]);
+
+        let rgb = DynamicImage::Rgb8(Matrix3::new(1, 1, vec![30, 60, 90]));
+        assert_eq!(rgb.into_gray8(GrayscaleMethod::Average).data(), &[60]);
+    }
 }
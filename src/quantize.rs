@@ -0,0 +1,555 @@
+//! Color quantization module.
+//!
+//! This module reduces RGB images to a small palette, as needed for GIF and
+//! indexed PNG output, for compact transmission over slow links, and for
+//! extracting the dominant colors of a scene. Quantization returns the palette
+//! together with an index map holding the palette index of every pixel.
+//!
+//! Palettes are built on a histogram of the image with 5 bits per channel, so their
+//! cost depends on the number of distinct colors rather than on the image size.
+//! Palette entries are ordered by the number of pixels they represent, most common
+//! first.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::{DitherMethod, Matrix3, QuantizeMethod};
+//!
+//! let image = Matrix3::zeros(320, 240);
+//! let (palette, indices) = image.quantize(16, QuantizeMethod::MedianCut, DitherMethod::None);
+//! assert!(palette.len() <= 16);
+//! assert_eq!(indices.dimensions(), (320, 240));
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use core::cmp::Reverse;
+use core::ops::Range;
+
+use crate::matrix::{Matrix1, Matrix3};
+
+/// Algorithms for choosing a palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantizeMethod {
+    /// Recursively splits the color box with the widest channel range, weighted by its
+    /// pixel count, at its median. Fast and deterministic.
+    MedianCut,
+    /// Refines the median-cut palette with k-means clustering, which lowers the
+    /// average color error at a higher cost.
+    KMeans,
+}
+
+/// Dithering applied when mapping pixels onto a palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DitherMethod {
+    /// Maps every pixel to its nearest palette color. Smooth gradients show banding.
+    None,
+    /// Floyd-Steinberg error diffusion. Gives the most faithful gradients, but
+    /// processes pixels sequentially and compresses worse.
+    FloydSteinberg,
+    /// Ordered dithering with an 8x8 Bayer matrix. Produces a regular pattern that
+    /// is stable between video frames.
+    Ordered,
+}
+
+/// Number of histogram bits kept per channel.
+const HISTOGRAM_BITS: u32 = 5;
+
+/// Maximum number of k-means refinement passes.
+const KMEANS_ITERATIONS: usize = 16;
+
+/// 8x8 Bayer threshold matrix for ordered dithering.
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// A set of pixels with their summed color.
+#[derive(Debug, Clone, Copy, Default)]
+struct Cluster {
+    sum: [u64; 3],
+    count: u64,
+}
+
+impl Cluster {
+    fn add(&mut self, other: &Cluster) {
+        for (sum, value) in self.sum.iter_mut().zip(other.sum) {
+            *sum += value;
+        }
+        self.count += other.count;
+    }
+
+    /// Returns the mean color of the pixels.
+    fn mean(&self) -> [f32; 3] {
+        self.sum.map(|sum| sum as f32 / self.count.max(1) as f32)
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        let [r, g, b] = self.mean().map(|c| (c + 0.5) as u8);
+        (r, g, b)
+    }
+}
+
+/// Groups the pixels into histogram bins and returns the non-empty bins.
+fn color_bins(data: &[u8]) -> Vec<Cluster> {
+    let shift = 8 - HISTOGRAM_BITS;
+    let mut bins = vec![Cluster::default(); 1 << (3 * HISTOGRAM_BITS)];
+    for pixel in data.chunks_exact(3) {
+        let key = pixel
+            .iter()
+            .fold(0, |key, &v| (key << HISTOGRAM_BITS) | (v >> shift) as usize);
+        let bin = &mut bins[key];
+        for (sum, &v) in bin.sum.iter_mut().zip(pixel) {
+            *sum += v as u64;
+        }
+        bin.count += 1;
+    }
+    bins.retain(|bin| bin.count > 0);
+    bins
+}
+
+/// Returns the channel with the widest range of mean colors and that range.
+fn widest_channel(bins: &[Cluster]) -> (usize, f32) {
+    let mut lower = [f32::MAX; 3];
+    let mut upper = [f32::MIN; 3];
+    for bin in bins {
+        for (c, value) in bin.mean().into_iter().enumerate() {
+            lower[c] = lower[c].min(value);
+            upper[c] = upper[c].max(value);
+        }
+    }
+    (0..3)
+        .map(|c| (c, upper[c] - lower[c]))
+        .fold(
+            (0, 0.0),
+            |best, range| if range.1 > best.1 { range } else { best },
+        )
+}
+
+/// Partitions the bins into at most `colors` clusters with the median-cut algorithm.
+fn median_cut(bins: &mut [Cluster], colors: usize) -> Vec<Cluster> {
+    let mut boxes: Vec<Range<usize>> = Vec::with_capacity(colors);
+    boxes.push(0..bins.len());
+    while boxes.len() < colors {
+        // Pick the box whose widest range, weighted by its pixel count, is largest
+        let mut best = None;
+        let mut best_score = 0.0;
+        for (i, range) in boxes.iter().enumerate() {
+            let (channel, extent) = widest_channel(&bins[range.clone()]);
+            let count: u64 = bins[range.clone()].iter().map(|bin| bin.count).sum();
+            let score = extent * count as f32;
+            if range.len() > 1 && score > best_score {
+                best = Some((i, channel));
+                best_score = score;
+            }
+        }
+        let Some((i, channel)) = best else {
+            break;
+        };
+
+        let range = boxes[i].clone();
+        let members = &mut bins[range.clone()];
+        members.sort_by(|a, b| a.mean()[channel].total_cmp(&b.mean()[channel]));
+
+        // Split at the weighted median, keeping at least one bin on each side
+        let half = members.iter().map(|bin| bin.count).sum::<u64>() / 2;
+        let mut cumulative = 0;
+        let mut split = 1;
+        for (j, bin) in members.iter().enumerate().take(members.len() - 1) {
+            cumulative += bin.count;
+            split = j + 1;
+            if cumulative >= half {
+                break;
+            }
+        }
+
+        boxes[i] = range.start..range.start + split;
+        boxes.push(range.start + split..range.end);
+    }
+
+    boxes
+        .into_iter()
+        .map(|range| {
+            let mut cluster = Cluster::default();
+            bins[range].iter().for_each(|bin| cluster.add(bin));
+            cluster
+        })
+        .collect()
+}
+
+/// Refines cluster centers with k-means iterations over the histogram bins.
+fn kmeans(bins: &[Cluster], initial: Vec<Cluster>) -> Vec<Cluster> {
+    let mut centers: Vec<[f32; 3]> = initial.iter().map(Cluster::mean).collect();
+    let mut clusters = initial;
+    let mut assignment = vec![usize::MAX; bins.len()];
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (bin, assigned) in bins.iter().zip(assignment.iter_mut()) {
+            let nearest = nearest_color(&centers, bin.mean());
+            changed |= nearest != *assigned;
+            *assigned = nearest;
+        }
+        if !changed {
+            break;
+        }
+
+        clusters = vec![Cluster::default(); centers.len()];
+        for (bin, &assigned) in bins.iter().zip(&assignment) {
+            clusters[assigned].add(bin);
+        }
+        // Empty clusters keep their previous center
+        for (center, cluster) in centers.iter_mut().zip(&clusters) {
+            if cluster.count > 0 {
+                *center = cluster.mean();
+            }
+        }
+    }
+
+    clusters
+}
+
+/// Builds a palette of at most `colors` entries, most common first, together with
+/// the number of pixels each entry represents.
+fn build_palette(image: &Matrix3, colors: usize, method: QuantizeMethod) -> Vec<Cluster> {
+    let mut bins = color_bins(image.data());
+    let mut clusters = median_cut(&mut bins, colors);
+    if method == QuantizeMethod::KMeans {
+        clusters = kmeans(&bins, clusters);
+    }
+    // Images without pixels, and k-means clusters that lost all their members,
+    // leave empty clusters behind
+    clusters.retain(|cluster| cluster.count > 0);
+    clusters.sort_by_key(|cluster| Reverse(cluster.count));
+    clusters
+}
+
+/// Returns the index of the palette color closest to `color`.
+fn nearest_color(palette: &[[f32; 3]], color: [f32; 3]) -> usize {
+    let mut best = 0;
+    let mut best_distance = f32::MAX;
+    for (i, entry) in palette.iter().enumerate() {
+        let distance: f32 = entry
+            .iter()
+            .zip(color)
+            .map(|(p, c)| (p - c) * (p - c))
+            .sum();
+        if distance < best_distance {
+            best = i;
+            best_distance = distance;
+        }
+    }
+    best
+}
+
+/// Checks a requested palette size.
+fn check_colors(colors: usize) {
+    assert!(
+        (1..=256).contains(&colors),
+        "Color count must be between 1 and 256"
+    );
+}
+
+impl Matrix3 {
+    /// Reduces the image to a palette of at most `colors` colors.
+    ///
+    /// Images with few distinct colors may get a smaller palette. Pixels are mapped in
+    /// parallel when the `parallel` feature is enabled, except with Floyd-Steinberg
+    /// dithering, which is inherently sequential.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - Maximum palette size, 1 to 256
+    /// * `method` - Algorithm used to choose the palette
+    /// * `dither` - Dithering applied when mapping pixels onto the palette
+    ///
+    /// # Returns
+    ///
+    /// The palette as (r, g, b) entries, most common first, and an index map holding
+    /// the palette index of every pixel. Images without pixels yield an empty palette.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `colors` is not between 1 and 256.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{DitherMethod, Matrix3, QuantizeMethod};
+    ///
+    /// let image = Matrix3::new(3, 1, vec![250, 10, 10, 240, 20, 0, 10, 10, 240]);
+    /// let (palette, indices) = image.quantize(2, QuantizeMethod::MedianCut, DitherMethod::None);
+    /// assert_eq!(palette, vec![(245, 15, 5), (10, 10, 240)]);
+    /// assert_eq!(indices.data(), &[0, 0, 1]);
+    /// ```
+    pub fn quantize(
+        &self,
+        colors: usize,
+        method: QuantizeMethod,
+        dither: DitherMethod,
+    ) -> (Vec<(u8, u8, u8)>, Matrix1) {
+        check_colors(colors);
+        let palette: Vec<(u8, u8, u8)> = build_palette(self, colors, method)
+            .iter()
+            .map(Cluster::color)
+            .collect();
+        if palette.is_empty() {
+            return (
+                palette,
+                Matrix1::new(self.width(), self.height(), Vec::new()),
+            );
+        }
+        let indices = self.map_to_palette(&palette, dither);
+        (palette, indices)
+    }
+
+    /// Maps every pixel onto the nearest color of an existing palette.
+    ///
+    /// Use this to share one palette between several images, for example all frames
+    /// of an animation, or to restrict an image to a fixed set of theme colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `palette` - Palette entries as (r, g, b), 1 to 256
+    /// * `dither` - Dithering applied when mapping
+    ///
+    /// # Returns
+    ///
+    /// An index map holding the palette index of every pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the palette is empty or has more than 256 entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{DitherMethod, Matrix3};
+    ///
+    /// let image = Matrix3::new(2, 1, vec![30, 30, 30, 200, 210, 220]);
+    /// let indices = image.map_to_palette(&[(255, 255, 255), (0, 0, 0)], DitherMethod::None);
+    /// assert_eq!(indices.data(), &[1, 0]);
+    /// ```
+    pub fn map_to_palette(&self, palette: &[(u8, u8, u8)], dither: DitherMethod) -> Matrix1 {
+        check_colors(palette.len());
+        let centers: Vec<[f32; 3]> = palette
+            .iter()
+            .map(|&(r, g, b)| [r as f32, g as f32, b as f32])
+            .collect();
+        let width = self.width();
+        let mut indices = vec![0u8; width * self.height()];
+        if width == 0 {
+            return Matrix1::new(width, self.height(), indices);
+        }
+
+        match dither {
+            DitherMethod::FloydSteinberg => self.diffuse_errors(&centers, &mut indices),
+            DitherMethod::None | DitherMethod::Ordered => {
+                // Ordered dithering offsets pixels by up to half the typical spacing
+                // between palette colors
+                let spread = match dither {
+                    DitherMethod::Ordered => 255.0 / libm::cbrtf(centers.len() as f32),
+                    _ => 0.0,
+                };
+                let data = self.data();
+                let map_row = |(y, row): (usize, &mut [u8])| {
+                    let pixels = data[y * width * 3..(y + 1) * width * 3].chunks_exact(3);
+                    for (x, (index, pixel)) in row.iter_mut().zip(pixels).enumerate() {
+                        let threshold = (BAYER_8X8[y % 8][x % 8] as f32 + 0.5) / 64.0 - 0.5;
+                        let offset = threshold * spread;
+                        let color = [0, 1, 2].map(|c| pixel[c] as f32 + offset);
+                        *index = nearest_color(&centers, color) as u8;
+                    }
+                };
+
+                #[cfg(feature = "parallel")]
+                indices.par_chunks_mut(width).enumerate().for_each(map_row);
+
+                #[cfg(not(feature = "parallel"))]
+                indices.chunks_mut(width).enumerate().for_each(map_row);
+            }
+        }
+
+        Matrix1::new(width, self.height(), indices)
+    }
+
+    /// Maps pixels onto a palette with Floyd-Steinberg error diffusion.
+    fn diffuse_errors(&self, centers: &[[f32; 3]], indices: &mut [u8]) {
+        let width = self.width();
+        // Errors carried into the current and the next row, with one pixel of padding
+        // on both sides
+        let mut current = vec![[0.0f32; 3]; width + 2];
+        let mut next = vec![[0.0f32; 3]; width + 2];
+
+        for (y, row) in indices.chunks_mut(width).enumerate() {
+            for (x, index) in row.iter_mut().enumerate() {
+                let offset = (y * width + x) * 3;
+                let pixel = &self.data()[offset..offset + 3];
+                let color =
+                    [0, 1, 2].map(|c| (pixel[c] as f32 + current[x + 1][c]).clamp(0.0, 255.0));
+                let nearest = nearest_color(centers, color);
+                *index = nearest as u8;
+
+                for c in 0..3 {
+                    let error = color[c] - centers[nearest][c];
+                    current[x + 2][c] += error * 7.0 / 16.0;
+                    next[x][c] += error * 3.0 / 16.0;
+                    next[x + 1][c] += error * 5.0 / 16.0;
+                    next[x + 2][c] += error / 16.0;
+                }
+            }
+            core::mem::swap(&mut current, &mut next);
+            next.fill([0.0; 3]);
+        }
+    }
+
+    /// Finds the most prominent colors of the image.
+    ///
+    /// Colors are found with k-means clustering, so each one is the average of a group
+    /// of similar pixels rather than a single pixel value.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Maximum number of colors to return, 1 to 256
+    ///
+    /// # Returns
+    ///
+    /// Up to `count` colors as (r, g, b) together with the fraction of pixels they
+    /// cover, sorted from most to least common.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is not between 1 and 256.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let mut image = Matrix3::zeros(4, 1);
+    /// image.set_pixel(0, 0, 0, 128, 255);
+    /// let colors = image.dominant_colors(3);
+    /// assert_eq!(colors, vec![((0, 0, 0), 0.75), ((0, 128, 255), 0.25)]);
+    /// ```
+    pub fn dominant_colors(&self, count: usize) -> Vec<((u8, u8, u8), f32)> {
+        check_colors(count);
+        let total = (self.width() * self.height()).max(1) as f32;
+        build_palette(self, count, QuantizeMethod::KMeans)
+            .iter()
+            .map(|cluster| (cluster.color(), cluster.count as f32 / total))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> Matrix3 {
+        let data = (0..64 * 16)
+            .flat_map(|i| {
+                let (x, y) = ((i % 64) as u8, (i / 64) as u8);
+                [x * 4, y * 16, 255 - x * 4]
+            })
+            .collect();
+        Matrix3::new(64, 16, data)
+    }
+
+    fn mean_error(image: &Matrix3, palette: &[(u8, u8, u8)], indices: &Matrix1) -> f32 {
+        let total: u32 = image
+            .data()
+            .chunks_exact(3)
+            .zip(indices.data())
+            .map(|(pixel, &i)| {
+                let (r, g, b) = palette[i as usize];
+                pixel[0].abs_diff(r) as u32
+                    + pixel[1].abs_diff(g) as u32
+                    + pixel[2].abs_diff(b) as u32
+            })
+            .sum();
+        total as f32 / indices.data().len() as f32
+    }
+
+    #[test]
+    fn test_median_cut_and_kmeans() {
+        let image = gradient();
+        let (palette, indices) = image.quantize(16, QuantizeMethod::MedianCut, DitherMethod::None);
+        assert_eq!(palette.len(), 16);
+        assert!(indices.data().iter().all(|&i| (i as usize) < palette.len()));
+        let median_error = mean_error(&image, &palette, &indices);
+        // 16 colors over a two-dimensional gradient leave about 16 levels per channel
+        assert!(median_error < 60.0, "median-cut error {}", median_error);
+
+        let (palette, indices) = image.quantize(16, QuantizeMethod::KMeans, DitherMethod::None);
+        assert!(mean_error(&image, &palette, &indices) <= median_error + 0.5);
+
+        // Fewer distinct colors than requested
+        let flat = Matrix3::new(2, 1, vec![1, 2, 3, 1, 2, 3]);
+        let (palette, indices) = flat.quantize(8, QuantizeMethod::KMeans, DitherMethod::Ordered);
+        assert_eq!(palette, vec![(1, 2, 3)]);
+        assert_eq!(indices.data(), &[0, 0]);
+    }
+
+    #[test]
+    fn test_empty_images() {
+        for (width, height) in [(0, 0), (0, 4), (4, 0)] {
+            let image = Matrix3::zeros(width, height);
+            for method in [QuantizeMethod::MedianCut, QuantizeMethod::KMeans] {
+                let (palette, indices) = image.quantize(4, method, DitherMethod::FloydSteinberg);
+                assert!(palette.is_empty());
+                assert_eq!(indices.dimensions(), (width, height));
+            }
+            assert!(image.dominant_colors(3).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_dithering_preserves_average() {
+        // A mid gray rendered with black and white only
+        let image = Matrix3::new(16, 16, vec![100; 16 * 16 * 3]);
+        let palette = [(0, 0, 0), (255, 255, 255)];
+        assert!(image
+            .map_to_palette(&palette, DitherMethod::None)
+            .data()
+            .iter()
+            .all(|&i| i == 0));
+
+        for dither in [DitherMethod::FloydSteinberg, DitherMethod::Ordered] {
+            let indices = image.map_to_palette(&palette, dither);
+            let white = indices.data().iter().filter(|&&i| i == 1).count();
+            let average = white as f32 * 255.0 / 256.0;
+            assert!((average - 100.0).abs() < 12.0, "{:?}: {}", dither, average);
+        }
+    }
+
+    #[test]
+    fn test_dominant_colors() {
+        let mut data = [200, 30, 30].repeat(60);
+        data.extend([20, 20, 180].repeat(30));
+        data.extend([250, 250, 250].repeat(10));
+        let image = Matrix3::new(10, 10, data);
+
+        let colors = image.dominant_colors(3);
+        assert_eq!(
+            colors,
+            vec![
+                ((200, 30, 30), 0.6),
+                ((20, 20, 180), 0.3),
+                ((250, 250, 250), 0.1)
+            ]
+        );
+        assert_eq!(image.dominant_colors(1).len(), 1);
+    }
+}
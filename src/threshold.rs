@@ -0,0 +1,203 @@
+//! Thresholding module.
+//!
+//! This module selects pixels whose values fall within a range and returns them as
+//! binary masks, the building block of color-based segmentation and blob tracking.
+//! Masks are `Matrix1` images holding 255 for selected pixels and 0 elsewhere.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::matrix::{Matrix1, Matrix3};
+
+/// Value written to mask pixels that are inside the range.
+const MASK_ON: u8 = 255;
+
+impl Matrix1 {
+    /// Selects the pixels whose value lies within an inclusive range.
+    ///
+    /// Pixels are tested in parallel when the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - Smallest selected value
+    /// * `upper` - Largest selected value
+    ///
+    /// # Returns
+    ///
+    /// A mask with 255 where `lower <= value <= upper` and 0 elsewhere. The mask is
+    /// empty if `lower > upper`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix1;
+    ///
+    /// let image = Matrix1::new(4, 1, vec![10, 100, 150, 250]);
+    /// let mask = image.in_range(100, 200);
+    /// assert_eq!(mask.data(), &[0, 255, 255, 0]);
+    /// ```
+    pub fn in_range(&self, lower: u8, upper: u8) -> Matrix1 {
+        let mut mask = vec![0u8; self.data().len()];
+        let select = |(out, &value): (&mut u8, &u8)| {
+            if (lower..=upper).contains(&value) {
+                *out = MASK_ON;
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        mask.par_iter_mut()
+            .zip(self.data().par_iter())
+            .for_each(select);
+
+        #[cfg(not(feature = "parallel"))]
+        mask.iter_mut().zip(self.data().iter()).for_each(select);
+
+        Matrix1::new(self.width(), self.height(), mask)
+    }
+}
+
+impl Matrix3 {
+    /// Selects the pixels whose three channels all lie within inclusive ranges.
+    ///
+    /// The channels are compared as stored, so this works on RGB images as well as on
+    /// the output of [`Matrix3::convert_color`]. For hue ranges that wrap around red,
+    /// use [`Matrix3::in_range_hsv`]. Pixels are tested in parallel when the `parallel`
+    /// feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - Smallest selected value of each channel
+    /// * `upper` - Largest selected value of each channel
+    ///
+    /// # Returns
+    ///
+    /// A mask with 255 where every channel is within its range and 0 elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let image = Matrix3::new(2, 1, vec![200, 40, 30, 40, 200, 30]);
+    /// let reddish = image.in_range((150, 0, 0), (255, 100, 100));
+    /// assert_eq!(reddish.data(), &[255, 0]);
+    /// ```
+    pub fn in_range(&self, lower: (u8, u8, u8), upper: (u8, u8, u8)) -> Matrix1 {
+        let ranges = [lower.0..=upper.0, lower.1..=upper.1, lower.2..=upper.2];
+        self.mask_pixels(|pixel| ranges.iter().zip(pixel).all(|(r, v)| r.contains(v)))
+    }
+
+    /// Selects the pixels of an 8-bit HSV image that lie within a hue, saturation and
+    /// value range, with hue wrap-around.
+    ///
+    /// The image must hold HSV channels as produced by
+    /// `convert_color(ColorSpace::Hsv)`, with hue halved to 0-180. When the lower hue
+    /// is greater than the upper hue the range wraps around 180, so reds can be
+    /// selected with a hue range such as 170-10.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - Lower hue, saturation and value bounds
+    /// * `upper` - Upper hue, saturation and value bounds
+    ///
+    /// # Returns
+    ///
+    /// A mask with 255 where the pixel is within the range and 0 elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{ColorSpace, Matrix3};
+    ///
+    /// // Red, slightly purple red, and green
+    /// let image = Matrix3::new(3, 1, vec![255, 0, 0, 255, 0, 40, 0, 255, 0]);
+    /// let hsv = image.convert_color(ColorSpace::Hsv);
+    /// let red = hsv.in_range_hsv((170, 100, 100), (10, 255, 255));
+    /// assert_eq!(red.data(), &[255, 255, 0]);
+    /// ```
+    pub fn in_range_hsv(&self, lower: (u8, u8, u8), upper: (u8, u8, u8)) -> Matrix1 {
+        let saturation = lower.1..=upper.1;
+        let value = lower.2..=upper.2;
+        self.mask_pixels(|pixel| {
+            let hue = pixel[0];
+            let hue_selected = if lower.0 <= upper.0 {
+                (lower.0..=upper.0).contains(&hue)
+            } else {
+                hue >= lower.0 || hue <= upper.0
+            };
+            hue_selected && saturation.contains(&pixel[1]) && value.contains(&pixel[2])
+        })
+    }
+
+    /// Builds a mask from a per-pixel predicate.
+    fn mask_pixels<F>(&self, selected: F) -> Matrix1
+    where
+        F: Fn(&[u8]) -> bool + Send + Sync,
+    {
+        let mut mask = vec![0u8; self.width() * self.height()];
+        let select = |(out, pixel): (&mut u8, &[u8])| {
+            if selected(pixel) {
+                *out = MASK_ON;
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        mask.par_iter_mut()
+            .zip(self.data().par_chunks_exact(3))
+            .for_each(select);
+
+        #[cfg(not(feature = "parallel"))]
+        mask.iter_mut()
+            .zip(self.data().chunks_exact(3))
+            .for_each(select);
+
+        Matrix1::new(self.width(), self.height(), mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorSpace;
+
+    #[test]
+    fn test_in_range_gray() {
+        let image = Matrix1::new(3, 2, vec![0, 50, 100, 150, 200, 255]);
+        assert_eq!(image.in_range(50, 150).data(), &[0, 255, 255, 255, 0, 0]);
+        assert_eq!(image.in_range(0, 255).data(), &[255; 6]);
+        assert_eq!(image.in_range(200, 100).data(), &[0; 6]);
+    }
+
+    #[test]
+    fn test_in_range_rgb() {
+        let image = Matrix3::new(2, 2, vec![10, 20, 30, 10, 20, 31, 0, 0, 0, 255, 255, 255]);
+        let mask = image.in_range((10, 20, 30), (10, 20, 30));
+        assert_eq!(mask.dimensions(), (2, 2));
+        assert_eq!(mask.data(), &[255, 0, 0, 0]);
+        assert_eq!(image.in_range((0, 0, 0), (255, 255, 255)).data(), &[255; 4]);
+    }
+
+    #[test]
+    fn test_in_range_hsv_wraps_hue() {
+        // Hues of 0°, 350°, 60° and 180°, plus a dark red and a gray
+        let image = Matrix3::new(
+            6,
+            1,
+            vec![
+                255, 0, 0, 255, 0, 43, 255, 255, 0, 0, 255, 255, 60, 0, 0, 128, 128, 128,
+            ],
+        );
+        let hsv = image.convert_color(ColorSpace::Hsv);
+
+        let red = hsv.in_range_hsv((170, 100, 100), (10, 255, 255));
+        assert_eq!(red.data(), &[255, 255, 0, 0, 0, 0]);
+
+        let yellow_to_cyan = hsv.in_range_hsv((20, 100, 100), (100, 255, 255));
+        assert_eq!(yellow_to_cyan.data(), &[0, 0, 255, 255, 0, 0]);
+    }
+}
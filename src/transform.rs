@@ -7,7 +7,7 @@ use alloc::vec;
 
 use crate::matrix::{Matrix1, Matrix3};
 use core::f32::consts::PI;
-use libm::{ceilf, cosf, floorf, roundf, sinf};
+use libm::{atan2f, ceilf, cosf, expf, floorf, logf, roundf, sinf, sqrtf};
 
 /// Interpolation method for resizing operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +29,63 @@ pub enum RotationAngle {
     Rotate270,
 }
 
+/// Axis across which an image is mirrored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlipDirection {
+    /// Mirror left-to-right
+    Horizontal,
+    /// Mirror top-to-bottom
+    Vertical,
+}
+
+/// The eight image orientations defined by the EXIF `Orientation` tag.
+///
+/// Each variant names the transformation that turns the stored pixels into the
+/// upright image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Stored upright (EXIF value 1)
+    Normal,
+    /// Mirrored horizontally (EXIF value 2)
+    FlipHorizontal,
+    /// Rotated 180 degrees (EXIF value 3)
+    Rotate180,
+    /// Mirrored vertically (EXIF value 4)
+    FlipVertical,
+    /// Mirrored across the main diagonal (EXIF value 5)
+    Transpose,
+    /// Needs a 90 degree clockwise rotation (EXIF value 6)
+    Rotate90,
+    /// Mirrored across the anti-diagonal (EXIF value 7)
+    Transverse,
+    /// Needs a 270 degree clockwise rotation (EXIF value 8)
+    Rotate270,
+}
+
+impl Orientation {
+    /// Converts an EXIF `Orientation` tag value (1-8).
+    ///
+    /// Returns `None` for values outside that range.
+    pub fn from_exif(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(Orientation::Normal),
+            2 => Some(Orientation::FlipHorizontal),
+            3 => Some(Orientation::Rotate180),
+            4 => Some(Orientation::FlipVertical),
+            5 => Some(Orientation::Transpose),
+            6 => Some(Orientation::Rotate90),
+            7 => Some(Orientation::Transverse),
+            8 => Some(Orientation::Rotate270),
+            _ => None,
+        }
+    }
+
+    /// Returns the EXIF `Orientation` tag value (1-8).
+    pub fn to_exif(self) -> u16 {
+        self as u16 + 1
+    }
+}
+
 /// Represents a rotation angle with arbitrary value.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Rotation {
@@ -56,6 +113,120 @@ impl Rotation {
     }
 }
 
+/// Radius mapping used by polar warps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolarMapping {
+    /// Radius grows linearly along the x-axis of the polar image
+    Linear,
+    /// Radius grows logarithmically along the x-axis, giving more columns to the center
+    LogPolar,
+}
+
+impl PolarMapping {
+    /// Maps a polar column in `[0, width)` to a radius in `[0, max_radius)`.
+    fn column_to_radius(self, column: f32, width: f32, max_radius: f32) -> f32 {
+        match self {
+            PolarMapping::Linear => column * max_radius / width,
+            PolarMapping::LogPolar => expf(column * logf(max_radius + 1.0) / width) - 1.0,
+        }
+    }
+
+    /// Maps a radius in `[0, max_radius)` back to a polar column in `[0, width)`.
+    fn radius_to_column(self, radius: f32, width: f32, max_radius: f32) -> f32 {
+        match self {
+            PolarMapping::Linear => radius * width / max_radius,
+            PolarMapping::LogPolar => logf(radius + 1.0) * width / logf(max_radius + 1.0),
+        }
+    }
+}
+
+/// Computes the cartesian source location of a pixel in a polar image.
+fn polar_source(
+    x: usize,
+    y: usize,
+    polar_size: (usize, usize),
+    center: (f32, f32),
+    max_radius: f32,
+    mapping: PolarMapping,
+) -> (f32, f32) {
+    let (polar_width, polar_height) = polar_size;
+    let radius = mapping.column_to_radius(x as f32, polar_width as f32, max_radius);
+    let angle = y as f32 * 2.0 * PI / polar_height as f32;
+    (
+        center.0 + radius * cosf(angle),
+        center.1 + radius * sinf(angle),
+    )
+}
+
+/// Computes the polar source location of a pixel in a cartesian image.
+fn cartesian_source(
+    x: usize,
+    y: usize,
+    polar_size: (usize, usize),
+    center: (f32, f32),
+    max_radius: f32,
+    mapping: PolarMapping,
+) -> (f32, f32) {
+    let (polar_width, polar_height) = polar_size;
+    let dx = x as f32 - center.0;
+    let dy = y as f32 - center.1;
+    let radius = sqrtf(dx * dx + dy * dy);
+    let mut angle = atan2f(dy, dx);
+    if angle < 0.0 {
+        angle += 2.0 * PI;
+    }
+    (
+        mapping.radius_to_column(radius, polar_width as f32, max_radius),
+        angle * polar_height as f32 / (2.0 * PI),
+    )
+}
+
+/// Finds the polar pixels and weights that make up a sample at (`x`, `y`).
+///
+/// Rows hold angles, so the row index wraps around and the last row is interpolated with
+/// the first. Returns `None` when the radius falls outside the polar image.
+fn polar_taps(
+    x: f32,
+    y: f32,
+    polar_size: (usize, usize),
+    method: InterpolationMethod,
+) -> Option<[(usize, f32); 4]> {
+    let (width, height) = polar_size;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let y = y - floorf(y / height as f32) * height as f32;
+
+    match method {
+        InterpolationMethod::NearestNeighbor => {
+            let ix = roundf(x) as isize;
+            if ix < 0 || ix >= width as isize {
+                return None;
+            }
+            let ix = ix as usize;
+            let iy = roundf(y) as usize % height;
+            Some([(iy * width + ix, 1.0), (0, 0.0), (0, 0.0), (0, 0.0)])
+        }
+        InterpolationMethod::Bilinear => {
+            if x < 0.0 || x >= width as f32 {
+                return None;
+            }
+            let x1 = floorf(x) as usize;
+            let x2 = (x1 + 1).min(width - 1);
+            let y1 = floorf(y) as usize % height;
+            let y2 = (y1 + 1) % height;
+            let dx = x - floorf(x);
+            let dy = y - floorf(y);
+            Some([
+                (y1 * width + x1, (1.0 - dx) * (1.0 - dy)),
+                (y1 * width + x2, dx * (1.0 - dy)),
+                (y2 * width + x1, (1.0 - dx) * dy),
+                (y2 * width + x2, dx * dy),
+            ])
+        }
+    }
+}
+
 impl Matrix1 {
     /// Resizes the image to the specified dimensions.
     ///
@@ -274,6 +445,83 @@ impl Matrix1 {
         Matrix1::new(new_width, new_height, data)
     }
 
+    /// Mirrors the image horizontally or vertically.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Axis to mirror across
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 with the mirrored image data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix1, FlipDirection};
+    ///
+    /// let image = Matrix1::zeros(640, 480);
+    /// let mirrored = image.flip(FlipDirection::Horizontal);
+    /// assert_eq!(mirrored.dimensions(), (640, 480));
+    /// ```
+    pub fn flip(&self, direction: FlipDirection) -> Self {
+        let mut data = vec![0u8; self.width() * self.height()];
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let src_idx = y * self.width() + x;
+                let (new_x, new_y) = match direction {
+                    FlipDirection::Horizontal => (self.width() - 1 - x, y),
+                    FlipDirection::Vertical => (x, self.height() - 1 - y),
+                };
+                let dst_idx = new_y * self.width() + new_x;
+                data[dst_idx] = self.data()[src_idx];
+            }
+        }
+
+        Matrix1::new(self.width(), self.height(), data)
+    }
+
+    /// Transforms the image from a stored orientation to upright.
+    ///
+    /// This is used to display photos whose EXIF orientation tag says the camera
+    /// was rotated, and is built from [`rotate`](Self::rotate) and [`flip`](Self::flip).
+    ///
+    /// # Arguments
+    ///
+    /// * `orientation` - The orientation the image is stored in
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 with the upright image data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix1, Orientation};
+    ///
+    /// // A portrait photo stored sideways by the camera
+    /// let stored = Matrix1::zeros(640, 480);
+    /// let upright = stored.apply_orientation(Orientation::Rotate90);
+    /// assert_eq!(upright.dimensions(), (480, 640));
+    /// ```
+    pub fn apply_orientation(&self, orientation: Orientation) -> Self {
+        match orientation {
+            Orientation::Normal => self.clone(),
+            Orientation::FlipHorizontal => self.flip(FlipDirection::Horizontal),
+            Orientation::Rotate180 => self.rotate(RotationAngle::Rotate180),
+            Orientation::FlipVertical => self.flip(FlipDirection::Vertical),
+            Orientation::Transpose => self
+                .flip(FlipDirection::Horizontal)
+                .rotate(RotationAngle::Rotate270),
+            Orientation::Rotate90 => self.rotate(RotationAngle::Rotate90),
+            Orientation::Transverse => self
+                .flip(FlipDirection::Horizontal)
+                .rotate(RotationAngle::Rotate90),
+            Orientation::Rotate270 => self.rotate(RotationAngle::Rotate270),
+        }
+    }
+
     /// Rotates the image by an arbitrary angle using interpolation.
     ///
     /// This method supports any rotation angle (not just 90-degree increments).
@@ -358,6 +606,124 @@ impl Matrix1 {
         Matrix1::new(new_width, new_height, data)
     }
 
+    /// Unwraps a circular region of the image into polar coordinates.
+    ///
+    /// Each column of the output corresponds to a radius and each row to an angle,
+    /// so a ring around `center` becomes a vertical stripe. Angles increase clockwise
+    /// from the positive x-axis, with row 0 at 0 degrees.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the polar image (number of radius samples)
+    /// * `height` - Height of the polar image (number of angle samples)
+    /// * `center_x` - X-coordinate of the polar origin
+    /// * `center_y` - Y-coordinate of the polar origin
+    /// * `max_radius` - Radius mapped to the right edge of the polar image
+    /// * `mapping` - Linear or log-polar radius mapping
+    /// * `method` - Interpolation method for sampling source pixels
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 of size `width` x `height` with the polar image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix1, InterpolationMethod, PolarMapping};
+    ///
+    /// let image = Matrix1::zeros(200, 200);
+    /// let polar = image.warp_polar(100, 360, 100.0, 100.0, 100.0, PolarMapping::Linear, InterpolationMethod::Bilinear);
+    /// assert_eq!(polar.width(), 100);
+    /// assert_eq!(polar.height(), 360);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn warp_polar(
+        &self,
+        width: usize,
+        height: usize,
+        center_x: f32,
+        center_y: f32,
+        max_radius: f32,
+        mapping: PolarMapping,
+        method: InterpolationMethod,
+    ) -> Self {
+        let mut data = vec![0u8; width * height];
+
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = polar_source(
+                    x,
+                    y,
+                    (width, height),
+                    (center_x, center_y),
+                    max_radius,
+                    mapping,
+                );
+
+                data[y * width + x] = match method {
+                    InterpolationMethod::NearestNeighbor => self.sample_nearest(src_x, src_y),
+                    InterpolationMethod::Bilinear => self.sample_bilinear(src_x, src_y),
+                };
+            }
+        }
+
+        Matrix1::new(width, height, data)
+    }
+
+    /// Maps a polar image produced by [`Matrix1::warp_polar`] back to cartesian coordinates.
+    ///
+    /// Pixels farther than `max_radius` from the center are set to 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the cartesian output image
+    /// * `height` - Height of the cartesian output image
+    /// * `center_x` - X-coordinate of the polar origin in the output image
+    /// * `center_y` - Y-coordinate of the polar origin in the output image
+    /// * `max_radius` - Radius that was mapped to the right edge of the polar image
+    /// * `mapping` - Radius mapping that was used to create the polar image
+    /// * `method` - Interpolation method for sampling polar pixels
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix1 of size `width` x `height` with the cartesian image.
+    #[allow(clippy::too_many_arguments)]
+    pub fn warp_polar_inverse(
+        &self,
+        width: usize,
+        height: usize,
+        center_x: f32,
+        center_y: f32,
+        max_radius: f32,
+        mapping: PolarMapping,
+        method: InterpolationMethod,
+    ) -> Self {
+        let mut data = vec![0u8; width * height];
+
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = cartesian_source(
+                    x,
+                    y,
+                    (self.width(), self.height()),
+                    (center_x, center_y),
+                    max_radius,
+                    mapping,
+                );
+
+                if let Some(taps) = polar_taps(src_x, src_y, self.dimensions(), method) {
+                    let val: f32 = taps
+                        .iter()
+                        .map(|&(idx, weight)| self.data()[idx] as f32 * weight)
+                        .sum();
+                    data[y * width + x] = roundf(val) as u8;
+                }
+            }
+        }
+
+        Matrix1::new(width, height, data)
+    }
+
     /// Sample pixel using nearest neighbor interpolation.
     fn sample_nearest(&self, x: f32, y: f32) -> u8 {
         let ix = roundf(x) as isize;
@@ -497,6 +863,84 @@ impl Matrix3 {
         Matrix3::new(new_width, new_height, data)
     }
 
+    /// Resizes the image, interpolating in linear light.
+    ///
+    /// [`Matrix3::resize`] interpolates the gamma-encoded sRGB bytes, which darkens
+    /// fine detail and high-contrast edges when downscaling. This variant decodes the
+    /// samples to linear light first, so the result keeps the image's brightness.
+    /// Nearest-neighbor resizing does not mix pixels and gives the same result as
+    /// [`Matrix3::resize`].
+    ///
+    /// # Arguments
+    ///
+    /// * `new_width` - Target width
+    /// * `new_height` - Target height
+    /// * `method` - Interpolation method to use
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the resized image data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, InterpolationMethod};
+    ///
+    /// // Halfway between black and white is a light gray in linear light, not 128
+    /// let edge = Matrix3::new(2, 1, vec![0, 0, 0, 255, 255, 255]);
+    /// let large = edge.resize_linear_light(4, 1, InterpolationMethod::Bilinear);
+    /// assert_eq!(large.get_pixel(2, 0), Some((188, 188, 188)));
+    /// let gamma = edge.resize(4, 1, InterpolationMethod::Bilinear);
+    /// assert_eq!(gamma.get_pixel(2, 0), Some((128, 128, 128)));
+    /// ```
+    pub fn resize_linear_light(
+        &self,
+        new_width: usize,
+        new_height: usize,
+        method: InterpolationMethod,
+    ) -> Self {
+        if method == InterpolationMethod::NearestNeighbor {
+            return self.resize_nearest(new_width, new_height);
+        }
+
+        let source = self.to_linear();
+        let mut data = vec![0f32; new_width * new_height * 3];
+
+        let x_ratio = (self.width() - 1) as f32 / new_width as f32;
+        let y_ratio = (self.height() - 1) as f32 / new_height as f32;
+
+        for y in 0..new_height {
+            for x in 0..new_width {
+                let src_x = x as f32 * x_ratio;
+                let src_y = y as f32 * y_ratio;
+
+                let x1 = floorf(src_x) as usize;
+                let y1 = floorf(src_y) as usize;
+                let x2 = (x1 + 1).min(self.width() - 1);
+                let y2 = (y1 + 1).min(self.height() - 1);
+
+                let dx = src_x - x1 as f32;
+                let dy = src_y - y1 as f32;
+
+                let dst_idx = (y * new_width + x) * 3;
+
+                for c in 0..3 {
+                    let p11 = source[(y1 * self.width() + x1) * 3 + c];
+                    let p12 = source[(y2 * self.width() + x1) * 3 + c];
+                    let p21 = source[(y1 * self.width() + x2) * 3 + c];
+                    let p22 = source[(y2 * self.width() + x2) * 3 + c];
+
+                    data[dst_idx + c] = p11 * (1.0 - dx) * (1.0 - dy)
+                        + p21 * dx * (1.0 - dy)
+                        + p12 * (1.0 - dx) * dy
+                        + p22 * dx * dy;
+                }
+            }
+        }
+
+        Matrix3::from_linear(&data, new_width, new_height)
+    }
+
     /// Crops the image to the specified rectangle.
     ///
     /// # Arguments
@@ -633,6 +1077,83 @@ impl Matrix3 {
         Matrix3::new(new_width, new_height, data)
     }
 
+    /// Mirrors the image horizontally or vertically.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Axis to mirror across
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the mirrored image data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, FlipDirection};
+    ///
+    /// let image = Matrix3::zeros(640, 480);
+    /// let mirrored = image.flip(FlipDirection::Horizontal);
+    /// assert_eq!(mirrored.dimensions(), (640, 480));
+    /// ```
+    pub fn flip(&self, direction: FlipDirection) -> Self {
+        let mut data = vec![0u8; self.width() * self.height() * 3];
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let src_idx = (y * self.width() + x) * 3;
+                let (new_x, new_y) = match direction {
+                    FlipDirection::Horizontal => (self.width() - 1 - x, y),
+                    FlipDirection::Vertical => (x, self.height() - 1 - y),
+                };
+                let dst_idx = (new_y * self.width() + new_x) * 3;
+                data[dst_idx..dst_idx + 3].copy_from_slice(&self.data()[src_idx..src_idx + 3]);
+            }
+        }
+
+        Matrix3::new(self.width(), self.height(), data)
+    }
+
+    /// Transforms the image from a stored orientation to upright.
+    ///
+    /// This is used to display photos whose EXIF orientation tag says the camera
+    /// was rotated, and is built from [`rotate`](Self::rotate) and [`flip`](Self::flip).
+    ///
+    /// # Arguments
+    ///
+    /// * `orientation` - The orientation the image is stored in
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the upright image data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, Orientation};
+    ///
+    /// // A portrait photo stored sideways by the camera
+    /// let stored = Matrix3::zeros(640, 480);
+    /// let upright = stored.apply_orientation(Orientation::Rotate90);
+    /// assert_eq!(upright.dimensions(), (480, 640));
+    /// ```
+    pub fn apply_orientation(&self, orientation: Orientation) -> Self {
+        match orientation {
+            Orientation::Normal => self.clone(),
+            Orientation::FlipHorizontal => self.flip(FlipDirection::Horizontal),
+            Orientation::Rotate180 => self.rotate(RotationAngle::Rotate180),
+            Orientation::FlipVertical => self.flip(FlipDirection::Vertical),
+            Orientation::Transpose => self
+                .flip(FlipDirection::Horizontal)
+                .rotate(RotationAngle::Rotate270),
+            Orientation::Rotate90 => self.rotate(RotationAngle::Rotate90),
+            Orientation::Transverse => self
+                .flip(FlipDirection::Horizontal)
+                .rotate(RotationAngle::Rotate90),
+            Orientation::Rotate270 => self.rotate(RotationAngle::Rotate270),
+        }
+    }
+
     /// Rotates the image by an arbitrary angle using interpolation.
     ///
     /// This method supports any rotation angle (not just 90-degree increments).
@@ -720,6 +1241,132 @@ impl Matrix3 {
         Matrix3::new(new_width, new_height, data)
     }
 
+    /// Unwraps a circular region of the image into polar coordinates.
+    ///
+    /// Each column of the output corresponds to a radius and each row to an angle,
+    /// so a ring around `center` becomes a vertical stripe. Angles increase clockwise
+    /// from the positive x-axis, with row 0 at 0 degrees.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the polar image (number of radius samples)
+    /// * `height` - Height of the polar image (number of angle samples)
+    /// * `center_x` - X-coordinate of the polar origin
+    /// * `center_y` - Y-coordinate of the polar origin
+    /// * `max_radius` - Radius mapped to the right edge of the polar image
+    /// * `mapping` - Linear or log-polar radius mapping
+    /// * `method` - Interpolation method for sampling source pixels
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 of size `width` x `height` with the polar image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, InterpolationMethod, PolarMapping};
+    ///
+    /// let image = Matrix3::zeros(200, 200);
+    /// let polar = image.warp_polar(100, 360, 100.0, 100.0, 100.0, PolarMapping::LogPolar, InterpolationMethod::Bilinear);
+    /// let restored = polar.warp_polar_inverse(200, 200, 100.0, 100.0, 100.0, PolarMapping::LogPolar, InterpolationMethod::Bilinear);
+    /// assert_eq!(restored.dimensions(), (200, 200));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn warp_polar(
+        &self,
+        width: usize,
+        height: usize,
+        center_x: f32,
+        center_y: f32,
+        max_radius: f32,
+        mapping: PolarMapping,
+        method: InterpolationMethod,
+    ) -> Self {
+        let mut data = vec![0u8; width * height * 3];
+
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = polar_source(
+                    x,
+                    y,
+                    (width, height),
+                    (center_x, center_y),
+                    max_radius,
+                    mapping,
+                );
+
+                let (r, g, b) = match method {
+                    InterpolationMethod::NearestNeighbor => self.sample_nearest(src_x, src_y),
+                    InterpolationMethod::Bilinear => self.sample_bilinear(src_x, src_y),
+                };
+
+                let idx = (y * width + x) * 3;
+                data[idx] = r;
+                data[idx + 1] = g;
+                data[idx + 2] = b;
+            }
+        }
+
+        Matrix3::new(width, height, data)
+    }
+
+    /// Maps a polar image produced by [`Matrix3::warp_polar`] back to cartesian coordinates.
+    ///
+    /// Pixels farther than `max_radius` from the center are set to black.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the cartesian output image
+    /// * `height` - Height of the cartesian output image
+    /// * `center_x` - X-coordinate of the polar origin in the output image
+    /// * `center_y` - Y-coordinate of the polar origin in the output image
+    /// * `max_radius` - Radius that was mapped to the right edge of the polar image
+    /// * `mapping` - Radius mapping that was used to create the polar image
+    /// * `method` - Interpolation method for sampling polar pixels
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 of size `width` x `height` with the cartesian image.
+    #[allow(clippy::too_many_arguments)]
+    pub fn warp_polar_inverse(
+        &self,
+        width: usize,
+        height: usize,
+        center_x: f32,
+        center_y: f32,
+        max_radius: f32,
+        mapping: PolarMapping,
+        method: InterpolationMethod,
+    ) -> Self {
+        let mut data = vec![0u8; width * height * 3];
+
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = cartesian_source(
+                    x,
+                    y,
+                    (self.width(), self.height()),
+                    (center_x, center_y),
+                    max_radius,
+                    mapping,
+                );
+
+                if let Some(taps) = polar_taps(src_x, src_y, self.dimensions(), method) {
+                    let idx = (y * width + x) * 3;
+                    for c in 0..3 {
+                        let val: f32 = taps
+                            .iter()
+                            .map(|&(src, weight)| self.data()[src * 3 + c] as f32 * weight)
+                            .sum();
+                        data[idx + c] = roundf(val) as u8;
+                    }
+                }
+            }
+        }
+
+        Matrix3::new(width, height, data)
+    }
+
     /// Sample pixel using nearest neighbor interpolation.
     fn sample_nearest(&self, x: f32, y: f32) -> (u8, u8, u8) {
         let ix = roundf(x) as isize;
@@ -1059,4 +1706,205 @@ mod tests {
         let max_val = rotated.data().iter().max().unwrap();
         assert!(*max_val > 200); // Should still have bright pixels
     }
+
+    #[test]
+    fn test_warp_polar_ring_becomes_column() {
+        // A bright ring of radius 20 around the center
+        let mut image = Matrix1::zeros(64, 64);
+        for y in 0..64 {
+            for x in 0..64 {
+                let dx = x as f32 - 32.0;
+                let dy = y as f32 - 32.0;
+                let r = sqrtf(dx * dx + dy * dy);
+                if (r - 20.0).abs() < 1.5 {
+                    image.set_pixel(x, y, 255);
+                }
+            }
+        }
+
+        let polar = image.warp_polar(
+            32,
+            90,
+            32.0,
+            32.0,
+            32.0,
+            PolarMapping::Linear,
+            InterpolationMethod::NearestNeighbor,
+        );
+        assert_eq!(polar.dimensions(), (32, 90));
+
+        // Column 20 maps to radius 20 and must be bright on every row
+        for y in 0..90 {
+            assert_eq!(polar.get_pixel(20, y), Some(255));
+            assert_eq!(polar.get_pixel(5, y), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_log_polar_mapping_roundtrip() {
+        let mapping = PolarMapping::LogPolar;
+        for column in [0.0, 10.0, 50.0, 99.0] {
+            let radius = mapping.column_to_radius(column, 100.0, 80.0);
+            let back = mapping.radius_to_column(radius, 100.0, 80.0);
+            assert!((back - column).abs() < 0.01);
+        }
+        assert!(mapping.column_to_radius(0.0, 100.0, 80.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_warp_polar_inverse_wraps_angle() {
+        // Row 0 holds angle 0; the last row is just below 2π
+        let mut polar = Matrix1::new(20, 8, vec![240; 160]);
+        for x in 0..20 {
+            polar.set_pixel(x, 0, 100);
+        }
+
+        // (15, 8) lies at about 338° from (10, 10), i.e. polar row 7.5
+        let sample = |method| {
+            polar
+                .warp_polar_inverse(21, 21, 10.0, 10.0, 10.0, PolarMapping::Linear, method)
+                .get_pixel(15, 8)
+                .unwrap()
+        };
+        assert_eq!(sample(InterpolationMethod::NearestNeighbor), 100);
+        let blended = sample(InterpolationMethod::Bilinear);
+        assert!((160..=176).contains(&blended), "{}", blended);
+
+        // A constant disc survives a round trip all the way around, with no wedge
+        let disc = Matrix3::new(41, 41, vec![180; 41 * 41 * 3]);
+        for method in [
+            InterpolationMethod::NearestNeighbor,
+            InterpolationMethod::Bilinear,
+        ] {
+            let restored = disc
+                .warp_polar(30, 90, 20.0, 20.0, 20.0, PolarMapping::Linear, method)
+                .warp_polar_inverse(41, 41, 20.0, 20.0, 20.0, PolarMapping::Linear, method);
+            for (x, y) in [(35, 19), (35, 18), (30, 17), (38, 20)] {
+                assert_eq!(
+                    restored.get_pixel(x, y),
+                    Some((180, 180, 180)),
+                    "{:?}",
+                    method
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_warp_polar_inverse_roundtrip_matrix3() {
+        let mut data = vec![0u8; 40 * 40 * 3];
+        for y in 0..40 {
+            for x in 0..40 {
+                let idx = (y * 40 + x) * 3;
+                data[idx] = 200;
+                data[idx + 1] = (x * 6) as u8;
+                data[idx + 2] = 50;
+            }
+        }
+        let image = Matrix3::new(40, 40, data);
+
+        let polar = image.warp_polar(
+            40,
+            360,
+            20.0,
+            20.0,
+            20.0,
+            PolarMapping::Linear,
+            InterpolationMethod::Bilinear,
+        );
+        let restored = polar.warp_polar_inverse(
+            40,
+            40,
+            20.0,
+            20.0,
+            20.0,
+            PolarMapping::Linear,
+            InterpolationMethod::Bilinear,
+        );
+
+        assert_eq!(restored.dimensions(), (40, 40));
+        // Inside the unwrapped disc the constant red channel survives
+        assert_eq!(restored.get_pixel(20, 20).unwrap().0, 200);
+        let (r, _, b) = restored.get_pixel(25, 18).unwrap();
+        assert!((r as i16 - 200).abs() <= 2);
+        assert!((b as i16 - 50).abs() <= 2);
+        // Corners lie outside max_radius
+        assert_eq!(restored.get_pixel(0, 0), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_flip() {
+        let image = Matrix1::new(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            image.flip(FlipDirection::Horizontal).data(),
+            &[3, 2, 1, 6, 5, 4]
+        );
+        assert_eq!(
+            image.flip(FlipDirection::Vertical).data(),
+            &[4, 5, 6, 1, 2, 3]
+        );
+
+        let rgb = Matrix3::new(2, 1, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            rgb.flip(FlipDirection::Horizontal).data(),
+            &[4, 5, 6, 1, 2, 3]
+        );
+        assert_eq!(rgb.flip(FlipDirection::Vertical).data(), rgb.data());
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        // 1 2 3
+        // 4 5 6
+        let image = Matrix1::new(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        let expected: [(Orientation, &[u8]); 8] = [
+            (Orientation::Normal, &[1, 2, 3, 4, 5, 6]),
+            (Orientation::FlipHorizontal, &[3, 2, 1, 6, 5, 4]),
+            (Orientation::Rotate180, &[6, 5, 4, 3, 2, 1]),
+            (Orientation::FlipVertical, &[4, 5, 6, 1, 2, 3]),
+            (Orientation::Transpose, &[1, 4, 2, 5, 3, 6]),
+            (Orientation::Rotate90, &[4, 1, 5, 2, 6, 3]),
+            (Orientation::Transverse, &[6, 3, 5, 2, 4, 1]),
+            (Orientation::Rotate270, &[3, 6, 2, 5, 1, 4]),
+        ];
+        for (orientation, data) in expected {
+            assert_eq!(
+                image.apply_orientation(orientation).data(),
+                data,
+                "{:?}",
+                orientation
+            );
+            assert_eq!(
+                Orientation::from_exif(orientation.to_exif()),
+                Some(orientation)
+            );
+        }
+        assert_eq!(Orientation::from_exif(0), None);
+
+        let rgb = Matrix3::new(2, 1, vec![1, 1, 1, 2, 2, 2]);
+        let upright = rgb.apply_orientation(Orientation::Rotate90);
+        assert_eq!(upright.dimensions(), (1, 2));
+        assert_eq!(upright.data(), &[1, 1, 1, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_resize_linear_light() {
+        let mut image = Matrix3::zeros(4, 4);
+        image.set_pixel(1, 2, 255, 0, 0);
+        image.set_pixel(3, 0, 30, 200, 90);
+
+        assert_eq!(
+            image
+                .resize_linear_light(8, 8, InterpolationMethod::NearestNeighbor)
+                .data(),
+            image
+                .resize(8, 8, InterpolationMethod::NearestNeighbor)
+                .data()
+        );
+
+        let uniform = Matrix3::new(3, 3, vec![77; 27]);
+        let resized = uniform.resize_linear_light(7, 5, InterpolationMethod::Bilinear);
+        assert_eq!(resized.dimensions(), (7, 5));
+        assert!(resized.data().iter().all(|&v| v == 77));
+    }
 }
@@ -0,0 +1,656 @@
+//! Frame sources and sinks for video-like inputs and outputs.
+//!
+//! [`FrameSource`] and [`FrameSink`] let processing code treat live and recorded
+//! inputs uniformly: a directory of numbered images, a Motion JPEG stream, a Y4M
+//! file and an in-memory vector all produce the same timestamped [`Frame`]s.
+//!
+//! This module requires the `std` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cv_rusty::video::{FrameSink, FrameSource, ImageSequenceSink, MjpegSource};
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! let file = BufReader::new(File::open("camera.mjpeg").unwrap());
+//! let mut source = MjpegSource::new(file, (25, 1));
+//! let mut sink = ImageSequenceSink::new("frames", "frame_", "png").unwrap();
+//!
+//! for frame in source.frames() {
+//!     let frame = frame.expect("Failed to read frame");
+//!     println!("Frame at {:?}", frame.timestamp);
+//!     sink.push_frame(&frame).expect("Failed to write frame");
+//! }
+//! ```
+
+use crate::io::{
+    decode_jpeg, read_image, write_image, write_jpeg_to, ImageError, ImageFormat, Y4mReader,
+    Y4mWriter,
+};
+use crate::matrix::Matrix3;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// An image with its presentation time.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// The frame contents
+    pub image: Matrix3,
+    /// Time of the frame relative to the start of the stream
+    pub timestamp: Duration,
+}
+
+impl Frame {
+    /// Creates a frame.
+    pub fn new(image: Matrix3, timestamp: Duration) -> Self {
+        Self { image, timestamp }
+    }
+}
+
+/// A producer of timestamped frames.
+pub trait FrameSource {
+    /// Reads the next frame.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(None)` once the source is exhausted, the frame on success, or an
+    /// `ImageError` if the frame cannot be read.
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError>;
+
+    /// Returns an iterator over the remaining frames.
+    fn frames(&mut self) -> Frames<'_, Self>
+    where
+        Self: Sized,
+    {
+        Frames { source: self }
+    }
+}
+
+/// A consumer of timestamped frames.
+pub trait FrameSink {
+    /// Writes a frame.
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError>;
+
+    /// Flushes any buffered output.
+    fn flush(&mut self) -> Result<(), ImageError> {
+        Ok(())
+    }
+}
+
+/// Iterator over the frames of a [`FrameSource`], returned by [`FrameSource::frames`].
+pub struct Frames<'a, S: ?Sized> {
+    source: &'a mut S,
+}
+
+impl<S: FrameSource + ?Sized> Iterator for Frames<'_, S> {
+    type Item = Result<Frame, ImageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next_frame().transpose()
+    }
+}
+
+impl<S: FrameSource + ?Sized> FrameSource for &mut S {
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError> {
+        (**self).next_frame()
+    }
+}
+
+impl<S: FrameSource + ?Sized> FrameSource for Box<S> {
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError> {
+        (**self).next_frame()
+    }
+}
+
+impl<S: FrameSink + ?Sized> FrameSink for &mut S {
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError> {
+        (**self).push_frame(frame)
+    }
+
+    fn flush(&mut self) -> Result<(), ImageError> {
+        (**self).flush()
+    }
+}
+
+impl<S: FrameSink + ?Sized> FrameSink for Box<S> {
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError> {
+        (**self).push_frame(frame)
+    }
+
+    fn flush(&mut self) -> Result<(), ImageError> {
+        (**self).flush()
+    }
+}
+
+/// Returns the timestamp of the frame at `index` for a (numerator, denominator) frame rate.
+fn frame_time(index: u64, frame_rate: (u32, u32)) -> Duration {
+    let (num, den) = (frame_rate.0.max(1) as u128, frame_rate.1 as u128);
+    let nanos = index as u128 * den * 1_000_000_000 / num;
+    Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+}
+
+/// Frames held in memory.
+///
+/// # Examples
+///
+/// ```
+/// use cv_rusty::Matrix3;
+/// use cv_rusty::video::{FrameSource, MemorySource};
+///
+/// let images = vec![Matrix3::zeros(4, 4), Matrix3::zeros(4, 4)];
+/// let mut source = MemorySource::from_images(images, (10, 1));
+/// let second = source.frames().nth(1).unwrap().unwrap();
+/// assert_eq!(second.timestamp.as_millis(), 100);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    frames: VecDeque<Frame>,
+}
+
+impl MemorySource {
+    /// Creates a source that yields the given frames in order.
+    pub fn new(frames: Vec<Frame>) -> Self {
+        Self {
+            frames: frames.into(),
+        }
+    }
+
+    /// Creates a source from images spaced evenly at the given frame rate.
+    pub fn from_images(images: Vec<Matrix3>, frame_rate: (u32, u32)) -> Self {
+        let frames = images
+            .into_iter()
+            .enumerate()
+            .map(|(i, image)| Frame::new(image, frame_time(i as u64, frame_rate)))
+            .collect();
+        Self { frames }
+    }
+
+    /// Returns the number of remaining frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if no frames remain.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl FrameSource for MemorySource {
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError> {
+        Ok(self.frames.pop_front())
+    }
+}
+
+impl FrameSink for Vec<Frame> {
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError> {
+        self.push(frame.clone());
+        Ok(())
+    }
+}
+
+/// Frames read from a directory of numbered image files.
+///
+/// Files are ordered by the number at the end of their name, so `frame_9.png`
+/// comes before `frame_10.png`. Files without a trailing number or with an
+/// unrecognized extension are ignored.
+#[derive(Debug, Clone)]
+pub struct ImageSequenceSource {
+    paths: Vec<PathBuf>,
+    next_index: usize,
+    frame_rate: (u32, u32),
+}
+
+impl ImageSequenceSource {
+    /// Lists the numbered images in a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory holding the images
+    /// * `frame_rate` - Frame rate used to compute timestamps, as a
+    ///   (numerator, denominator) fraction
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the source on success, or an `ImageError` if the
+    /// directory cannot be read.
+    pub fn open<P: AsRef<Path>>(dir: P, frame_rate: (u32, u32)) -> Result<Self, ImageError> {
+        let mut numbered = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() || ImageFormat::from_path(&path).is_none() {
+                continue;
+            }
+            if let Some(number) = trailing_number(&path) {
+                numbered.push((number, path));
+            }
+        }
+        numbered.sort();
+
+        Ok(Self {
+            paths: numbered.into_iter().map(|(_, path)| path).collect(),
+            next_index: 0,
+            frame_rate,
+        })
+    }
+
+    /// Returns the paths of all frames in playback order.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+/// Parses the number at the end of a file stem (`frame_0042.png` gives 42).
+fn trailing_number(path: &Path) -> Option<u64> {
+    let stem = path.file_stem()?.to_str()?;
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    stem[stem.len() - digits..].parse().ok()
+}
+
+impl FrameSource for ImageSequenceSource {
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError> {
+        let Some(path) = self.paths.get(self.next_index) else {
+            return Ok(None);
+        };
+        let image = read_image(path)?;
+        let timestamp = frame_time(self.next_index as u64, self.frame_rate);
+        self.next_index += 1;
+        Ok(Some(Frame::new(image, timestamp)))
+    }
+}
+
+/// Writes frames as numbered image files in a directory.
+///
+/// Files are named `{prefix}{index:06}.{extension}` starting at index 0, and the
+/// format is chosen from the extension as in [`write_image`].
+#[derive(Debug, Clone)]
+pub struct ImageSequenceSink {
+    dir: PathBuf,
+    prefix: String,
+    extension: String,
+    next_index: u64,
+}
+
+impl ImageSequenceSink {
+    /// Creates a sink, creating the directory if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to write the images to
+    /// * `prefix` - File name prefix placed before the frame number
+    /// * `extension` - File extension that selects the format (e.g. `png`)
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the sink on success, or an `ImageError` if the
+    /// directory cannot be created.
+    pub fn new<P: AsRef<Path>>(dir: P, prefix: &str, extension: &str) -> Result<Self, ImageError> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.to_string(),
+            extension: extension.to_string(),
+            next_index: 0,
+        })
+    }
+}
+
+impl FrameSink for ImageSequenceSink {
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError> {
+        let name = format!("{}{:06}.{}", self.prefix, self.next_index, self.extension);
+        write_image(self.dir.join(name), &frame.image)?;
+        self.next_index += 1;
+        Ok(())
+    }
+}
+
+/// Frames read from a Motion JPEG stream of concatenated JPEG images.
+///
+/// Bytes between images, such as the multipart headers in an HTTP camera dump,
+/// are skipped. Timestamps are derived from the frame rate because the stream
+/// carries no timing information.
+pub struct MjpegSource<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    eof: bool,
+    frames_read: u64,
+    frame_rate: (u32, u32),
+}
+
+/// Number of bytes requested from the reader at a time.
+const MJPEG_CHUNK: usize = 64 * 1024;
+
+impl MjpegSource<BufReader<File>> {
+    /// Opens a Motion JPEG file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the stream
+    /// * `frame_rate` - Frame rate used to compute timestamps, as a
+    ///   (numerator, denominator) fraction
+    pub fn open<P: AsRef<Path>>(path: P, frame_rate: (u32, u32)) -> Result<Self, ImageError> {
+        let file = File::open(path)?;
+        Ok(Self::new(BufReader::new(file), frame_rate))
+    }
+}
+
+impl<R: Read> MjpegSource<R> {
+    /// Creates a source over any Motion JPEG stream.
+    ///
+    /// See [`MjpegSource::open`] for the arguments.
+    pub fn new(reader: R, frame_rate: (u32, u32)) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            eof: false,
+            frames_read: 0,
+            frame_rate,
+        }
+    }
+}
+
+impl<R: Read> FrameSource for MjpegSource<R> {
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError> {
+        loop {
+            // Drop everything before the next start-of-image marker, keeping a trailing
+            // 0xFF that may be the first half of one
+            match self.buffer.windows(2).position(|w| w == [0xFF, 0xD8]) {
+                Some(start) => {
+                    self.buffer.drain(..start);
+                }
+                None => {
+                    let partial_marker = self.buffer.last() == Some(&0xFF);
+                    self.buffer.clear();
+                    if partial_marker {
+                        self.buffer.push(0xFF);
+                    }
+                }
+            }
+
+            if self.buffer.starts_with(&[0xFF, 0xD8]) {
+                match jpeg_length(&self.buffer) {
+                    JpegScan::Complete(len) => {
+                        // Consume the image before decoding so a corrupt frame is
+                        // skipped rather than returned again on the next call
+                        let jpeg: Vec<u8> = self.buffer.drain(..len).collect();
+                        let timestamp = frame_time(self.frames_read, self.frame_rate);
+                        self.frames_read += 1;
+                        let image = decode_jpeg(&jpeg)?;
+                        return Ok(Some(Frame::new(image, timestamp)));
+                    }
+                    // Not a real image; resynchronize after the false marker
+                    JpegScan::Invalid => {
+                        self.buffer.drain(..2);
+                        continue;
+                    }
+                    JpegScan::Incomplete => {}
+                }
+            }
+
+            if self.eof {
+                if self.buffer.starts_with(&[0xFF, 0xD8]) {
+                    return Err(ImageError::JpegDecode(
+                        "Motion JPEG stream ends inside an image".to_string(),
+                    ));
+                }
+                return Ok(None);
+            }
+
+            let len = self.buffer.len();
+            self.buffer.resize(len + MJPEG_CHUNK, 0);
+            let read = loop {
+                match self.reader.read(&mut self.buffer[len..]) {
+                    Ok(read) => break read,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e.into()),
+                }
+            };
+            self.buffer.truncate(len + read);
+            self.eof = read == 0;
+        }
+    }
+}
+
+/// Result of scanning for the end of a JPEG image.
+enum JpegScan {
+    /// The buffer starts with a complete image of this many bytes
+    Complete(usize),
+    /// More data is needed
+    Incomplete,
+    /// The data is not a valid JPEG marker structure
+    Invalid,
+}
+
+/// Finds the end of the JPEG image at the start of `data`.
+///
+/// Marker segments are skipped by their length, so embedded thumbnails cannot end
+/// the image early.
+fn jpeg_length(data: &[u8]) -> JpegScan {
+    let mut pos = 2;
+    loop {
+        let (Some(&prefix), Some(&marker)) = (data.get(pos), data.get(pos + 1)) else {
+            return JpegScan::Incomplete;
+        };
+        if prefix != 0xFF {
+            return JpegScan::Invalid;
+        }
+
+        match marker {
+            // Fill byte before a marker
+            0xFF => pos += 1,
+            0xD9 => return JpegScan::Complete(pos + 2),
+            0x01 | 0xD0..=0xD7 => pos += 2,
+            0x00 | 0xD8 => return JpegScan::Invalid,
+            _ => {
+                let Some(length) = data.get(pos + 2..pos + 4) else {
+                    return JpegScan::Incomplete;
+                };
+                pos += 2 + u16::from_be_bytes([length[0], length[1]]) as usize;
+
+                // Entropy-coded data follows a scan header up to the next marker
+                if marker == 0xDA {
+                    loop {
+                        let (Some(&byte), Some(&next)) = (data.get(pos), data.get(pos + 1)) else {
+                            return JpegScan::Incomplete;
+                        };
+                        if byte == 0xFF && next != 0x00 && !(0xD0..=0xD7).contains(&next) {
+                            break;
+                        }
+                        pos += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Writes frames as a Motion JPEG stream of concatenated JPEG images.
+pub struct MjpegSink<W: Write> {
+    writer: W,
+    quality: u8,
+}
+
+impl MjpegSink<BufWriter<File>> {
+    /// Creates a Motion JPEG file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path where the stream should be written
+    /// * `quality` - JPEG quality (1-100, where 100 is best quality)
+    pub fn create<P: AsRef<Path>>(path: P, quality: u8) -> Result<Self, ImageError> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file), quality))
+    }
+}
+
+impl<W: Write> MjpegSink<W> {
+    /// Creates a sink over any output.
+    ///
+    /// See [`MjpegSink::create`] for the arguments.
+    pub fn new(writer: W, quality: u8) -> Self {
+        Self { writer, quality }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> FrameSink for MjpegSink<W> {
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError> {
+        write_jpeg_to(&frame.image, &mut self.writer, self.quality)
+    }
+
+    fn flush(&mut self) -> Result<(), ImageError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<R: Read> FrameSource for Y4mReader<R> {
+    fn next_frame(&mut self) -> Result<Option<Frame>, ImageError> {
+        let timestamp = frame_time(self.frames_read(), self.frame_rate());
+        Ok(self.read_frame()?.map(|image| Frame::new(image, timestamp)))
+    }
+}
+
+impl<W: Write> FrameSink for Y4mWriter<W> {
+    fn push_frame(&mut self, frame: &Frame) -> Result<(), ImageError> {
+        self.write_frame(&frame.image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{encode_jpeg, encode_jpeg_with_options, ChromaSubsampling, JpegOptions};
+    use std::fs;
+
+    /// Reader that returns at most a few bytes per call.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(7);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_jpeg_length() {
+        let jpeg = encode_jpeg(&Matrix3::new(16, 8, vec![50; 384]), 80).unwrap();
+        assert!(matches!(jpeg_length(&jpeg), JpegScan::Complete(n) if n == jpeg.len()));
+        assert!(matches!(
+            jpeg_length(&jpeg[..jpeg.len() - 1]),
+            JpegScan::Incomplete
+        ));
+
+        // An embedded thumbnail must not end the image early
+        let options = JpegOptions::default().with_exif(vec![0xFF, 0xD8, 0xFF, 0xD9]);
+        let jpeg = encode_jpeg_with_options(&Matrix3::zeros(8, 8), &options).unwrap();
+        assert!(matches!(jpeg_length(&jpeg), JpegScan::Complete(n) if n == jpeg.len()));
+
+        assert!(matches!(
+            jpeg_length(&[0xFF, 0xD8, 0x12, 0x34]),
+            JpegScan::Invalid
+        ));
+    }
+
+    #[test]
+    fn test_mjpeg_roundtrip() {
+        let images: Vec<Matrix3> = (0..3)
+            .map(|i| Matrix3::new(8, 8, vec![40 + i * 60; 192]))
+            .collect();
+        let mut sink = MjpegSink::new(Vec::new(), 95);
+        for frame in MemorySource::from_images(images, (25, 1)).frames() {
+            sink.push_frame(&frame.unwrap()).unwrap();
+            // Multipart headers between images are skipped
+            sink.writer.extend_from_slice(b"\r\n--boundary\r\n\r\n");
+        }
+        sink.flush().unwrap();
+        let stream = sink.into_inner();
+
+        let mut source = MjpegSource::new(Trickle(&stream), (25, 1));
+        let frames: Vec<Frame> = source.frames().collect::<Result<_, _>>().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].timestamp, Duration::from_millis(80));
+        assert!((frames[2].image.data()[0] as i16 - 160).abs() <= 2);
+
+        let mut truncated = MjpegSource::new(&stream[..stream.len() / 2], (25, 1));
+        assert!(truncated.next_frame().unwrap().is_some());
+        assert!(truncated.frames().any(|frame| frame.is_err()));
+    }
+
+    #[test]
+    fn test_mjpeg_skips_corrupt_frame() {
+        // A well-formed marker sequence with no image data in it
+        let mut stream = vec![0xFF, 0xD8, 0xFF, 0xD9];
+        stream.extend(encode_jpeg(&Matrix3::new(8, 8, vec![100; 192]), 95).unwrap());
+
+        let mut source = MjpegSource::new(&stream[..], (25, 1));
+        assert!(source.next_frame().is_err());
+        let frame = source.next_frame().unwrap().unwrap();
+        assert_eq!(frame.timestamp, Duration::from_millis(40));
+        assert!((frame.image.data()[0] as i16 - 100).abs() <= 2);
+        assert!(source.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_image_sequence() {
+        let dir = "test_sequence_frames";
+        let mut sink = ImageSequenceSink::new(dir, "frame_", "png").unwrap();
+        for i in 0..3u8 {
+            let frame = Frame::new(Matrix3::new(2, 2, vec![i; 12]), Duration::ZERO);
+            sink.push_frame(&frame).unwrap();
+        }
+        // Numeric rather than lexicographic order, and unrelated files are ignored
+        fs::rename(
+            Path::new(dir).join("frame_000002.png"),
+            Path::new(dir).join("frame_10.png"),
+        )
+        .unwrap();
+        fs::write(Path::new(dir).join("notes.txt"), "not a frame").unwrap();
+
+        let mut source = ImageSequenceSource::open(dir, (30, 1)).unwrap();
+        assert_eq!(source.paths().len(), 3);
+        let frames: Vec<Frame> = source.frames().collect::<Result<_, _>>().unwrap();
+        let values: Vec<u8> = frames.iter().map(|f| f.image.data()[0]).collect();
+        assert_eq!(values, vec![0, 1, 2]);
+        assert_eq!(frames[1].timestamp, Duration::from_nanos(33_333_333));
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_sources_are_interchangeable() {
+        let images = vec![Matrix3::new(4, 2, vec![100; 24]); 2];
+
+        let mut y4m =
+            Y4mWriter::new(Vec::new(), 4, 2, (30000, 1001), ChromaSubsampling::Yuv444).unwrap();
+        let mut recorded = Vec::new();
+        for frame in MemorySource::from_images(images.clone(), (30000, 1001)).frames() {
+            let frame = frame.unwrap();
+            y4m.push_frame(&frame).unwrap();
+            recorded.push_frame(&frame).unwrap();
+        }
+        let stream = y4m.finish().unwrap();
+
+        let mut sources: Vec<Box<dyn FrameSource>> = vec![
+            Box::new(MemorySource::new(recorded)),
+            Box::new(Y4mReader::new(std::io::Cursor::new(stream)).unwrap()),
+        ];
+        for source in &mut sources {
+            let frames: Vec<Frame> = source.frames().collect::<Result<_, _>>().unwrap();
+            assert_eq!(frames.len(), 2);
+            assert_eq!(frames[1].timestamp, Duration::from_nanos(33_366_666));
+            assert_eq!(frames[1].image.dimensions(), (4, 2));
+        }
+    }
+}
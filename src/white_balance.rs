@@ -0,0 +1,379 @@
+//! White balance module.
+//!
+//! This module removes color casts from RGB images. Every method computes one gain
+//! per channel and scales the channels with lookup tables, so gains measured on one
+//! frame can be stored and reused for the rest of a stream:
+//!
+//! - [`WhiteBalanceMethod`] estimates gains automatically from the image content.
+//! - [`Matrix3::white_balance_from_patch`] uses a region known to be neutral, such as
+//!   a gray card.
+//! - [`Matrix3::adjust_temperature`] shifts the white point between two color
+//!   temperatures given in Kelvin.
+//!
+//! This module is `no_std` compatible and only requires the `alloc` crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use cv_rusty::{Matrix3, WhiteBalanceMethod};
+//!
+//! let image = Matrix3::zeros(320, 240);
+//! let gains = image.white_balance_gains(WhiteBalanceMethod::GrayWorld);
+//! let balanced = image.apply_white_balance(gains);
+//! ```
+
+use crate::matrix::Matrix3;
+
+/// Methods for estimating white balance gains from image content.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhiteBalanceMethod {
+    /// Assumes the scene averages to gray and scales each channel so the channel means
+    /// match. Works well on busy scenes, but is pulled off by large single-colored
+    /// areas such as sky or grass.
+    GrayWorld,
+    /// Assumes the brightest value of each channel belongs to a white surface and
+    /// scales it to 255. Sensitive to single hot pixels and clipped highlights.
+    WhitePatch,
+    /// Like [`WhiteBalanceMethod::WhitePatch`], but uses the given percentile (0.0 to
+    /// 100.0) of each channel instead of its maximum, which ignores specular
+    /// highlights and noise. Values around 99.0 are typical.
+    Percentile(f32),
+}
+
+/// Lowest color temperature accepted by [`Matrix3::adjust_temperature`], in Kelvin.
+const MIN_KELVIN: f32 = 1000.0;
+
+/// Highest color temperature accepted by [`Matrix3::adjust_temperature`], in Kelvin.
+const MAX_KELVIN: f32 = 40000.0;
+
+/// Builds a table that multiplies every value by `gain`, saturating at 255.
+fn gain_lut(gain: f32) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (value, out) in table.iter_mut().enumerate() {
+        *out = (value as f32 * gain + 0.5).min(255.0) as u8;
+    }
+    table
+}
+
+/// Sums the samples of each channel of interleaved RGB data.
+fn channel_sums(data: &[u8]) -> [u64; 3] {
+    let mut sums = [0u64; 3];
+    for pixel in data.chunks_exact(3) {
+        for (sum, &value) in sums.iter_mut().zip(pixel) {
+            *sum += value as u64;
+        }
+    }
+    sums
+}
+
+/// Returns gains that scale each channel mean to the average of the three means.
+///
+/// Channels without any signal keep a gain of 1.0.
+fn gray_world_gains(sums: [u64; 3]) -> [f32; 3] {
+    let gray = sums.iter().sum::<u64>() as f32 / 3.0;
+    sums.map(|sum| if sum == 0 { 1.0 } else { gray / sum as f32 })
+}
+
+/// Returns the smallest value at or below which `percentile` percent of the
+/// histogram's samples lie.
+fn percentile_value(histogram: &[u32], percentile: f32) -> u8 {
+    let total: u64 = histogram.iter().map(|&count| count as u64).sum();
+    let target = libm::ceil(total as f64 * percentile as f64 / 100.0).max(1.0) as u64;
+    let mut cumulative = 0u64;
+    for (value, &count) in histogram.iter().enumerate() {
+        cumulative += count as u64;
+        if cumulative >= target {
+            return value as u8;
+        }
+    }
+    255
+}
+
+/// Approximates the sRGB color of a black-body light source.
+///
+/// Uses Tanner Helland's curve fit to the CIE 1964 black-body colors, which is
+/// accurate to a few levels between 1000 K and 40000 K.
+fn kelvin_to_rgb(kelvin: f32) -> [f32; 3] {
+    let t = kelvin / 100.0;
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.69873 * libm::powf(t - 60.0, -0.13320476)
+    };
+    let green = if t <= 66.0 {
+        99.4708 * libm::logf(t) - 161.11957
+    } else {
+        288.12216 * libm::powf(t - 60.0, -0.07551485)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.51773 * libm::logf(t - 10.0) - 305.0448
+    };
+    [red, green, blue].map(|c| c.clamp(0.0, 255.0))
+}
+
+impl Matrix3 {
+    /// Estimates per-channel white balance gains from the image content.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The estimation method
+    ///
+    /// # Returns
+    ///
+    /// The red, green and blue gains, to be passed to
+    /// [`Matrix3::apply_white_balance`]. Channels that are entirely black keep a gain
+    /// of 1.0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`WhiteBalanceMethod::Percentile`] is outside 0.0 to 100.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, WhiteBalanceMethod};
+    ///
+    /// // A blue-tinted white and black
+    /// let image = Matrix3::new(2, 1, vec![200, 220, 250, 0, 0, 0]);
+    /// let [r, g, b] = image.white_balance_gains(WhiteBalanceMethod::WhitePatch);
+    /// assert!(r > g && g > b);
+    /// ```
+    pub fn white_balance_gains(&self, method: WhiteBalanceMethod) -> [f32; 3] {
+        let percentile = match method {
+            WhiteBalanceMethod::GrayWorld => return gray_world_gains(channel_sums(self.data())),
+            WhiteBalanceMethod::WhitePatch => 100.0,
+            WhiteBalanceMethod::Percentile(percentile) => {
+                assert!(
+                    (0.0..=100.0).contains(&percentile),
+                    "Percentile must be between 0 and 100"
+                );
+                percentile
+            }
+        };
+
+        self.histogram(256, None)
+            .map(|histogram| match percentile_value(&histogram, percentile) {
+                0 => 1.0,
+                white => 255.0 / white as f32,
+            })
+    }
+
+    /// Scales each channel by its own gain.
+    ///
+    /// Results are rounded and saturated to 255. Pixels are processed in parallel when
+    /// the `parallel` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `gains` - The red, green and blue gains, for example from
+    ///   [`Matrix3::white_balance_gains`]
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the gains applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a gain is negative or not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let image = Matrix3::new(1, 1, vec![100, 100, 100]);
+    /// let warmer = image.apply_white_balance([1.2, 1.0, 0.8]);
+    /// assert_eq!(warmer.data(), &[120, 100, 80]);
+    /// ```
+    pub fn apply_white_balance(&self, gains: [f32; 3]) -> Matrix3 {
+        assert!(
+            gains.iter().all(|gain| gain.is_finite() && *gain >= 0.0),
+            "Gains must be non-negative finite numbers"
+        );
+        let [red, green, blue] = gains.map(gain_lut);
+        self.apply_channel_luts([&red, &green, &blue])
+    }
+
+    /// Removes a color cast with gains estimated from the image content.
+    ///
+    /// This is a shorthand for [`Matrix3::white_balance_gains`] followed by
+    /// [`Matrix3::apply_white_balance`].
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The estimation method
+    ///
+    /// # Returns
+    ///
+    /// A new, white balanced Matrix3.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`WhiteBalanceMethod::Percentile`] is outside 0.0 to 100.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::{Matrix3, WhiteBalanceMethod};
+    ///
+    /// let image = Matrix3::new(2, 1, vec![80, 100, 120, 40, 50, 60]);
+    /// let balanced = image.auto_white_balance(WhiteBalanceMethod::GrayWorld);
+    /// assert_eq!(balanced.data(), &[100, 100, 100, 50, 50, 50]);
+    /// ```
+    pub fn auto_white_balance(&self, method: WhiteBalanceMethod) -> Matrix3 {
+        self.apply_white_balance(self.white_balance_gains(method))
+    }
+
+    /// Removes a color cast using a region that is known to be neutral gray or white.
+    ///
+    /// The gains make the average color of the region gray while keeping its average
+    /// brightness.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X coordinate of the region's top-left corner
+    /// * `y` - Y coordinate of the region's top-left corner
+    /// * `width` - Width of the region
+    /// * `height` - Height of the region
+    ///
+    /// # Returns
+    ///
+    /// A new, white balanced Matrix3, or `None` if the region is empty or extends
+    /// beyond the image bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// // The left pixel is a gray card photographed under bluish light
+    /// let image = Matrix3::new(2, 1, vec![90, 100, 110, 180, 100, 55]);
+    /// let balanced = image.white_balance_from_patch(0, 0, 1, 1).unwrap();
+    /// assert_eq!(balanced.data(), &[100, 100, 100, 200, 100, 50]);
+    /// ```
+    pub fn white_balance_from_patch(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<Matrix3> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let patch = self.crop(x, y, width, height)?;
+        let gains = gray_world_gains(channel_sums(patch.data()));
+        Some(self.apply_white_balance(gains))
+    }
+
+    /// Shifts the white point from one color temperature to another.
+    ///
+    /// Colors are rescaled as if the scene had been lit by a black-body source of
+    /// `to_kelvin` instead of `from_kelvin`: lowering the temperature warms the image
+    /// (more red, less blue) and raising it cools the image. The green gain is kept
+    /// at 1.0, so overall brightness changes little. Temperatures are clamped to
+    /// 1000-40000 K.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_kelvin` - Color temperature the image currently reflects, e.g. 6500.0
+    /// * `to_kelvin` - Color temperature to render the image at
+    ///
+    /// # Returns
+    ///
+    /// A new Matrix3 with the temperature shift applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cv_rusty::Matrix3;
+    ///
+    /// let image = Matrix3::new(1, 1, vec![128, 128, 128]);
+    /// let warmer = image.adjust_temperature(6500.0, 4500.0);
+    /// let (r, g, b) = warmer.get_pixel(0, 0).unwrap();
+    /// assert!(r > g && g > b);
+    /// ```
+    pub fn adjust_temperature(&self, from_kelvin: f32, to_kelvin: f32) -> Matrix3 {
+        let from = kelvin_to_rgb(from_kelvin.clamp(MIN_KELVIN, MAX_KELVIN));
+        let to = kelvin_to_rgb(to_kelvin.clamp(MIN_KELVIN, MAX_KELVIN));
+
+        // Dim primaries are clamped to one level to avoid dividing by zero
+        let mut gains = [0.0; 3];
+        for ((gain, to), from) in gains.iter_mut().zip(to).zip(from) {
+            *gain = to.max(1.0) / from.max(1.0);
+        }
+        let green = gains[1];
+        self.apply_white_balance(gains.map(|gain| gain / green))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gray_world_and_white_patch() {
+        let image = Matrix3::new(2, 1, vec![50, 100, 200, 25, 50, 100]);
+
+        let gains = image.white_balance_gains(WhiteBalanceMethod::GrayWorld);
+        let balanced = image.apply_white_balance(gains);
+        assert_eq!(balanced.data(), &[117, 117, 117, 58, 58, 58]);
+
+        let balanced = image.auto_white_balance(WhiteBalanceMethod::WhitePatch);
+        assert_eq!(balanced.data(), &[255, 255, 255, 128, 128, 128]);
+
+        // Black channels keep a neutral gain
+        let black = Matrix3::zeros(2, 2);
+        for method in [
+            WhiteBalanceMethod::GrayWorld,
+            WhiteBalanceMethod::WhitePatch,
+            WhiteBalanceMethod::Percentile(99.0),
+        ] {
+            assert_eq!(black.white_balance_gains(method), [1.0; 3]);
+        }
+    }
+
+    #[test]
+    fn test_percentile_ignores_highlights() {
+        // Nine bluish-white pixels and one clipped specular highlight
+        let mut data = [200, 220, 250].repeat(9);
+        data.extend_from_slice(&[255, 255, 255]);
+        let image = Matrix3::new(10, 1, data);
+
+        let max = image.white_balance_gains(WhiteBalanceMethod::WhitePatch);
+        assert_eq!(max, [1.0; 3]);
+
+        let balanced = image.auto_white_balance(WhiteBalanceMethod::Percentile(90.0));
+        assert_eq!(balanced.get_pixel(0, 0), Some((255, 255, 255)));
+    }
+
+    #[test]
+    fn test_patch_and_temperature() {
+        let image = Matrix3::new(2, 2, vec![90, 100, 110, 10, 10, 10, 0, 0, 0, 0, 0, 0]);
+        assert!(image.white_balance_from_patch(1, 1, 2, 1).is_none());
+        assert!(image.white_balance_from_patch(0, 0, 0, 1).is_none());
+        let balanced = image.white_balance_from_patch(0, 0, 1, 1).unwrap();
+        assert_eq!(balanced.get_pixel(0, 0), Some((100, 100, 100)));
+
+        let gray = Matrix3::new(1, 1, vec![128, 128, 128]);
+        assert_eq!(gray.adjust_temperature(5000.0, 5000.0).data(), gray.data());
+
+        let (r, g, b) = gray
+            .adjust_temperature(6500.0, 9000.0)
+            .get_pixel(0, 0)
+            .unwrap();
+        assert!(b > g && g >= r);
+
+        // Warming and cooling back roughly restores the original
+        let round_trip = gray
+            .adjust_temperature(6500.0, 4000.0)
+            .adjust_temperature(4000.0, 6500.0);
+        for (&a, &b) in round_trip.data().iter().zip(gray.data()) {
+            assert!(a.abs_diff(b) <= 2);
+        }
+    }
+}